@@ -1,9 +1,19 @@
-use ethers::types::U256;
-use ethers::types::{Block as EthersBlock, H64, H256, Transaction};
+use ethers::providers::{Middleware, StreamExt};
+use ethers::types::{Address, Filter, I256, Log, U256};
+use ethers::types::{Block as EthersBlock, H64, H256, OtherFields, Transaction, U64, Withdrawal};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
-use crate::{Evm, types::EvmError};
+use crate::{Evm, trade::TransferEvent, types::EvmError};
+use ethers::utils::{
+    keccak256,
+    rlp::{self, RlpStream},
+};
+
+/// Transaction types ethers cannot re-encode: EIP-4844 blob, EIP-7702 set-code and
+/// OP Stack deposit transactions
+const UNENCODABLE_TX_TYPES: [u64; 3] = [0x03, 0x04, 0x7e];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockInfo {
@@ -53,6 +63,37 @@ pub struct BlockInfo {
     pub mix_hash: Option<H256>,
     /// Uncles
     pub uncles: Vec<H256>,
+    /// L1 block number the block was derived from (Arbitrum `l1BlockNumber`), `None` on L1
+    #[serde(default)]
+    pub l1_block_number: Option<u64>,
+    /// Whether the node reported an author; `miner` is zero when it did not
+    #[serde(default = "default_true")]
+    pub has_author: bool,
+    /// Seal fields (pre-merge Parity/OpenEthereum)
+    #[serde(default)]
+    pub seal_fields: Vec<ethers::types::Bytes>,
+    /// Blob gas used (EIP-4844)
+    #[serde(default)]
+    pub blob_gas_used: Option<U256>,
+    /// Excess blob gas (EIP-4844)
+    #[serde(default)]
+    pub excess_blob_gas: Option<U256>,
+    /// Withdrawals root (EIP-4895)
+    #[serde(default)]
+    pub withdrawals_root: Option<H256>,
+    /// Validator withdrawals (EIP-4895)
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Parent beacon block root (EIP-4788)
+    #[serde(default)]
+    pub parent_beacon_block_root: Option<H256>,
+    /// Non-standard fields reported by the node, e.g. L2 extensions
+    #[serde(default)]
+    pub other: OtherFields,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl BlockInfo {
@@ -82,6 +123,15 @@ impl BlockInfo {
             nonce: block.nonce,
             mix_hash: block.mix_hash,
             uncles: block.uncles.clone(),
+            l1_block_number: Self::l1_block_number_from(&block.other),
+            has_author: block.author.is_some(),
+            seal_fields: block.seal_fields.clone(),
+            blob_gas_used: block.blob_gas_used,
+            excess_blob_gas: block.excess_blob_gas,
+            withdrawals_root: block.withdrawals_root,
+            withdrawals: block.withdrawals.clone(),
+            parent_beacon_block_root: block.parent_beacon_block_root,
+            other: block.other.clone(),
         }
     }
 
@@ -113,9 +163,83 @@ impl BlockInfo {
             nonce: block.nonce,
             mix_hash: block.mix_hash,
             uncles: block.uncles.clone(),
+            l1_block_number: Self::l1_block_number_from(&block.other),
+            has_author: block.author.is_some(),
+            seal_fields: block.seal_fields.clone(),
+            blob_gas_used: block.blob_gas_used,
+            excess_blob_gas: block.excess_blob_gas,
+            withdrawals_root: block.withdrawals_root,
+            withdrawals: block.withdrawals.clone(),
+            parent_beacon_block_root: block.parent_beacon_block_root,
+            other: block.other.clone(),
         }
     }
 
+    /// Convert back to an Ethers block with transaction hashes
+    ///
+    /// Inverse of `from_ethers_block`, so a `BlockInfo` cached as JSON can be handed
+    /// to code expecting the ethers type. Hashes are taken from `transactions` when
+    /// `transaction_hashes` is absent.
+    pub fn to_ethers_block(&self) -> EthersBlock<H256> {
+        let transactions = match (&self.transaction_hashes, &self.transactions) {
+            (Some(hashes), _) => hashes.clone(),
+            (None, Some(txs)) => txs.iter().map(|tx| tx.hash).collect(),
+            (None, None) => Vec::new(),
+        };
+        self.to_ethers_block_of(transactions)
+    }
+
+    /// Convert back to an Ethers block with full transactions
+    ///
+    /// Fails when the block was fetched without transaction bodies.
+    pub fn to_ethers_block_with_txs(&self) -> Result<EthersBlock<Transaction>, EvmError> {
+        let transactions = self.transactions.clone().ok_or_else(|| {
+            EvmError::InvalidInput(format!("Block {:?} has no transaction bodies", self.number))
+        })?;
+        Ok(self.to_ethers_block_of(transactions))
+    }
+
+    fn to_ethers_block_of<TX>(&self, transactions: Vec<TX>) -> EthersBlock<TX> {
+        EthersBlock {
+            hash: self.hash,
+            parent_hash: self.parent_hash,
+            uncles_hash: self.sha3_uncles,
+            author: self.has_author.then_some(self.miner),
+            state_root: self.state_root,
+            transactions_root: self.transactions_root,
+            receipts_root: self.receipts_root,
+            number: self.number.map(U64::from),
+            gas_used: self.gas_used,
+            gas_limit: self.gas_limit,
+            extra_data: self.extra_data.clone(),
+            logs_bloom: self.logs_bloom,
+            timestamp: self.timestamp,
+            difficulty: self.difficulty,
+            total_difficulty: self.total_difficulty,
+            seal_fields: self.seal_fields.clone(),
+            uncles: self.uncles.clone(),
+            transactions,
+            size: self.size,
+            mix_hash: self.mix_hash,
+            nonce: self.nonce,
+            base_fee_per_gas: self.base_fee_per_gas,
+            blob_gas_used: self.blob_gas_used,
+            excess_blob_gas: self.excess_blob_gas,
+            withdrawals_root: self.withdrawals_root,
+            withdrawals: self.withdrawals.clone(),
+            parent_beacon_block_root: self.parent_beacon_block_root,
+            other: self.other.clone(),
+        }
+    }
+
+    /// Read Arbitrum's `l1BlockNumber` from the non-standard block fields
+    fn l1_block_number_from(other: &OtherFields) -> Option<u64> {
+        other
+            .get_deserialized::<U64>("l1BlockNumber")
+            .and_then(|n| n.ok())
+            .map(|n| n.as_u64())
+    }
+
     /// Get block timestamp as u64 (if it fits)
     pub fn timestamp_u64(&self) -> Option<u64> {
         self.timestamp.try_into().ok()
@@ -145,6 +269,88 @@ impl BlockInfo {
         }
         Some((gas_used as f64 / gas_limit as f64) * 100.0)
     }
+
+    /// Get the fee recipient of the block.
+    ///
+    /// Post-merge the `miner` field no longer identifies a miner, it is the
+    /// `feeRecipient` chosen by whoever built the execution payload.
+    pub fn fee_recipient(&self) -> ethers::types::Address {
+        self.miner
+    }
+
+    /// Decode `extra_data` as UTF-8, which builders commonly use to tag their blocks
+    pub fn extra_data_text(&self) -> Option<String> {
+        let text = String::from_utf8(self.extra_data.to_vec()).ok()?;
+        let text = text.trim_matches(char::from(0)).trim();
+        if text.is_empty() || text.chars().any(|c| c.is_control()) {
+            return None;
+        }
+        Some(text.to_string())
+    }
+}
+
+/// Builder attribution for a PoS block built through MEV-Boost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuilderInfo {
+    /// Block number
+    pub block_number: u64,
+    /// Builder address (the block fee recipient that paid the proposer)
+    pub builder: ethers::types::Address,
+    /// Proposer fee recipient receiving the builder payment
+    pub proposer_fee_recipient: ethers::types::Address,
+    /// Value paid by the builder to the proposer
+    pub proposer_payment: U256,
+    /// Hash of the payment transaction
+    pub payment_tx_hash: H256,
+    /// Builder tag decoded from extra data, e.g. "beaverbuild.org"
+    pub builder_tag: Option<String>,
+}
+
+/// Fee and issuance breakdown of a single block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockEconomics {
+    /// Block number
+    pub block_number: u64,
+    /// Base fee per gas, `None` for pre-London blocks
+    pub base_fee_per_gas: Option<U256>,
+    /// Gas used by all transactions in the block
+    pub gas_used: U256,
+    /// Total execution fees paid by transactions (`gas_used * effective_gas_price`)
+    pub total_fees: U256,
+    /// Part of the fees burned through the base fee (`gas_used * base_fee`)
+    pub burned_fees: U256,
+    /// Part of the fees paid to the fee recipient as priority tips
+    pub priority_fees: U256,
+    /// Static block reward paid to the miner, including uncle inclusion rewards.
+    /// Zero after the Merge and on chains without an execution layer reward.
+    pub block_reward: U256,
+    /// Block reward minus burned fees; negative when the block burned more than it issued
+    pub net_issuance: I256,
+}
+
+impl BlockEconomics {
+    /// Total revenue of the fee recipient: block reward plus priority tips
+    pub fn proposer_revenue(&self) -> U256 {
+        self.block_reward + self.priority_fees
+    }
+}
+
+/// Event emitted by `BlockService::listen_block_events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BlockEvent {
+    /// Block extending the canonical chain
+    NewBlock(BlockInfo),
+    /// The chain switched to another branch above `common_ancestor`
+    Reorg {
+        common_ancestor: u64,
+        /// Previously emitted blocks that are no longer canonical, oldest first
+        orphaned: Vec<BlockInfo>,
+        /// Blocks of the new canonical branch, oldest first
+        adopted: Vec<BlockInfo>,
+        /// Transactions of `orphaned` that are not part of `adopted`, in block order;
+        /// consumers that applied them must roll them back
+        orphaned_transactions: Vec<H256>,
+    },
 }
 
 pub struct BlockService {
@@ -218,6 +424,318 @@ impl BlockService {
         Ok(block.map(|b| BlockInfo::from_ethers_block_with_txs(&b)))
     }
 
+    /// Infer the block builder and the proposer payment of a block.
+    ///
+    /// MEV-Boost builders set themselves as the block fee recipient and append a
+    /// plain ETH transfer from that address to the proposer as the last transaction
+    /// of the block. This method looks for exactly that shape: the last
+    /// transaction must be sent by the fee recipient, carry no calldata and have a
+    /// recipient.
+    ///
+    /// The heuristic has limits:
+    /// - locally built blocks (no MEV-Boost) and builders that pay the proposer
+    ///   by other means (e.g. setting the proposer as fee recipient directly)
+    ///   have no payment transaction and yield `None`;
+    /// - the relay that delivered the block is not recorded on-chain and cannot
+    ///   be derived from the block alone.
+    ///
+    /// # Example
+    /// ```rust
+    /// let block_service = evm.get_block_service();
+    /// if let Some(info) = block_service.get_block_builder_info(19_000_000).await? {
+    ///     println!("builder {:?} paid {} wei", info.builder, info.proposer_payment);
+    /// }
+    /// ```
+    pub async fn get_block_builder_info(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<BuilderInfo>, EvmError> {
+        let block = match self.get_block_with_txs(block_number).await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let fee_recipient = block.fee_recipient();
+        let last_tx = match block.transactions.as_ref().and_then(|txs| txs.last()) {
+            Some(tx) => tx,
+            None => return Ok(None),
+        };
+        let proposer = match last_tx.to {
+            Some(to) => to,
+            None => return Ok(None),
+        };
+        if last_tx.from != fee_recipient || !last_tx.input.is_empty() || proposer == fee_recipient {
+            return Ok(None);
+        }
+        Ok(Some(BuilderInfo {
+            block_number,
+            builder: fee_recipient,
+            proposer_fee_recipient: proposer,
+            proposer_payment: last_tx.value,
+            payment_tx_hash: last_tx.hash,
+            builder_tag: block.extra_data_text(),
+        }))
+    }
+
+    /// Fetch the timestamps of a set of blocks.
+    ///
+    /// Only block headers are requested, duplicated block numbers are fetched once,
+    /// and blocks unknown to the node are left out of the returned map.
+    ///
+    /// # Example
+    /// ```rust
+    /// let timestamps = block_service.get_timestamps(vec![19_000_000, 19_000_001]).await?;
+    /// println!("{:?}", timestamps.get(&19_000_000));
+    /// ```
+    pub async fn get_timestamps(&self, blocks: Vec<u64>) -> Result<HashMap<u64, u64>, EvmError> {
+        const CONCURRENCY: usize = 32;
+        let unique: Vec<u64> = blocks
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut timestamps = HashMap::with_capacity(unique.len());
+        for chunk in unique.chunks(CONCURRENCY) {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|&block_number| {
+                    let evm = self.evm.clone();
+                    tokio::spawn(async move {
+                        let block = evm
+                            .get_block_by_number(ethers::types::BlockNumber::Number(
+                                block_number.into(),
+                            ))
+                            .await?;
+                        Ok::<_, EvmError>((block_number, block.map(|b| b.timestamp)))
+                    })
+                })
+                .collect();
+            for handle in handles {
+                let (block_number, timestamp) = handle
+                    .await
+                    .map_err(|e| EvmError::RpcError(format!("Failed to fetch block: {}", e)))??;
+                if let Some(timestamp) = timestamp {
+                    timestamps.insert(block_number, timestamp.low_u64());
+                }
+            }
+        }
+        Ok(timestamps)
+    }
+
+    /// Compute the fee and issuance breakdown of a block.
+    ///
+    /// Receipts are fetched with a single `eth_getBlockReceipts` call, falling back to one
+    /// `eth_getTransactionReceipt` per transaction on nodes that do not support it. Pre-London
+    /// blocks have no base fee, so nothing is burned and all fees go to the miner. Blob fees
+    /// (EIP-4844) and rewards paid to uncle miners are not included.
+    ///
+    /// # Example
+    /// ```rust
+    /// let economics = block_service.get_block_economics(19_000_000).await?;
+    /// println!("burned {} wei, tips {} wei", economics.burned_fees, economics.priority_fees);
+    /// ```
+    pub async fn get_block_economics(&self, block_number: u64) -> Result<BlockEconomics, EvmError> {
+        let block = self
+            .evm
+            .get_block_with_txs(ethers::types::BlockNumber::Number(block_number.into()))
+            .await?
+            .ok_or_else(|| EvmError::RpcError(format!("Block {} not found", block_number)))?;
+        let tx_hashes: Vec<H256> = block.transactions.iter().map(|tx| tx.hash).collect();
+        let mut receipts_by_hash = self.evm.receipts_of(block_number, &tx_hashes).await?;
+        let receipts = tx_hashes
+            .iter()
+            .map(|tx_hash| {
+                receipts_by_hash.remove(tx_hash).ok_or_else(|| {
+                    EvmError::RpcError(format!("Receipt not found for transaction {:?}", tx_hash))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let base_fee = block.base_fee_per_gas;
+        let mut gas_used = U256::zero();
+        let mut total_fees = U256::zero();
+        let mut burned_fees = U256::zero();
+        for (tx, receipt) in block.transactions.iter().zip(receipts.iter()) {
+            let tx_gas = receipt.gas_used.unwrap_or_default();
+            let gas_price = receipt
+                .effective_gas_price
+                .unwrap_or_else(|| effective_gas_price(tx, base_fee));
+            gas_used += tx_gas;
+            total_fees += tx_gas * gas_price;
+            if let Some(base_fee) = base_fee {
+                burned_fees += tx_gas * base_fee;
+            }
+        }
+
+        let chain_id = self.evm.get_chain_id().await?;
+        let block_reward = ethereum_block_reward(chain_id, block_number, block.uncles.len());
+        Ok(BlockEconomics {
+            block_number,
+            base_fee_per_gas: base_fee,
+            gas_used,
+            total_fees,
+            burned_fees,
+            priority_fees: total_fees.saturating_sub(burned_fees),
+            block_reward,
+            net_issuance: I256::from_raw(block_reward) - I256::from_raw(burned_fees),
+        })
+    }
+
+    /// Net ERC20 balance change of every (holder, token) pair in a block
+    ///
+    /// Decodes all `Transfer(address,address,uint256)` logs of the block. The zero
+    /// address is not tracked as a holder, so mints only credit the receiver and burns
+    /// only debit the sender. ERC721 transfers share the signature but index the token
+    /// id, and are skipped. Pairs whose transfers cancel out are omitted.
+    ///
+    /// # Example
+    /// ```rust
+    /// let deltas = block_service.get_token_balance_deltas(19_000_000).await?;
+    /// for ((holder, token), delta) in deltas {
+    ///     println!("{:?} {:?} {}", holder, token, delta);
+    /// }
+    /// ```
+    pub async fn get_token_balance_deltas(
+        &self,
+        block: u64,
+    ) -> Result<HashMap<(Address, Address), I256>, EvmError> {
+        let filter = Filter::new()
+            .from_block(block)
+            .to_block(block)
+            .event("Transfer(address,address,uint256)");
+        let logs = self.evm.get_logs(filter).await?;
+        Ok(net_token_deltas(&logs))
+    }
+
+    /// Check that a transaction is part of a block and that the block's transaction list
+    /// hashes to the `transactions_root` in its header
+    ///
+    /// Returns an error when the block or the transaction cannot be found, `Ok(false)`
+    /// when the recomputed root (or the block hash) does not match the header, which
+    /// means the node served inconsistent data, as does a transaction that does not hash
+    /// to its own hash. Transaction types this crate cannot re-encode (blob, EIP-7702
+    /// and OP Stack deposit transactions) make the root uncheckable; such blocks are
+    /// accepted on inclusion alone, with a warning.
+    ///
+    /// # Example
+    /// ```
+    /// let verified = block_service.verify_transaction_in_block(tx_hash, block_hash).await?;
+    /// ```
+    pub async fn verify_transaction_in_block(
+        &self,
+        tx_hash: H256,
+        block_hash: H256,
+    ) -> Result<bool, EvmError> {
+        let block = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_block_with_txs(block_hash)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get block", e))
+            })
+            .await?
+            .ok_or_else(|| EvmError::InvalidInput(format!("Block {:?} not found", block_hash)))?;
+        if !block.transactions.iter().any(|tx| tx.hash == tx_hash) {
+            return Err(EvmError::InvalidInput(format!(
+                "Transaction {:?} is not included in block {:?}",
+                tx_hash, block_hash
+            )));
+        }
+        let unsupported = block.transactions.iter().any(|tx| {
+            tx.transaction_type
+                .is_some_and(|tx_type| UNENCODABLE_TX_TYPES.contains(&tx_type.as_u64()))
+        });
+        if unsupported {
+            log::warn!(
+                "Block {:?} contains transaction types that cannot be re-encoded, skipping transactions root check",
+                block_hash
+            );
+            return Ok(true);
+        }
+        let encoded: Vec<Vec<u8>> = block
+            .transactions
+            .iter()
+            .map(|tx| tx.rlp().to_vec())
+            .collect();
+        // A transaction that does not hash to its own hash was altered
+        if encoded
+            .iter()
+            .zip(&block.transactions)
+            .any(|(raw, tx)| H256::from(keccak256(raw)) != tx.hash)
+        {
+            return Ok(false);
+        }
+        Ok(
+            block.hash == Some(block_hash)
+                && ordered_trie_root(&encoded) == block.transactions_root,
+        )
+    }
+
+    /// Stream canonical blocks, reporting chain reorganizations as they are detected
+    ///
+    /// The last `max_depth` canonical blocks are remembered. A head whose parent is not
+    /// the remembered block at that height is walked back by parent hash until it joins
+    /// the remembered chain, and the replaced blocks are reported in one
+    /// `BlockEvent::Reorg`. Heads that skip blocks are filled in the same way, as
+    /// `NewBlock` events. A fork deeper than `max_depth` cannot be resolved: it is logged
+    /// and the stream restarts from the new head.
+    ///
+    /// # Example
+    /// ```
+    /// let mut events = block_service.listen_block_events(64).await?;
+    /// while let Ok(event) = events.recv().await {
+    ///     if let BlockEvent::Reorg { orphaned_transactions, .. } = event {
+    ///         for tx_hash in orphaned_transactions {
+    ///             indexer.revert(tx_hash);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn listen_block_events(
+        &self,
+        max_depth: usize,
+    ) -> Result<tokio::sync::broadcast::Receiver<BlockEvent>, EvmError> {
+        if max_depth == 0 {
+            return Err(EvmError::InvalidInput(
+                "max_depth must be at least 1 to detect reorgs".to_string(),
+            ));
+        }
+        let (sender, receiver) = tokio::sync::broadcast::channel(1024);
+        let evm = self.evm.clone();
+        tokio::spawn(async move {
+            let mut stream = match evm.provider().watch_blocks().await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::error!("Failed to create block watcher: {}", e);
+                    return;
+                }
+            };
+            let mut canonical: BTreeMap<u64, BlockInfo> = BTreeMap::new();
+            while let Some(head_hash) = stream.next().await {
+                if canonical
+                    .values()
+                    .any(|block| block.hash == Some(head_hash))
+                {
+                    continue;
+                }
+                let events = match follow_head(&evm, &mut canonical, head_hash, max_depth).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log::error!("Failed to follow head {:?}: {}", head_hash, e);
+                        continue;
+                    }
+                };
+                for event in events {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
     /// Get multiple blocks in a range
     pub async fn get_blocks_in_range(
         &self,
@@ -244,6 +762,237 @@ impl BlockService {
     }
 }
 
+/// Attach the branch ending at `head_hash` to the remembered canonical chain and return
+/// the events it produces
+async fn follow_head(
+    evm: &Evm,
+    canonical: &mut BTreeMap<u64, BlockInfo>,
+    head_hash: H256,
+    max_depth: usize,
+) -> Result<Vec<BlockEvent>, EvmError> {
+    let mut branch = vec![fetch_block_info(evm, head_hash).await?];
+    loop {
+        let first = &branch[branch.len() - 1];
+        let number = first
+            .number
+            .ok_or_else(|| EvmError::RpcError(format!("Block {:?} has no number", first.hash)))?;
+        let Some(&oldest) = canonical.keys().next() else {
+            break;
+        };
+        let joins = number
+            .checked_sub(1)
+            .and_then(|parent| canonical.get(&parent))
+            .is_some_and(|parent| parent.hash == Some(first.parent_hash));
+        if joins {
+            break;
+        }
+        if number <= oldest {
+            log::warn!(
+                "Reorg deeper than {} blocks at block #{}, restarting from the new head",
+                max_depth,
+                number
+            );
+            canonical.clear();
+            branch.truncate(1);
+            break;
+        }
+        let parent = fetch_block_info(evm, first.parent_hash).await?;
+        branch.push(parent);
+    }
+    branch.reverse();
+    let fork_point = branch[0].number.unwrap_or_default();
+    let orphaned: Vec<BlockInfo> = canonical.split_off(&fork_point).into_values().collect();
+    for block in &branch {
+        if let Some(number) = block.number {
+            canonical.insert(number, block.clone());
+        }
+    }
+    while canonical.len() > max_depth {
+        canonical.pop_first();
+    }
+    if orphaned.is_empty() {
+        return Ok(branch.into_iter().map(BlockEvent::NewBlock).collect());
+    }
+    Ok(vec![BlockEvent::Reorg {
+        common_ancestor: fork_point.saturating_sub(1),
+        orphaned_transactions: orphaned_transactions(&orphaned, &branch),
+        orphaned,
+        adopted: branch,
+    }])
+}
+
+async fn fetch_block_info(evm: &Evm, block_hash: H256) -> Result<BlockInfo, EvmError> {
+    evm.get_block_by_hash(block_hash)
+        .await?
+        .map(|block| BlockInfo::from_ethers_block(&block))
+        .ok_or_else(|| EvmError::RpcError(format!("Block {:?} not found", block_hash)))
+}
+
+/// Transactions of the orphaned blocks that the adopted branch did not include again
+fn orphaned_transactions(orphaned: &[BlockInfo], adopted: &[BlockInfo]) -> Vec<H256> {
+    let hashes = |blocks: &[BlockInfo]| -> Vec<H256> {
+        blocks
+            .iter()
+            .flat_map(|block| block.transaction_hashes.iter().flatten().copied())
+            .collect()
+    };
+    let kept: HashSet<H256> = hashes(adopted).into_iter().collect();
+    hashes(orphaned)
+        .into_iter()
+        .filter(|tx_hash| !kept.contains(tx_hash))
+        .collect()
+}
+
+/// Net `Transfer` logs into signed balance deltas keyed by (holder, token)
+///
+/// Values that do not fit an `I256` cannot come from a real balance and are skipped;
+/// deltas that would overflow saturate instead of wrapping around.
+pub(crate) fn net_token_deltas(logs: &[Log]) -> HashMap<(Address, Address), I256> {
+    let mut deltas: HashMap<(Address, Address), I256> = HashMap::new();
+    for log in logs {
+        let Ok(transfer) = TransferEvent::from_log(log) else {
+            continue;
+        };
+        if transfer.value > I256::MAX.into_raw() {
+            continue;
+        }
+        let value = I256::from_raw(transfer.value);
+        if !transfer.from.is_zero() {
+            let delta = deltas.entry((transfer.from, log.address)).or_default();
+            *delta = delta.checked_sub(value).unwrap_or(I256::MIN);
+        }
+        if !transfer.to.is_zero() {
+            let delta = deltas.entry((transfer.to, log.address)).or_default();
+            *delta = delta.checked_add(value).unwrap_or(I256::MAX);
+        }
+    }
+    deltas.retain(|_, delta| !delta.is_zero());
+    deltas
+}
+
+/// Root of a Merkle Patricia trie keyed by the RLP-encoded index of each value, as used
+/// for the transactions and receipts roots of a block header
+fn ordered_trie_root(values: &[Vec<u8>]) -> H256 {
+    trie_root(
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (rlp::encode(&index).to_vec(), value.as_slice()))
+            .collect(),
+    )
+}
+
+/// Root of a Merkle Patricia trie holding the given key/value pairs
+fn trie_root(entries: Vec<(Vec<u8>, &[u8])>) -> H256 {
+    if entries.is_empty() {
+        return H256::from(keccak256(rlp::NULL_RLP));
+    }
+    let mut entries: Vec<(Vec<u8>, &[u8])> = entries
+        .into_iter()
+        .map(|(key, value)| (key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect(), value))
+        .collect();
+    entries.sort();
+    H256::from(keccak256(trie_node(&entries, 0)))
+}
+
+/// RLP encoding of the trie node holding `entries`, whose nibble keys share their first
+/// `depth` nibbles
+fn trie_node(entries: &[(Vec<u8>, &[u8])], depth: usize) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    if let [(key, value)] = entries {
+        stream.begin_list(2);
+        stream.append(&hex_prefix(&key[depth..], true).as_slice());
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+    let first = &entries[0].0;
+    let shared = (0..)
+        .take_while(|&i| {
+            entries
+                .iter()
+                .all(|(key, _)| key.len() > depth + i && key[depth + i] == first[depth + i])
+        })
+        .count();
+    if shared > 0 {
+        stream.begin_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + shared], false).as_slice());
+        append_child(&mut stream, trie_node(entries, depth + shared));
+        return stream.out().to_vec();
+    }
+    stream.begin_list(17);
+    for nibble in 0..16u8 {
+        let group: Vec<(Vec<u8>, &[u8])> = entries
+            .iter()
+            .filter(|(key, _)| key.len() > depth && key[depth] == nibble)
+            .cloned()
+            .collect();
+        if group.is_empty() {
+            stream.append_empty_data();
+        } else {
+            append_child(&mut stream, trie_node(&group, depth + 1));
+        }
+    }
+    match entries.iter().find(|(key, _)| key.len() == depth) {
+        Some((_, value)) => stream.append(value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+/// Reference a child node: inlined when its encoding is shorter than a hash, hashed otherwise
+fn append_child(stream: &mut RlpStream, node: Vec<u8>) {
+    if node.len() < 32 {
+        stream.append_raw(&node, 1);
+    } else {
+        stream.append(&keccak256(&node).as_slice());
+    }
+}
+
+/// Hex-prefix encoding of a nibble path, flagging leaves and odd lengths
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+/// Price per gas a transaction actually paid, for receipts that omit `effectiveGasPrice`
+pub(crate) fn effective_gas_price(tx: &Transaction, base_fee: Option<U256>) -> U256 {
+    match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas, base_fee) {
+        (Some(max_fee), Some(max_priority_fee), Some(base_fee)) => {
+            max_fee.min(base_fee + max_priority_fee)
+        }
+        _ => tx.gas_price.unwrap_or_default(),
+    }
+}
+
+/// Static Ethereum mainnet block reward, including 1/32 of it per included uncle.
+/// Other chains pay no execution layer reward, and neither does mainnet after the Merge.
+fn ethereum_block_reward(chain_id: u64, block_number: u64, uncles: usize) -> U256 {
+    const BYZANTIUM: u64 = 4_370_000;
+    const CONSTANTINOPLE: u64 = 7_280_000;
+    const MERGE: u64 = 15_537_394;
+    if chain_id != 1 || block_number >= MERGE {
+        return U256::zero();
+    }
+    let ether = U256::exp10(18);
+    let base = if block_number < BYZANTIUM {
+        ether * U256::from(5u64)
+    } else if block_number < CONSTANTINOPLE {
+        ether * U256::from(3u64)
+    } else {
+        ether * U256::from(2u64)
+    };
+    base + base / U256::from(32u64) * U256::from(uncles)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::trade::{self, Trade};
@@ -253,6 +1002,95 @@ mod tests {
     use evm_client::EvmType;
     use std::sync::Arc;
 
+    #[test]
+    fn test_orphaned_transactions() {
+        let block = |txs: &[u64]| {
+            BlockInfo::from_ethers_block(&EthersBlock {
+                transactions: txs.iter().map(|&tx| H256::from_low_u64_be(tx)).collect(),
+                ..Default::default()
+            })
+        };
+        let orphaned = [block(&[1, 2]), block(&[3, 4])];
+        let adopted = [block(&[2, 4]), block(&[5])];
+        assert_eq!(
+            orphaned_transactions(&orphaned, &adopted),
+            vec![H256::from_low_u64_be(1), H256::from_low_u64_be(3)]
+        );
+        assert!(orphaned_transactions(&adopted, &[block(&[5, 4, 2])]).is_empty());
+    }
+
+    #[test]
+    fn test_trie_root() {
+        let root = |hex_str: &str| H256::from_slice(&hex::decode(hex_str).unwrap());
+        assert_eq!(
+            ordered_trie_root(&[]),
+            root("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+        );
+        let puppy: Vec<(Vec<u8>, &[u8])> = vec![
+            (b"do".to_vec(), b"verb"),
+            (b"dog".to_vec(), b"puppy"),
+            (b"doge".to_vec(), b"coin"),
+            (b"horse".to_vec(), b"stallion"),
+        ];
+        assert_eq!(
+            trie_root(puppy),
+            root("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+        let values: Vec<Vec<u8>> = (0..200u8).map(|i| vec![i; 40]).collect();
+        assert_eq!(
+            ordered_trie_root(&values),
+            root("8807025e51e6711456504fe17c63b47ccd2ae57da3b0cc45aaaef1bf63b703ad")
+        );
+    }
+
+    #[test]
+    fn test_block_info_round_trip() {
+        let block = EthersBlock::<H256> {
+            hash: Some(H256::repeat_byte(1)),
+            parent_hash: H256::repeat_byte(2),
+            number: Some(U64::from(19_000_000u64)),
+            timestamp: U256::from(1_700_000_000u64),
+            gas_limit: U256::from(30_000_000u64),
+            gas_used: U256::from(12_345_678u64),
+            base_fee_per_gas: Some(U256::from(20_000_000_000u64)),
+            withdrawals_root: Some(H256::repeat_byte(3)),
+            withdrawals: Some(Vec::new()),
+            transactions: vec![H256::repeat_byte(4), H256::repeat_byte(5)],
+            ..Default::default()
+        };
+        let info = BlockInfo::from_ethers_block(&block);
+        let cached: BlockInfo =
+            serde_json::from_str(&serde_json::to_string(&info).unwrap()).unwrap();
+        assert_eq!(cached.to_ethers_block(), block);
+        assert!(cached.to_ethers_block_with_txs().is_err());
+    }
+
+    #[test]
+    fn test_net_token_deltas() {
+        let token = Address::repeat_byte(0xaa);
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let transfer = |from: Address, to: Address, value: u64| {
+            trade::transfer_log(token, from, to, value.into())
+        };
+        let logs = vec![
+            transfer(Address::zero(), alice, 100), // mint
+            transfer(alice, bob, 30),
+            transfer(bob, Address::zero(), 10), // burn
+            transfer(bob, alice, 20),
+            transfer(alice, bob, 20),
+        ];
+        let deltas = net_token_deltas(&logs);
+        assert_eq!(deltas[&(alice, token)], I256::from(70));
+        assert_eq!(deltas[&(bob, token)], I256::from(20));
+        assert!(!deltas.contains_key(&(Address::zero(), token)));
+        assert_eq!(deltas.len(), 2);
+
+        // A value no real balance can hold is ignored instead of wrapping negative
+        let mut forged = transfer(alice, bob, 0);
+        forged.data = ethers::abi::encode(&[ethers::abi::Token::Uint(U256::MAX)]).into();
+        assert_eq!(net_token_deltas(&[logs, vec![forged]].concat()), deltas);
+    }
+
     #[tokio::test]
     async fn lisent_liquidity_last_transaction() {
         let evm = Arc::new(Evm::new(EvmType::ETHEREUM_MAINNET).await.unwrap());
@@ -274,7 +1112,7 @@ mod tests {
                         );
                         println!("received: {:?}", trade.get_received_token_eth());
                         println!("spent: {:?}", trade.get_spent_token_eth());
-                        println!("direction: {:?}", trade.getDirection());
+                        println!("direction: {:?}", trade.get_direction());
                     }
                 }
                 Ok(None) => println!("⚠️ Nont Block"),
@@ -1,12 +1,39 @@
 /// The abstraction layer module for smart contracts.
 use crate::Evm;
 use crate::EvmError;
+use ethers::abi::{Abi, Event, Token};
+use ethers::contract::abigen;
 use ethers::providers::Middleware;
-use ethers::types::{Address, Bytes, H256};
+use ethers::types::{Address, Bytes, Filter, H256, U256};
+use futures::{Stream, StreamExt, stream};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
+abigen!(
+    IERC165,
+    r#"[
+        function supportsInterface(bytes4 interfaceId) external view returns (bool)
+    ]"#
+);
+
+/// Etherscan v2 multichain API endpoint, selected per chain with the `chainid` parameter
+const EXPLORER_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// Block range requested per `eth_getLogs` call when reading a contract's history
+const LOG_CHUNK_SIZE: u64 = 2_000;
+
+/// ERC165 interface ids
+pub mod interface_ids {
+    pub const ERC165: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+    pub const ERC721: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+    pub const ERC721_METADATA: [u8; 4] = [0x5b, 0x5e, 0x13, 0x9f];
+    pub const ERC721_ENUMERABLE: [u8; 4] = [0x78, 0x0e, 0x9d, 0x63];
+    pub const ERC1155: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+    pub const ERC1155_METADATA_URI: [u8; 4] = [0x0e, 0x89, 0x34, 0x1c];
+    pub const ERC2981: [u8; 4] = [0x2a, 0x55, 0x20, 0x5a];
+}
+
 /// Basic contract information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractInfo {
@@ -124,11 +151,14 @@ impl ContractAnalyzer {
     /// ```
     pub async fn get_contract_bytecode(&self, address: Address) -> Result<Bytes, EvmError> {
         self.evm
-            .client
-            .provider
-            .get_code(address, None)
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_code(address, None)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get contract bytecode", e))
+            })
             .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get contract bytecode: {}", e)))
     }
 
     /// Retrieves deployed bytecode (runtime bytecode)
@@ -146,13 +176,16 @@ impl ContractAnalyzer {
         for block_number in (start_block..=current_block).rev() {
             if let Some(block) = self
                 .evm
-                .client
-                .provider
-                .get_block(block_number)
-                .await
-                .map_err(|e| {
-                    EvmError::RpcError(format!("Failed to get block {}: {}", block_number, e))
-                })?
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
+                        .get_block(block_number)
+                        .await
+                        .map_err(|e| {
+                            EvmError::rpc(&format!("Failed to get block {}", block_number), e)
+                        })
+                })
+                .await?
             {
                 if let transactions = block.transactions {
                     for tx_hash in transactions {
@@ -202,14 +235,17 @@ impl ContractAnalyzer {
         slot: H256,
     ) -> Result<Option<H256>, EvmError> {
         self.evm
-            .client
-            .provider
-            .get_storage_at(address, slot, None)
-            .await
-            .map(Some)
-            .map_err(|e| {
-                EvmError::RpcError(format!("Failed to get storage at slot {:?}: {}", slot, e))
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_storage_at(address, slot, None)
+                    .await
+                    .map(Some)
+                    .map_err(|e| {
+                        EvmError::rpc(&format!("Failed to get storage at slot {:?}", slot), e)
+                    })
             })
+            .await
     }
 
     /// Analyzes storage layout of a contract
@@ -357,6 +393,10 @@ impl ContractAnalyzer {
     }
 
     /// Calculates similarity between two bytecodes
+    ///
+    /// Jaccard similarity of the opcode n-grams of both codes. Push immediates and the
+    /// trailing Solidity metadata are ignored, so clones that only differ in embedded
+    /// addresses, constants or compiler metadata still score close to 1.
     fn calculate_bytecode_similarity(&self, bytecode1: &Bytes, bytecode2: &Bytes) -> f64 {
         if bytecode1.is_empty() && bytecode2.is_empty() {
             return 1.0;
@@ -364,18 +404,76 @@ impl ContractAnalyzer {
         if bytecode1.is_empty() || bytecode2.is_empty() {
             return 0.0;
         }
-        let len1 = bytecode1.len();
-        let len2 = bytecode2.len();
-        let max_len = len1.max(len2) as f64;
-        if max_len == 0.0 {
-            return 1.0;
+        let ngrams1 = opcode_ngrams(bytecode1);
+        let ngrams2 = opcode_ngrams(bytecode2);
+        if ngrams1.is_empty() || ngrams2.is_empty() {
+            return if bytecode1 == bytecode2 { 1.0 } else { 0.0 };
         }
-        let common_prefix = bytecode1
-            .iter()
-            .zip(bytecode2.iter())
-            .take_while(|(a, b)| a == b)
-            .count();
-        common_prefix as f64 / max_len
+        let common = ngrams1.intersection(&ngrams2).count();
+        common as f64 / (ngrams1.len() + ngrams2.len() - common) as f64
+    }
+
+    /// Finds the candidates whose bytecode is at least `threshold` similar to `reference`
+    ///
+    /// Bytecodes are fetched at most `CLONE_FETCH_CONCURRENCY` at a time and compared
+    /// with the same n-gram similarity as `compare_contracts`. Matches are returned with
+    /// their score, most similar first; candidates without code never match, and a
+    /// candidate whose code cannot be fetched is logged and skipped.
+    ///
+    /// # Example
+    /// ```rust
+    /// let clones = analyzer.find_clones(usdt, new_tokens, 0.9).await?;
+    /// for (address, score) in clones {
+    ///     println!("{:?} is {:.1}% similar to USDT", address, score * 100.0);
+    /// }
+    /// ```
+    pub async fn find_clones(
+        &self,
+        reference: Address,
+        candidates: Vec<Address>,
+        threshold: f64,
+    ) -> Result<Vec<(Address, f64)>, EvmError> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(EvmError::InvalidInput(format!(
+                "Similarity threshold must be between 0 and 1, got {}",
+                threshold
+            )));
+        }
+        let reference_code = self.get_contract_bytecode(reference).await?;
+        if reference_code.is_empty() {
+            return Err(EvmError::InvalidInput(format!(
+                "{:?} has no contract code",
+                reference
+            )));
+        }
+        const CLONE_FETCH_CONCURRENCY: usize = 16;
+        let codes: Vec<(Address, Result<Bytes, EvmError>)> = stream::iter(candidates)
+            .map(
+                |candidate| async move { (candidate, self.get_contract_bytecode(candidate).await) },
+            )
+            .buffered(CLONE_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+        let mut clones: Vec<(Address, f64)> = codes
+            .into_iter()
+            .filter_map(|(candidate, code)| match code {
+                Ok(code) => Some((candidate, code)),
+                Err(e) => {
+                    log::warn!("Skipping clone candidate {:?}: {}", candidate, e);
+                    None
+                }
+            })
+            .filter(|(_, code)| !code.is_empty())
+            .map(|(candidate, code)| {
+                (
+                    candidate,
+                    self.calculate_bytecode_similarity(&reference_code, &code),
+                )
+            })
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        clones.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(clones)
     }
 
     /// Retrieves transaction statistics for a contract
@@ -411,6 +509,410 @@ impl ContractAnalyzer {
             last_seen_block: current_block,
         })
     }
+
+    /// Queries ERC165 `supportsInterface` for each interface id
+    ///
+    /// Calls are batched through Multicall3 when it is deployed on the chain and
+    /// issued one by one otherwise. A reverting call (e.g. a contract without
+    /// ERC165) counts as not supported.
+    ///
+    /// # Example
+    /// ```rust
+    /// use evm_sdk::contract::interface_ids;
+    ///
+    /// let supported = analyzer
+    ///     .supported_interfaces(address, vec![interface_ids::ERC721, interface_ids::ERC1155])
+    ///     .await?;
+    /// for (interface_id, is_supported) in supported {
+    ///     println!("0x{}: {}", hex::encode(interface_id), is_supported);
+    /// }
+    /// ```
+    pub async fn supported_interfaces(
+        &self,
+        address: Address,
+        interface_ids: Vec<[u8; 4]>,
+    ) -> Result<Vec<([u8; 4], bool)>, EvmError> {
+        let contract = IERC165::new(address, self.evm.provider().clone());
+        let calls = interface_ids
+            .iter()
+            .map(|interface_id| {
+                let data = contract.supports_interface(*interface_id).calldata();
+                (address, data.unwrap_or_default())
+            })
+            .collect();
+        if let Ok(results) = self.evm.multicall(calls).await {
+            return Ok(interface_ids
+                .into_iter()
+                .zip(results)
+                .map(|(interface_id, (success, data))| {
+                    let supported =
+                        success && data.len() == 32 && U256::from_big_endian(&data) == U256::one();
+                    (interface_id, supported)
+                })
+                .collect());
+        }
+        let mut supported = Vec::with_capacity(interface_ids.len());
+        for interface_id in interface_ids {
+            let is_supported = self
+                .evm
+                .read_with_retry(|| async {
+                    match IERC165::new(address, self.evm.provider().clone())
+                        .supports_interface(interface_id)
+                        .call()
+                        .await
+                    {
+                        Ok(is_supported) => Ok(is_supported),
+                        // A revert means the contract does not implement ERC-165
+                        Err(e) => match EvmError::contract("supportsInterface failed", &e) {
+                            EvmError::ContractError(_) => Ok(false),
+                            other => Err(other),
+                        },
+                    }
+                })
+                .await?;
+            supported.push((interface_id, is_supported));
+        }
+        Ok(supported)
+    }
+
+    /// Fetches the verified ABI of a contract from the block explorer (Etherscan v2 API)
+    ///
+    /// # Example
+    /// ```rust
+    /// let abi = analyzer.fetch_abi(address, "YOUR_API_KEY").await?;
+    /// println!("{} events", abi.events().count());
+    /// ```
+    pub async fn fetch_abi(&self, address: Address, api_key: &str) -> Result<Abi, EvmError> {
+        let chain_id = self.evm.get_chain_id().await?;
+        let response: serde_json::Value = reqwest::Client::new()
+            .get(EXPLORER_API_URL)
+            .query(&[
+                ("chainid", chain_id.to_string()),
+                ("module", "contract".to_string()),
+                ("action", "getabi".to_string()),
+                ("address", format!("{:?}", address)),
+                ("apikey", api_key.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| EvmError::ConnectionError(format!("Failed to reach explorer: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| EvmError::RpcError(format!("Invalid explorer response: {}", e)))?;
+        let result = response
+            .get("result")
+            .and_then(|result| result.as_str())
+            .unwrap_or_default();
+        if response.get("status").and_then(|status| status.as_str()) != Some("1") {
+            return Err(EvmError::ContractError(format!(
+                "Explorer returned no ABI for {:?}: {}",
+                address, result
+            )));
+        }
+        serde_json::from_str(result)
+            .map_err(|e| EvmError::ContractError(format!("Failed to parse ABI: {}", e)))
+    }
+
+    /// Reads a contract's events in a block range and decodes them with its verified ABI
+    ///
+    /// The ABI is fetched once from the explorer, then logs are queried in chunks of
+    /// `LOG_CHUNK_SIZE` blocks. Logs that do not match an event of the ABI (anonymous
+    /// events, events emitted through a proxy's implementation ABI, ...) are skipped.
+    ///
+    /// # Example
+    /// ```rust
+    /// let events = analyzer
+    ///     .get_decoded_events(address, 19_000_000, 19_010_000, "YOUR_API_KEY")
+    ///     .await?;
+    /// for event in events {
+    ///     println!("{} {}", event.block_number, event.display());
+    /// }
+    /// ```
+    pub async fn get_decoded_events(
+        &self,
+        address: Address,
+        from_block: u64,
+        to_block: u64,
+        api_key: &str,
+    ) -> Result<Vec<DecodedEvent>, EvmError> {
+        if from_block > to_block {
+            return Err(EvmError::InvalidInput(format!(
+                "from_block {} is after to_block {}",
+                from_block, to_block
+            )));
+        }
+        let events = events_by_topic(&self.fetch_abi(address, api_key).await?);
+        let mut decoded = Vec::new();
+        let mut chunk_start = from_block;
+        while chunk_start <= to_block {
+            let chunk_end = to_block.min(chunk_start + LOG_CHUNK_SIZE - 1);
+            decoded.extend(
+                self.get_decoded_chunk(address, &events, chunk_start, chunk_end)
+                    .await?,
+            );
+            chunk_start = chunk_end + 1;
+        }
+        Ok(decoded)
+    }
+
+    /// Streams every event a contract has emitted, from its creation block to the current head
+    ///
+    /// The creation block is located with `find_creation_block`, the ABI is fetched
+    /// once, and logs are then read lazily in windows of `LOG_CHUNK_SIZE` blocks, so
+    /// events are yielded in chronological order as the consumer polls. The first
+    /// error ends the stream.
+    ///
+    /// # Example
+    /// ```rust
+    /// use futures::{StreamExt, pin_mut};
+    ///
+    /// let events = analyzer.stream_all_events(address, "YOUR_API_KEY");
+    /// pin_mut!(events);
+    /// while let Some(event) = events.next().await {
+    ///     let event = event?;
+    ///     println!("{} {}", event.block_number, event.display());
+    /// }
+    /// ```
+    pub fn stream_all_events<'a>(
+        &'a self,
+        address: Address,
+        api_key: &'a str,
+    ) -> impl Stream<Item = Result<DecodedEvent, EvmError>> + 'a {
+        struct History {
+            events: HashMap<H256, Event>,
+            next_block: u64,
+            head: u64,
+            buffered: VecDeque<DecodedEvent>,
+        }
+        stream::unfold(None, move |state: Option<Option<History>>| async move {
+            let mut history = match state {
+                // the previous poll yielded an error
+                Some(None) => return None,
+                Some(Some(history)) => history,
+                None => {
+                    let setup = async {
+                        let next_block = self.find_creation_block(address).await?;
+                        let events = events_by_topic(&self.fetch_abi(address, api_key).await?);
+                        let head = self.evm.get_block_number().await?;
+                        Ok::<_, EvmError>(History {
+                            events,
+                            next_block,
+                            head,
+                            buffered: VecDeque::new(),
+                        })
+                    };
+                    match setup.await {
+                        Ok(history) => history,
+                        Err(e) => return Some((Err(e), Some(None))),
+                    }
+                }
+            };
+            loop {
+                if let Some(event) = history.buffered.pop_front() {
+                    return Some((Ok(event), Some(Some(history))));
+                }
+                if history.next_block > history.head {
+                    return None;
+                }
+                let chunk_end = history.head.min(history.next_block + LOG_CHUNK_SIZE - 1);
+                match self
+                    .get_decoded_chunk(address, &history.events, history.next_block, chunk_end)
+                    .await
+                {
+                    Ok(events) => history.buffered.extend(events),
+                    Err(e) => return Some((Err(e), Some(None))),
+                }
+                history.next_block = chunk_end + 1;
+            }
+        })
+    }
+
+    /// Finds the block a contract was deployed in by binary searching `eth_getCode`
+    ///
+    /// Needs a node serving historical state (archive node) for old contracts. A
+    /// contract that self-destructed and was redeployed reports its latest deployment
+    /// only if the code was absent at the probed heights.
+    ///
+    /// # Example
+    /// ```rust
+    /// let block = analyzer.find_creation_block(address).await?;
+    /// println!("Deployed in block {}", block);
+    /// ```
+    pub async fn find_creation_block(&self, address: Address) -> Result<u64, EvmError> {
+        let head = self.evm.get_block_number().await?;
+        if !self.has_code_at(address, head).await? {
+            return Err(EvmError::InvalidInput(format!(
+                "{:?} has no contract code",
+                address
+            )));
+        }
+        let (mut low, mut high) = (0, head);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.has_code_at(address, mid).await? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    async fn has_code_at(&self, address: Address, block_number: u64) -> Result<bool, EvmError> {
+        let code = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_code(address, Some(block_number.into()))
+                    .await
+                    .map_err(|e| {
+                        EvmError::rpc(&format!("Failed to get code at block {}", block_number), e)
+                    })
+            })
+            .await?;
+        Ok(!code.is_empty())
+    }
+
+    /// Reads and decodes one block window of a contract's logs
+    async fn get_decoded_chunk(
+        &self,
+        address: Address,
+        events: &HashMap<H256, Event>,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<DecodedEvent>, EvmError> {
+        let filter = Filter::new()
+            .address(address)
+            .from_block(from_block)
+            .to_block(to_block);
+        Ok(self
+            .evm
+            .get_logs(filter)
+            .await?
+            .into_iter()
+            .filter_map(|log| {
+                let event = log.topics.first().and_then(|topic| events.get(topic))?;
+                DecodedEvent::decode(event, log)
+            })
+            .collect())
+    }
+}
+
+/// Opcodes per n-gram in bytecode similarity
+const NGRAM_SIZE: usize = 4;
+
+/// Distinct opcode n-grams of a runtime bytecode, skipping push immediates and the
+/// CBOR metadata Solidity appends after the code
+fn opcode_ngrams(bytecode: &Bytes) -> HashSet<[u8; NGRAM_SIZE]> {
+    let mut code = bytecode.as_ref();
+    if let [.., high, low] = code {
+        let metadata_len = u16::from_be_bytes([*high, *low]) as usize + 2;
+        if metadata_len < code.len() && matches!(code[code.len() - metadata_len], 0xa1 | 0xa2) {
+            code = &code[..code.len() - metadata_len];
+        }
+    }
+    let mut opcodes = Vec::with_capacity(code.len());
+    let mut i = 0;
+    while i < code.len() {
+        let opcode = code[i];
+        opcodes.push(opcode);
+        // PUSH1..PUSH32 are followed by 1 to 32 bytes of data
+        i += 1 + if (0x60..=0x7f).contains(&opcode) {
+            (opcode - 0x5f) as usize
+        } else {
+            0
+        };
+    }
+    opcodes
+        .windows(NGRAM_SIZE)
+        .map(|window| window.try_into().expect("window has NGRAM_SIZE opcodes"))
+        .collect()
+}
+
+/// Non-anonymous events of an ABI keyed by their topic0
+fn events_by_topic(abi: &Abi) -> HashMap<H256, Event> {
+    abi.events()
+        .filter(|event| !event.anonymous)
+        .map(|event| (event.signature(), event.clone()))
+        .collect()
+}
+
+/// An event log decoded with the emitting contract's ABI
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedEvent {
+    pub address: Address,
+    /// Event name, e.g. `Transfer`
+    pub name: String,
+    /// Canonical signature, e.g. `Transfer(address,address,uint256)`
+    pub signature: String,
+    pub params: Vec<DecodedParam>,
+    pub transaction_hash: Option<H256>,
+    pub block_number: u64,
+    pub log_index: Option<u64>,
+}
+
+/// A named event parameter
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedParam {
+    pub name: String,
+    /// Solidity type, e.g. `uint256`
+    pub kind: String,
+    pub indexed: bool,
+    /// Human-readable value
+    pub display: String,
+    #[serde(skip)]
+    pub value: Token,
+}
+
+impl DecodedEvent {
+    pub(crate) fn decode(event: &Event, log: ethers::types::Log) -> Option<Self> {
+        let address = log.address;
+        let transaction_hash = log.transaction_hash;
+        let block_number = log.block_number.map(|n| n.as_u64()).unwrap_or_default();
+        let log_index = log.log_index.map(|i| i.as_u64());
+        let parsed = event.parse_log(log.into()).ok()?;
+        let params = event
+            .inputs
+            .iter()
+            .zip(parsed.params)
+            .map(|(input, param)| DecodedParam {
+                name: param.name,
+                kind: input.kind.to_string(),
+                indexed: input.indexed,
+                display: param.value.to_string(),
+                value: param.value,
+            })
+            .collect();
+        Some(Self {
+            address,
+            name: event.name.clone(),
+            signature: format!(
+                "{}({})",
+                event.name,
+                event
+                    .inputs
+                    .iter()
+                    .map(|input| input.kind.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            params,
+            transaction_hash,
+            block_number,
+            log_index,
+        })
+    }
+
+    /// One-line rendering, e.g. `Transfer(from=0x.., to=0x.., value=1000)`
+    pub fn display(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| format!("{}={}", param.name, param.display))
+            .collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
 }
 
 /// Characteristics and attributes of contract bytecode
@@ -464,6 +966,22 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_opcode_ngrams_ignore_push_data() {
+        // PUSH20 <address> PUSH1 0x00 SSTORE STOP, deployed with two different addresses
+        let code = |byte: u8| {
+            let mut code = vec![0x73];
+            code.extend([byte; 20]);
+            code.extend([0x60, 0x00, 0x55, 0x00]);
+            Bytes::from(code)
+        };
+        assert_eq!(opcode_ngrams(&code(0x11)), opcode_ngrams(&code(0x22)));
+        assert_eq!(
+            opcode_ngrams(&code(0x11)),
+            HashSet::from([[0x73, 0x60, 0x55, 0x00]])
+        );
+    }
+
     #[tokio::test]
     async fn test_analyze_bytecode_features() {
         let evm = Evm::new(evm_client::EvmType::ETHEREUM_MAINNET)
@@ -0,0 +1,132 @@
+use crate::{Evm, EvmError};
+use ethers::{
+    contract::abigen,
+    providers::{Http, Provider},
+    types::{Address, H256, U256},
+};
+use std::sync::Arc;
+
+abigen!(
+    IERC1155,
+    r#"[
+        function balanceOf(address account, uint256 id) external view returns (uint256)
+        function balanceOfBatch(address[] accounts, uint256[] ids) external view returns (uint256[])
+        function setApprovalForAll(address operator, bool approved) external
+        function isApprovedForAll(address account, address operator) external view returns (bool)
+        function safeTransferFrom(address from, address to, uint256 id, uint256 amount, bytes data) external
+        function safeBatchTransferFrom(address from, address to, uint256[] ids, uint256[] amounts, bytes data) external
+        function uri(uint256 id) external view returns (string)
+        event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+        event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)
+        event ApprovalForAll(address indexed account, address indexed operator, bool approved)
+        event URI(string value, uint256 indexed id)
+    ]"#
+);
+
+/// ERC1155 Service for interacting with multi-token contracts
+pub struct ERC1155Service {
+    evm: Arc<Evm>,
+}
+
+impl ERC1155Service {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self { evm }
+    }
+
+    /// Create ERC1155 contract instance
+    fn erc1155(&self, contract: Address) -> IERC1155<Provider<Http>> {
+        IERC1155::new(contract, self.evm.provider().clone())
+    }
+
+    /// Get the balance of token `id` held by `account`
+    pub async fn balance_of(
+        &self,
+        contract: Address,
+        account: Address,
+        id: U256,
+    ) -> Result<U256, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc1155(contract)
+                    .balance_of(account, id)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC1155 balance", &e))
+            })
+            .await
+    }
+
+    /// Get the balances of several `(account, id)` pairs in one call
+    ///
+    /// `accounts` and `ids` are paired by position and must have the same length; the
+    /// balances come back in the same order.
+    ///
+    /// # Example
+    /// ```
+    /// let items = ERC1155Service::new(evm.clone());
+    /// let balances = items
+    ///     .balance_of_batch(contract, vec![alice, alice], vec![U256::from(1), U256::from(2)])
+    ///     .await?;
+    /// ```
+    pub async fn balance_of_batch(
+        &self,
+        contract: Address,
+        accounts: Vec<Address>,
+        ids: Vec<U256>,
+    ) -> Result<Vec<U256>, EvmError> {
+        if accounts.len() != ids.len() {
+            return Err(EvmError::InvalidInput(format!(
+                "{} accounts for {} ids",
+                accounts.len(),
+                ids.len()
+            )));
+        }
+        self.evm
+            .read_with_retry(|| async {
+                self.erc1155(contract)
+                    .balance_of_batch(accounts.clone(), ids.clone())
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC1155 batch balances", &e))
+            })
+            .await
+    }
+
+    /// Get the metadata URI of token `id`
+    ///
+    /// The `{id}` placeholder of the standard is replaced by the id as 64 lowercase hex
+    /// digits, so the result can be fetched directly.
+    pub async fn uri(&self, contract: Address, id: U256) -> Result<String, EvmError> {
+        let uri = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc1155(contract)
+                    .uri(id)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC1155 URI", &e))
+            })
+            .await?;
+        let mut id_bytes = [0u8; 32];
+        id.to_big_endian(&mut id_bytes);
+        Ok(uri.replace("{id}", &hex::encode(id_bytes)))
+    }
+
+    /// Allow or revoke `operator` to transfer all tokens of the wallet in `contract`
+    pub async fn set_approval_for_all(
+        &self,
+        contract: Address,
+        operator: Address,
+        approved: bool,
+    ) -> Result<H256, EvmError> {
+        if self.evm.client.wallet.is_none() {
+            return Err(EvmError::WalletError("No wallet configured".to_string()));
+        }
+        let erc1155 = self.erc1155(contract);
+        let tx = erc1155.set_approval_for_all(operator, approved);
+        let pending_tx = tx.send().await.map_err(|e| {
+            EvmError::TransactionError(format!("Failed to set approval for all: {}", e))
+        })?;
+        Ok(pending_tx.tx_hash())
+    }
+}
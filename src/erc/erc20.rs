@@ -1,10 +1,22 @@
-use crate::{Evm, EvmClient, EvmError};
+use crate::{
+    Evm, EvmClient, EvmError,
+    global::dex_events,
+    tool::price::PriceOracle,
+    trade::{TRANSFER_LOG_CHUNK_SIZE, TransferEvent},
+};
 use ethers::{
+    abi::{Token, encode},
     contract::abigen,
-    providers::Provider,
-    types::{Address, H256, U256},
+    providers::{Provider, RawCall},
+    signers::Signer,
+    types::{
+        Address, BigEndianHash, Bytes, Filter, H160, H256, Log, TransactionRequest, U256, spoof,
+        transaction::eip2718::TypedTransaction,
+    },
+    utils::{format_units, keccak256},
 };
-use std::sync::Arc;
+use log::warn;
+use std::{sync::Arc, time::Duration};
 
 abigen!(
     IERC20,
@@ -23,6 +35,69 @@ abigen!(
     ]"#
 );
 
+abigen!(
+    IERC20Bytes32,
+    r#"[
+        function name() external view returns (bytes32)
+        function symbol() external view returns (bytes32)
+    ]"#
+);
+
+/// Conventional burn address `0x000000000000000000000000000000000000dEaD`.
+const DEAD_ADDRESS: Address = H160([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xde, 0xad,
+]);
+
+/// Holders whose balances `get_balances` reads per multicall.
+const BALANCE_BATCH_SIZE: usize = 500;
+
+/// How long `ensure_allowance` waits for an allowance reset to be mined.
+const APPROVAL_RESET_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Address the transfer probe contract is placed at during simulation.
+const PROBE_ADDRESS: Address = Address::repeat_byte(0xe2);
+
+/// Recipient of the simulated transfer; an otherwise unused address.
+const PROBE_RECIPIENT: Address = Address::repeat_byte(0xe3);
+
+/// Highest plain mapping slot tried when locating a token's balance storage.
+const MAX_BALANCE_SLOT: u64 = 20;
+
+/// ERC-7201 storage base of OpenZeppelin v5 `ERC20` (`_balances` is its first field).
+const OZ_V5_ERC20_STORAGE: [u8; 32] = [
+    0x52, 0xc6, 0x32, 0x47, 0xe1, 0xf4, 0x7d, 0xb1, 0x9d, 0x5c, 0xe0, 0x46, 0x00, 0x30, 0xc4, 0x97,
+    0xf0, 0x67, 0xca, 0x4c, 0xeb, 0xf7, 0x1b, 0xa9, 0x8e, 0xea, 0xda, 0xbe, 0x20, 0xba, 0xce, 0x00,
+];
+
+/// Runtime code of the probe contract. Called with `abi.encode(token, recipient, amount)`, it
+/// reads `balanceOf(recipient)`, calls `transfer(recipient, amount)` (reverting if the call
+/// reverts), reads `balanceOf(recipient)` again and returns both balances.
+const TRANSFER_PROBE_CODE: &str = "6370a0823160e01b60005260203560045260206080602460006000355afa15\
+    6100765763a9059cbb60e01b600052602035600452604035602452600060006044600060006000355af1156100\
+    76576370a0823160e01b600052602035600452602060a0602460006000355afa156100765760406080f35b3d60\
+    0060003e3d6000fd";
+
+/// How a token behaves when transferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TransferBehavior {
+    /// The recipient receives exactly the amount sent. `simulated` is `false` when the
+    /// transfer could not be simulated and the classification is only an assumption.
+    Standard { simulated: bool },
+    /// The recipient receives less than the amount sent; `fee_bps` is the shortfall in basis
+    /// points of the amount sent.
+    FeeOnTransfer { fee_bps: u32 },
+    /// Balances are not a plain stored amount (e.g. shares scaled by an index), or the
+    /// recipient received more than was sent.
+    Rebasing,
+}
+
+impl TransferBehavior {
+    /// Whether the classification comes from an actual simulation
+    pub fn is_certain(&self) -> bool {
+        !matches!(self, TransferBehavior::Standard { simulated: false })
+    }
+}
+
 /// ERC20 Service for interacting with ERC20 tokens
 pub struct ERC20Service {
     evm: Arc<Evm>,
@@ -35,7 +110,7 @@ impl ERC20Service {
 
     /// Create ERC20 token instance
     fn erc20(&self, token_address: Address) -> IERC20<Provider<ethers::providers::Http>> {
-        IERC20::new(token_address, self.evm.client.provider.clone())
+        IERC20::new(token_address, self.evm.provider().clone())
     }
 
     /// Get ERC20 token balance
@@ -44,20 +119,190 @@ impl ERC20Service {
         token_address: Address,
         owner: Address,
     ) -> Result<U256, EvmError> {
-        let erc20 = self.erc20(token_address);
-        erc20
-            .balance_of(owner)
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .balance_of(owner)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 balance", &e))
+            })
+            .await
+    }
+
+    /// Get the ERC20 balances of many holders, in the order of `owners`
+    ///
+    /// Balances are read through Multicall3 in chunks of `BALANCE_BATCH_SIZE`, falling
+    /// back to one call per holder where it is not deployed. A holder whose balance cannot
+    /// be read gets zero and a warning in the log, so gaps in a snapshot can be audited.
+    ///
+    /// # Example
+    /// ```
+    /// let balances = erc20_service.get_balances(token, holders.clone()).await?;
+    /// for (holder, balance) in holders.iter().zip(balances) {
+    ///     println!("{:?}: {}", holder, balance);
+    /// }
+    /// ```
+    pub async fn get_balances(
+        &self,
+        token: Address,
+        owners: Vec<Address>,
+    ) -> Result<Vec<U256>, EvmError> {
+        let erc20 = self.erc20(token);
+        let mut balances = Vec::with_capacity(owners.len());
+        for chunk in owners.chunks(BALANCE_BATCH_SIZE) {
+            let calls = chunk
+                .iter()
+                .map(|owner| {
+                    (
+                        token,
+                        erc20.balance_of(*owner).calldata().unwrap_or_default(),
+                    )
+                })
+                .collect();
+            match self.evm.multicall(calls).await {
+                Ok(results) => {
+                    for (owner, (success, data)) in chunk.iter().zip(results) {
+                        let balance =
+                            (success && data.len() == 32).then(|| U256::from_big_endian(&data));
+                        if balance.is_none() {
+                            warn!("Failed to get ERC20 balance of {:?} for {:?}", token, owner);
+                        }
+                        balances.push(balance.unwrap_or_default());
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Balance multicall failed, reading one by one: {}", e);
+                }
+            }
+            for owner in chunk {
+                let balance = self.get_balance(token, *owner).await.unwrap_or_else(|e| {
+                    warn!(
+                        "Failed to get ERC20 balance of {:?} for {:?}: {:?}",
+                        token, owner, e
+                    );
+                    U256::zero()
+                });
+                balances.push(balance);
+            }
+        }
+        Ok(balances)
+    }
+
+    /// Get the ERC20 balance of `owner` as of the end of `block`
+    ///
+    /// Blocks older than the node's pruning window need an archive node; when the state is
+    /// gone a `RpcError` says so instead of the raw node message.
+    ///
+    /// # Example
+    /// ```
+    /// let before = erc20_service.balance_of_at(usdc, holder, 18_000_000).await?;
+    /// ```
+    pub async fn balance_of_at(
+        &self,
+        token: Address,
+        owner: Address,
+        block: u64,
+    ) -> Result<U256, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc20(token)
+                    .balance_of(owner)
+                    .block(block)
+                    .call()
+                    .await
+                    .map_err(|e| {
+                        let message = e.to_string();
+                        if message.contains("missing trie node")
+                            || message.contains("historical state")
+                        {
+                            EvmError::RpcError(format!(
+                                "State at block {} is not available, an archive node is required: {}",
+                                block, message
+                            ))
+                        } else {
+                            EvmError::contract(
+                                &format!("Failed to get ERC20 balance at block {}", block),
+                                &e,
+                            )
+                        }
+                    })
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get ERC20 balance: {}", e)))
     }
 
     /// Get ERC20 token total supply
     pub async fn get_total_supply(&self, token_address: Address) -> Result<U256, EvmError> {
-        let erc20 = self.erc20(token_address);
-        erc20.total_supply().call().await.map_err(|e| {
-            EvmError::ContractError(format!("Failed to get ERC20 total supply: {}", e))
-        })
+        self.evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .total_supply()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 total supply", &e))
+            })
+            .await
+    }
+
+    /// Get the circulating supply: total supply minus balances held by burn and lock addresses
+    ///
+    /// The zero address and `0x...dEaD` are always excluded; `excluded` adds more, such
+    /// as lockers or treasury wallets. Tokens vesting in contracts are only left out when
+    /// the caller passes those contracts, since they cannot be recognized on-chain.
+    /// Balances are read in one multicall where Multicall3 is deployed.
+    ///
+    /// # Example
+    /// ```
+    /// let circulating = erc20_service.circulating_supply(token, vec![team_locker]).await?;
+    /// ```
+    pub async fn circulating_supply(
+        &self,
+        token: Address,
+        excluded: Vec<Address>,
+    ) -> Result<U256, EvmError> {
+        let mut holders = vec![Address::zero(), DEAD_ADDRESS];
+        for holder in excluded {
+            if !holders.contains(&holder) {
+                holders.push(holder);
+            }
+        }
+        let erc20 = self.erc20(token);
+        let calls = std::iter::once(erc20.total_supply().calldata())
+            .chain(
+                holders
+                    .iter()
+                    .map(|holder| erc20.balance_of(*holder).calldata()),
+            )
+            .map(|data| (token, data.unwrap_or_default()))
+            .collect();
+        let (total_supply, balances): (U256, Vec<U256>) = match self.evm.multicall(calls).await {
+            Ok(results) => {
+                let mut results = results.into_iter().map(|(success, data)| {
+                    (success && data.len() == 32).then(|| U256::from_big_endian(&data))
+                });
+                let total_supply = results.next().flatten().ok_or_else(|| {
+                    EvmError::ContractError("Failed to get ERC20 total supply".to_string())
+                })?;
+                (
+                    total_supply,
+                    results.map(Option::unwrap_or_default).collect(),
+                )
+            }
+            Err(_) => {
+                let mut balances = Vec::with_capacity(holders.len());
+                for holder in &holders {
+                    balances.push(self.get_balance(token, *holder).await.unwrap_or_default());
+                }
+                (self.get_total_supply(token).await?, balances)
+            }
+        };
+        let locked = balances
+            .into_iter()
+            .fold(U256::zero(), |total: U256, balance| {
+                total.saturating_add(balance)
+            });
+        Ok(total_supply.saturating_sub(locked))
     }
 
     /// Transfer ERC20 tokens
@@ -86,12 +331,15 @@ impl ERC20Service {
         owner: Address,
         spender: Address,
     ) -> Result<U256, EvmError> {
-        let erc20 = self.erc20(token_address);
-        erc20
-            .allowance(owner, spender)
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .allowance(owner, spender)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get allowance", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get allowance: {}", e)))
     }
 
     /// Approve spender to spend tokens
@@ -113,6 +361,74 @@ impl ERC20Service {
         Ok(pending_tx.tx_hash())
     }
 
+    /// Approve spender to spend an unlimited amount of tokens
+    pub async fn approve_max(
+        &self,
+        token_address: Address,
+        spender: Address,
+    ) -> Result<H256, EvmError> {
+        self.approve(token_address, spender, U256::MAX).await
+    }
+
+    /// Approve spender for `needed` tokens unless the wallet already allows at least that
+    ///
+    /// Tokens such as USDT refuse to change a non-zero allowance to another non-zero
+    /// value, so an insufficient non-zero allowance is first reset to zero and that
+    /// reset waited for. Returns the hash of the approval, or `Ok(None)` when the
+    /// current allowance suffices and no transaction was sent.
+    ///
+    /// # Example
+    /// ```
+    /// let erc20 = ERC20Service::new(evm.clone());
+    /// if let Some(tx_hash) = erc20.ensure_allowance(usdc, router, amount_in).await? {
+    ///     println!("Approval sent: {:?}", tx_hash);
+    /// }
+    /// ```
+    pub async fn ensure_allowance(
+        &self,
+        token_address: Address,
+        spender: Address,
+        needed: U256,
+    ) -> Result<Option<H256>, EvmError> {
+        if !self
+            .prepare_approval(token_address, spender, needed)
+            .await?
+        {
+            return Ok(None);
+        }
+        self.approve(token_address, spender, needed).await.map(Some)
+    }
+
+    /// Whether the wallet must approve `spender` for `needed` more of the token
+    ///
+    /// An insufficient non-zero allowance is reset to zero, and the reset waited for,
+    /// before `true` is returned, as `ensure_allowance` describes.
+    pub(crate) async fn prepare_approval(
+        &self,
+        token_address: Address,
+        spender: Address,
+        needed: U256,
+    ) -> Result<bool, EvmError> {
+        let owner = self
+            .evm
+            .client
+            .wallet
+            .as_ref()
+            .map(Signer::address)
+            .ok_or_else(|| EvmError::WalletError("No wallet configured".to_string()))?;
+        let allowance = self.get_allowance(token_address, owner, spender).await?;
+        if allowance >= needed {
+            return Ok(false);
+        }
+        if !allowance.is_zero() {
+            let reset = self.approve(token_address, spender, U256::zero()).await?;
+            self.evm
+                .confirm_transaction(reset, 1, APPROVAL_RESET_TIMEOUT)
+                .await?;
+        }
+        Ok(true)
+    }
+
     /// Transfer from (requires allowance)
     pub async fn transfer_from(
         &self,
@@ -135,13 +451,395 @@ impl ERC20Service {
 
     /// Get ERC20 token decimals
     pub async fn get_decimals(&self, token_address: Address) -> Result<u8, EvmError> {
-        let erc20 = self.erc20(token_address);
-        erc20
-            .decimals()
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .decimals()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 decimals", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get ERC20 decimals: {}", e)))
     }
+
+    /// Get name, symbol and decimals of a token, read in parallel
+    ///
+    /// Legacy tokens such as MKR return `bytes32` from `name()` and `symbol()`; those
+    /// are decoded as null-padded text when the `string` decoding fails.
+    ///
+    /// # Example
+    /// ```
+    /// let metadata = erc20_service.get_metadata(token).await?;
+    /// println!("{} ({}), {} decimals", metadata.name, metadata.symbol, metadata.decimals);
+    /// ```
+    pub async fn get_metadata(&self, token: Address) -> Result<ERCTokenMetadata, EvmError> {
+        let (name, symbol, decimals) = tokio::try_join!(
+            self.get_name(token),
+            self.get_symbol(token),
+            self.get_decimals(token)
+        )?;
+        Ok(ERCTokenMetadata {
+            address: token,
+            name,
+            symbol,
+            decimals,
+        })
+    }
+
+    /// Interface of legacy tokens (MKR, SAI, ...) returning `bytes32` metadata
+    fn erc20_bytes32(
+        &self,
+        token_address: Address,
+    ) -> IERC20Bytes32<Provider<ethers::providers::Http>> {
+        IERC20Bytes32::new(token_address, self.evm.provider().clone())
+    }
+
+    /// Get ERC20 token name
+    ///
+    /// Tokens returning `bytes32` instead of `string` are decoded with the null padding
+    /// trimmed.
+    pub async fn get_name(&self, token_address: Address) -> Result<String, EvmError> {
+        let name = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .name()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 name", &e))
+            })
+            .await;
+        let Err(EvmError::ContractError(message)) = name else {
+            return name;
+        };
+        let bytes = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc20_bytes32(token_address)
+                    .name()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 name", &e))
+            })
+            .await;
+        match bytes {
+            Ok(bytes) => bytes32_to_text(bytes).ok_or(EvmError::ContractError(message)),
+            Err(EvmError::ContractError(_)) => Err(EvmError::ContractError(message)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get ERC20 token symbol, falling back to `bytes32` like `get_name`
+    pub async fn get_symbol(&self, token_address: Address) -> Result<String, EvmError> {
+        let symbol = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc20(token_address)
+                    .symbol()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 symbol", &e))
+            })
+            .await;
+        let Err(EvmError::ContractError(message)) = symbol else {
+            return symbol;
+        };
+        let bytes = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc20_bytes32(token_address)
+                    .symbol()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get ERC20 symbol", &e))
+            })
+            .await;
+        match bytes {
+            Ok(bytes) => bytes32_to_text(bytes).ok_or(EvmError::ContractError(message)),
+            Err(EvmError::ContractError(_)) => Err(EvmError::ContractError(message)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get price, market cap and fully diluted valuation of a token
+    ///
+    /// Market cap uses the circulating supply (burn addresses excluded, see
+    /// `circulating_supply`), the fully diluted valuation uses the total supply. The
+    /// oracle is generic rather than `&dyn` because `PriceOracle` has async methods.
+    /// When the oracle cannot price the token, the price and both valuations are `None`
+    /// while the supplies are still filled in.
+    ///
+    /// # Example
+    /// ```
+    /// let market = erc20_service.get_market_data(token, &SimplePriceOracle::new()).await?;
+    /// if let Some(market_cap) = market.market_cap {
+    ///     println!("Market cap: ${}", format_big_num(market_cap));
+    /// }
+    /// ```
+    pub async fn get_market_data(
+        &self,
+        token: Address,
+        oracle: &impl PriceOracle,
+    ) -> Result<MarketData, EvmError> {
+        let decimals = self.get_decimals(token).await?;
+        let total_supply = self.get_total_supply(token).await?;
+        let circulating_supply = self.circulating_supply(token, Vec::new()).await?;
+        let price = oracle.get_price(token).await.ok();
+        let valuation = |supply: U256| -> Option<f64> {
+            let amount: f64 = format_units(supply, decimals as u32).ok()?.parse().ok()?;
+            price.map(|price| amount * price)
+        };
+        Ok(MarketData {
+            token,
+            decimals,
+            total_supply,
+            circulating_supply,
+            price,
+            market_cap: valuation(circulating_supply),
+            fully_diluted_valuation: valuation(total_supply),
+        })
+    }
+
+    /// Index every `Transfer` of a token from `start_block` up to the current head
+    ///
+    /// The range is walked in chunks of `TRANSFER_LOG_CHUNK_SIZE` blocks; the decoded
+    /// transfers of each chunk are passed to `on_batch` (skipped when a chunk has none) so
+    /// the caller can persist them before the next chunk is fetched. Log requests are
+    /// retried with the client's retry policy, see `Evm::with_retry`.
+    ///
+    /// Returns the last indexed block, the current head. When a chunk keeps failing, or
+    /// `on_batch` returns an error, indexing stops. If earlier chunks were processed the
+    /// error is an `EvmError::IndexingInterrupted` carrying the last fully processed
+    /// block, so calling again from the next block resumes where it stopped.
+    ///
+    /// # Example
+    /// ```
+    /// let last = erc20_service
+    ///     .index_all_transfers(token, deployment_block, |batch| db.insert_transfers(&batch))
+    ///     .await?;
+    /// ```
+    pub async fn index_all_transfers(
+        &self,
+        token: Address,
+        start_block: u64,
+        mut on_batch: impl FnMut(Vec<TransferEvent>) -> Result<(), EvmError>,
+    ) -> Result<u64, EvmError> {
+        let head = self.evm.get_block_number().await?;
+        if start_block > head {
+            return Err(EvmError::InvalidInput(format!(
+                "Start block {} is beyond head {}",
+                start_block, head
+            )));
+        }
+        let mut last_indexed: Option<u64> = None;
+        let mut chunk_start = start_block;
+        while chunk_start <= head {
+            let chunk_end = head.min(chunk_start.saturating_add(TRANSFER_LOG_CHUNK_SIZE - 1));
+            let result = match self.transfer_logs(token, chunk_start, chunk_end).await {
+                Ok(logs) => {
+                    let batch: Vec<TransferEvent> = logs
+                        .iter()
+                        .filter_map(|log| match TransferEvent::from_log(log) {
+                            Ok(transfer) => Some(transfer),
+                            Err(e) => {
+                                warn!("Skipping undecodable transfer log: {}", e);
+                                None
+                            }
+                        })
+                        .collect();
+                    if batch.is_empty() {
+                        Ok(())
+                    } else {
+                        on_batch(batch)
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                return Err(match last_indexed {
+                    Some(block) => EvmError::IndexingInterrupted(block, Box::new(e)),
+                    None => e,
+                });
+            }
+            last_indexed = Some(chunk_end);
+            chunk_start = chunk_end + 1;
+        }
+        Ok(head)
+    }
+
+    /// `Transfer` logs of a token in a block range
+    async fn transfer_logs(
+        &self,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, EvmError> {
+        let filter = Filter::new()
+            .address(token)
+            .topic0(H256::from(dex_events::erc20_transfer()))
+            .from_block(from_block)
+            .to_block(to_block);
+        self.evm.get_logs(filter).await
+    }
+
+    /// Detect whether a token charges a fee on transfer or rebases balances.
+    ///
+    /// The transfer is simulated with `eth_call` state overrides: the token's balance storage
+    /// is located by probing common layouts (Solidity and Vyper mappings in the first slots,
+    /// OpenZeppelin v5 namespaced storage), a balance is written for a probe contract, and the
+    /// probe transfers one whole token while measuring what the recipient actually receives.
+    /// Nothing is sent on chain.
+    ///
+    /// When the balance slot cannot be found or the node does not support state overrides,
+    /// `TransferBehavior::Standard { simulated: false }` is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// let behavior = erc20_service.detect_transfer_behavior(token).await?;
+    /// if let TransferBehavior::FeeOnTransfer { fee_bps } = behavior {
+    ///     println!("token takes {} bps per transfer", fee_bps);
+    /// }
+    /// ```
+    pub async fn detect_transfer_behavior(
+        &self,
+        token_address: Address,
+    ) -> Result<TransferBehavior, EvmError> {
+        let decimals = self.get_decimals(token_address).await?;
+        if decimals > 36 {
+            return Ok(TransferBehavior::Standard { simulated: false });
+        }
+        let unit = U256::exp10(decimals as usize);
+        let holding = unit * U256::from(1_000_000u64);
+
+        let slot = match self.find_balance_slot(token_address, holding).await {
+            Some(SlotProbe::Found(slot)) => slot,
+            Some(SlotProbe::Scaled) => return Ok(TransferBehavior::Rebasing),
+            None => return Ok(TransferBehavior::Standard { simulated: false }),
+        };
+
+        let code = match hex::decode(TRANSFER_PROBE_CODE) {
+            Ok(code) => code,
+            Err(_) => return Ok(TransferBehavior::Standard { simulated: false }),
+        };
+        let mut state = spoof::state();
+        state
+            .account(token_address)
+            .store(slot, H256::from_uint(&holding));
+        state.account(PROBE_ADDRESS).code(Bytes::from(code));
+
+        let data = encode(&[
+            Token::Address(token_address),
+            Token::Address(PROBE_RECIPIENT),
+            Token::Uint(unit),
+        ]);
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(PROBE_ADDRESS)
+            .data(data)
+            .into();
+        let output = match self.evm.provider().call_raw(&tx).state(&state).await {
+            Ok(output) if output.len() == 64 => output,
+            _ => return Ok(TransferBehavior::Standard { simulated: false }),
+        };
+
+        let before = U256::from_big_endian(&output[..32]);
+        let after = U256::from_big_endian(&output[32..]);
+        let received = after.saturating_sub(before);
+        Ok(Self::classify_transfer(unit, received))
+    }
+
+    /// Classify a simulated transfer of `sent` that credited the recipient with `received`
+    fn classify_transfer(sent: U256, received: U256) -> TransferBehavior {
+        if received == sent {
+            TransferBehavior::Standard { simulated: true }
+        } else if received > sent {
+            TransferBehavior::Rebasing
+        } else if received.is_zero() {
+            // A transfer that moved nothing (e.g. returned `false`) says nothing about fees.
+            TransferBehavior::Standard { simulated: false }
+        } else {
+            let fee_bps = (sent - received) * U256::from(10_000u64) / sent;
+            TransferBehavior::FeeOnTransfer {
+                fee_bps: fee_bps.as_u32(),
+            }
+        }
+    }
+
+    /// Locate the storage slot holding the probe contract's balance by writing `amount` to
+    /// candidate slots and reading it back through `balanceOf`.
+    async fn find_balance_slot(&self, token_address: Address, amount: U256) -> Option<SlotProbe> {
+        let holder = H256::from(PROBE_ADDRESS);
+        let mut candidates = Vec::new();
+        for slot in 0..=MAX_BALANCE_SLOT {
+            let slot = H256::from_low_u64_be(slot);
+            candidates.push(mapping_slot(holder, slot));
+            candidates.push(mapping_slot(slot, holder));
+        }
+        candidates.push(mapping_slot(holder, H256::from(OZ_V5_ERC20_STORAGE)));
+
+        let call = self.erc20(token_address).balance_of(PROBE_ADDRESS);
+        let tx = call.tx.clone();
+        let stored = H256::from_uint(&amount);
+        for key in candidates {
+            let mut state = spoof::state();
+            state.account(token_address).store(key, stored);
+            let output = match self.evm.provider().call_raw(&tx).state(&state).await {
+                Ok(output) if output.len() >= 32 => output,
+                Ok(_) => continue,
+                // Either the node rejects state overrides or the token itself reverts.
+                Err(_) => return None,
+            };
+            let balance = U256::from_big_endian(&output[..32]);
+            if balance == amount {
+                return Some(SlotProbe::Found(key));
+            }
+            if !balance.is_zero() {
+                return Some(SlotProbe::Scaled);
+            }
+        }
+        None
+    }
+}
+
+/// Outcome of probing a token's balance storage
+enum SlotProbe {
+    /// `balanceOf` returns exactly what was written to this slot
+    Found(H256),
+    /// `balanceOf` reacts to the written slot but reports a different amount
+    Scaled,
+}
+
+/// Storage key of `mapping[key]` for a mapping rooted at `slot`
+fn mapping_slot(key: H256, slot: H256) -> H256 {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(key.as_bytes());
+    preimage[32..].copy_from_slice(slot.as_bytes());
+    H256::from(keccak256(preimage))
+}
+
+/// Decode a `bytes32` name or symbol as text, dropping the null padding
+fn bytes32_to_text(bytes: [u8; 32]) -> Option<String> {
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| i + 1);
+    String::from_utf8(bytes[..end].to_vec()).ok()
+}
+
+/// Price and valuations of a token, see `ERC20Service::get_market_data`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MarketData {
+    pub token: Address,
+    pub decimals: u8,
+    /// Raw total supply, in token units
+    pub total_supply: U256,
+    /// Raw circulating supply, in token units
+    pub circulating_supply: U256,
+    /// Price of one whole token, as returned by the oracle
+    pub price: Option<f64>,
+    /// `price * circulating_supply`
+    pub market_cap: Option<f64>,
+    /// `price * total_supply`
+    pub fully_diluted_valuation: Option<f64>,
 }
 
 /// ERC20 Token Metadata
@@ -0,0 +1,268 @@
+use crate::{Evm, EvmError, global::vault_events};
+use ethers::{
+    abi::{ParamType, decode},
+    contract::abigen,
+    providers::{Http, Middleware, Provider},
+    types::{Address, Log, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+abigen!(
+    IERC4626,
+    r#"[
+        function asset() external view returns (address)
+        function totalAssets() external view returns (uint256)
+        function convertToShares(uint256 assets) external view returns (uint256)
+        function convertToAssets(uint256 shares) external view returns (uint256)
+        event Deposit(address indexed sender, address indexed owner, uint256 assets, uint256 shares)
+        event Withdraw(address indexed sender, address indexed receiver, address indexed owner, uint256 assets, uint256 shares)
+    ]"#
+);
+
+/// ERC-4626 vault event decoded from a log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VaultEvent {
+    /// `sender` paid `assets` and `owner` received `shares`
+    Deposit {
+        vault: Address,
+        sender: Address,
+        owner: Address,
+        assets: U256,
+        shares: U256,
+    },
+    /// `owner` burned `shares` and `receiver` received `assets`
+    Withdraw {
+        vault: Address,
+        sender: Address,
+        receiver: Address,
+        owner: Address,
+        assets: U256,
+        shares: U256,
+    },
+}
+
+impl VaultEvent {
+    /// Decode a `Deposit` or `Withdraw` log emitted by a vault
+    ///
+    /// WETH's `Deposit(address,uint256)` has a different topic and is not matched.
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        let topic0 = log.topics.first().ok_or("Log has no topics")?;
+        let is_deposit = topic0.as_bytes() == vault_events::deposit();
+        if !is_deposit && topic0.as_bytes() != vault_events::withdraw() {
+            return Err(format!("Not an ERC-4626 event: {:?}", topic0));
+        }
+        let indexed = if is_deposit { 2 } else { 3 };
+        if log.topics.len() != indexed + 1 {
+            return Err(format!(
+                "Invalid vault event log: expected {} topics, got {}",
+                indexed + 1,
+                log.topics.len()
+            ));
+        }
+        let topic_address = |index: usize| Address::from(log.topics[index]);
+        let amounts = decode(&[ParamType::Uint(256), ParamType::Uint(256)], &log.data)
+            .map_err(|e| format!("Invalid vault event data: {}", e))?;
+        let (Some(assets), Some(shares)) = (
+            amounts[0].clone().into_uint(),
+            amounts[1].clone().into_uint(),
+        ) else {
+            return Err("Invalid vault event amounts".to_string());
+        };
+        Ok(if is_deposit {
+            VaultEvent::Deposit {
+                vault: log.address,
+                sender: topic_address(1),
+                owner: topic_address(2),
+                assets,
+                shares,
+            }
+        } else {
+            VaultEvent::Withdraw {
+                vault: log.address,
+                sender: topic_address(1),
+                receiver: topic_address(2),
+                owner: topic_address(3),
+                assets,
+                shares,
+            }
+        })
+    }
+}
+
+/// Readers for ERC-4626 tokenized vaults
+pub struct Erc4626Service {
+    evm: Arc<Evm>,
+}
+
+impl Erc4626Service {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self { evm }
+    }
+
+    fn erc4626(&self, vault: Address) -> IERC4626<Provider<Http>> {
+        IERC4626::new(vault, self.evm.provider().clone())
+    }
+
+    /// Underlying asset of the vault
+    ///
+    /// Also serves as the ERC-4626 check of the other readers: a contract without code,
+    /// without `asset()` or returning the zero address is reported as a
+    /// `ContractError`.
+    ///
+    /// # Example
+    /// ```
+    /// let vaults = Erc4626Service::new(evm.clone());
+    /// let underlying = vaults.asset(sdai).await?;
+    /// ```
+    pub async fn asset(&self, vault: Address) -> Result<Address, EvmError> {
+        let not_a_vault = |reason: String| {
+            EvmError::ContractError(format!("{:?} is not an ERC-4626 vault: {}", vault, reason))
+        };
+        let code = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_code(vault, None)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get code", e))
+            })
+            .await?;
+        if code.is_empty() {
+            return Err(not_a_vault("no contract code".to_string()));
+        }
+        let asset = self
+            .evm
+            .read_with_retry(|| async {
+                self.erc4626(vault).asset().call().await.map_err(|e| {
+                    match EvmError::contract("asset() failed", &e) {
+                        EvmError::ContractError(_) => not_a_vault(e.to_string()),
+                        other => other,
+                    }
+                })
+            })
+            .await?;
+        if asset.is_zero() {
+            return Err(not_a_vault("asset() returned the zero address".to_string()));
+        }
+        Ok(asset)
+    }
+
+    /// Total amount of the underlying asset managed by the vault
+    ///
+    /// # Example
+    /// ```
+    /// let tvl = vaults.total_assets(sdai).await?;
+    /// ```
+    pub async fn total_assets(&self, vault: Address) -> Result<U256, EvmError> {
+        self.asset(vault).await?;
+        self.evm
+            .read_with_retry(|| async {
+                self.erc4626(vault)
+                    .total_assets()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get total assets", &e))
+            })
+            .await
+    }
+
+    /// Shares the vault would mint for `assets`, ignoring deposit limits and fees
+    ///
+    /// # Example
+    /// ```
+    /// let shares = vaults.convert_to_shares(sdai, U256::exp10(18)).await?;
+    /// ```
+    pub async fn convert_to_shares(&self, vault: Address, assets: U256) -> Result<U256, EvmError> {
+        self.asset(vault).await?;
+        self.evm
+            .read_with_retry(|| async {
+                self.erc4626(vault)
+                    .convert_to_shares(assets)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to convert to shares", &e))
+            })
+            .await
+    }
+
+    /// Assets `shares` are worth at the current exchange rate, ignoring limits and fees
+    ///
+    /// # Example
+    /// ```
+    /// let assets = vaults.convert_to_assets(sdai, shares).await?;
+    /// ```
+    pub async fn convert_to_assets(&self, vault: Address, shares: U256) -> Result<U256, EvmError> {
+        self.asset(vault).await?;
+        self.evm
+            .read_with_retry(|| async {
+                self.erc4626(vault)
+                    .convert_to_assets(shares)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to convert to assets", &e))
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::{Token, encode},
+        types::H256,
+    };
+
+    #[test]
+    fn test_vault_event_from_log() {
+        let vault = Address::repeat_byte(0x11);
+        let sender = Address::repeat_byte(0x22);
+        let owner = Address::repeat_byte(0x33);
+        let data = encode(&[Token::Uint(1_000.into()), Token::Uint(950.into())]);
+        let log = Log {
+            address: vault,
+            topics: vec![
+                H256::from(vault_events::deposit()),
+                H256::from(sender),
+                H256::from(owner),
+            ],
+            data: data.clone().into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            VaultEvent::from_log(&log).unwrap(),
+            VaultEvent::Deposit {
+                vault,
+                sender,
+                owner,
+                assets: 1_000.into(),
+                shares: 950.into(),
+            }
+        );
+        let receiver = Address::repeat_byte(0x44);
+        let log = Log {
+            address: vault,
+            topics: vec![
+                H256::from(vault_events::withdraw()),
+                H256::from(sender),
+                H256::from(receiver),
+                H256::from(owner),
+            ],
+            data: data.into(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            VaultEvent::from_log(&log).unwrap(),
+            VaultEvent::Withdraw { receiver: r, assets, .. } if r == receiver && assets == U256::from(1_000)
+        ));
+        let weth_deposit = Log {
+            topics: vec![H256::from(ethers::utils::keccak256(
+                "Deposit(address,uint256)",
+            ))],
+            ..Default::default()
+        };
+        assert!(VaultEvent::from_log(&weth_deposit).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{Evm, EvmError};
+use ethers::{
+    contract::abigen,
+    providers::{Http, Provider},
+    types::{Address, H256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+abigen!(
+    IERC721,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function ownerOf(uint256 tokenId) external view returns (address)
+        function safeTransferFrom(address from, address to, uint256 tokenId) external
+        function transferFrom(address from, address to, uint256 tokenId) external
+        function approve(address to, uint256 tokenId) external
+        function setApprovalForAll(address operator, bool approved) external
+        function getApproved(uint256 tokenId) external view returns (address)
+        function isApprovedForAll(address owner, address operator) external view returns (bool)
+        function name() external view returns (string)
+        function symbol() external view returns (string)
+        function tokenURI(uint256 tokenId) external view returns (string)
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)
+        event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)
+        event ApprovalForAll(address indexed owner, address indexed operator, bool approved)
+    ]"#
+);
+
+/// Public gateway `ipfs://` token URIs are fetched through.
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// ERC721 Service for interacting with NFT collections
+pub struct ERC721Service {
+    evm: Arc<Evm>,
+}
+
+impl ERC721Service {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self { evm }
+    }
+
+    /// Create ERC721 collection instance
+    fn erc721(&self, collection: Address) -> IERC721<Provider<Http>> {
+        IERC721::new(collection, self.evm.provider().clone())
+    }
+
+    /// Get the owner of an NFT
+    pub async fn owner_of(&self, collection: Address, token_id: U256) -> Result<Address, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc721(collection)
+                    .owner_of(token_id)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get NFT owner", &e))
+            })
+            .await
+    }
+
+    /// Get the number of NFTs of a collection held by `owner`
+    pub async fn balance_of(&self, collection: Address, owner: Address) -> Result<U256, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc721(collection)
+                    .balance_of(owner)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get NFT balance", &e))
+            })
+            .await
+    }
+
+    /// Get the metadata URI of an NFT
+    pub async fn token_uri(&self, collection: Address, token_id: U256) -> Result<String, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc721(collection)
+                    .token_uri(token_id)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get token URI", &e))
+            })
+            .await
+    }
+
+    /// Get the address approved to transfer an NFT, zero when there is none
+    pub async fn get_approved(
+        &self,
+        collection: Address,
+        token_id: U256,
+    ) -> Result<Address, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.erc721(collection)
+                    .get_approved(token_id)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get approved address", &e))
+            })
+            .await
+    }
+
+    /// Safely transfer an NFT, reverting if `to` is a contract that cannot receive it
+    pub async fn safe_transfer_from(
+        &self,
+        collection: Address,
+        from: Address,
+        to: Address,
+        token_id: U256,
+    ) -> Result<H256, EvmError> {
+        if self.evm.client.wallet.is_none() {
+            return Err(EvmError::WalletError("No wallet configured".to_string()));
+        }
+        let erc721 = self.erc721(collection);
+        let tx = erc721.safe_transfer_from(from, to, token_id);
+        let pending_tx = tx
+            .send()
+            .await
+            .map_err(|e| EvmError::TransactionError(format!("Failed to transfer NFT: {}", e)))?;
+        Ok(pending_tx.tx_hash())
+    }
+
+    /// Fetch and parse the JSON metadata `tokenURI` points to
+    ///
+    /// `ipfs://` URIs go through a public gateway and `data:application/json` URIs
+    /// (plain or base64) are decoded in place, as fully on-chain collections use them.
+    ///
+    /// # Example
+    /// ```
+    /// let nfts = ERC721Service::new(evm.clone());
+    /// let metadata = nfts.get_metadata(collection, U256::from(1)).await?;
+    /// println!("{:?}: {:?}", metadata.name, metadata.image);
+    /// ```
+    pub async fn get_metadata(
+        &self,
+        collection: Address,
+        token_id: U256,
+    ) -> Result<NftMetadata, EvmError> {
+        let uri = self.token_uri(collection, token_id).await?;
+        let raw: serde_json::Value = if let Some(data) = uri.strip_prefix("data:") {
+            let (media_type, payload) = data
+                .split_once(',')
+                .ok_or_else(|| EvmError::InvalidInput(format!("Invalid data URI: {}", uri)))?;
+            let json = if media_type.ends_with(";base64") {
+                decode_base64(payload).ok_or_else(|| {
+                    EvmError::InvalidInput("Invalid base64 in token URI".to_string())
+                })?
+            } else {
+                payload.as_bytes().to_vec()
+            };
+            serde_json::from_slice(&json)
+                .map_err(|e| EvmError::InvalidInput(format!("Invalid NFT metadata: {}", e)))?
+        } else {
+            let url = match uri.strip_prefix("ipfs://") {
+                Some(path) => format!("{}{}", IPFS_GATEWAY, path.trim_start_matches("ipfs/")),
+                None => uri.clone(),
+            };
+            reqwest::Client::new()
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| {
+                    EvmError::ConnectionError(format!("Failed to fetch NFT metadata: {}", e))
+                })?
+                .json()
+                .await
+                .map_err(|e| EvmError::InvalidInput(format!("Invalid NFT metadata: {}", e)))?
+        };
+        let text = |key: &str| {
+            raw.get(key)
+                .and_then(|value| value.as_str())
+                .map(String::from)
+        };
+        Ok(NftMetadata {
+            name: text("name"),
+            description: text("description"),
+            image: text("image").or_else(|| text("image_url")),
+            attributes: raw
+                .get("attributes")
+                .and_then(|attributes| attributes.as_array())
+                .cloned()
+                .unwrap_or_default(),
+            token_uri: uri,
+            raw,
+        })
+    }
+}
+
+/// NFT metadata following the common ERC721 metadata JSON schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftMetadata {
+    pub token_uri: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    /// OpenSea style `{ "trait_type": ..., "value": ... }` entries
+    pub attributes: Vec<serde_json::Value>,
+    /// The full document, for fields outside the schema
+    pub raw: serde_json::Value,
+}
+
+/// Decode standard base64, with or without padding
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in input.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("eyJuYW1lIjoiIzEifQ==").unwrap(), br#"{"name":"#1"}"#);
+        assert_eq!(decode_base64("TWE").unwrap(), b"Ma");
+        assert!(decode_base64("not base64!").is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    Evm, EvmError,
+    global::{address_of, wrapped_native_by_chain_id},
+};
+use ethers::{
+    contract::abigen,
+    providers::{Http, Provider},
+    types::{Address, H256, U256},
+};
+use std::sync::Arc;
+
+abigen!(
+    IWETH,
+    r#"[
+        function deposit() external payable
+        function withdraw(uint256 amount) external
+        function balanceOf(address account) external view returns (uint256)
+        event Deposit(address indexed dst, uint256 wad)
+        event Withdrawal(address indexed src, uint256 wad)
+    ]"#
+);
+
+/// Wraps and unwraps the native gas token through the canonical wrapper of the chain
+/// (WETH, WBNB, WAVAX, ...)
+pub struct WethService {
+    evm: Arc<Evm>,
+}
+
+impl WethService {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self { evm }
+    }
+
+    /// Address of the wrapped native token of the connected chain
+    ///
+    /// Chains without a registered wrapper are reported as a `ConfigError`.
+    pub async fn address(&self) -> Result<Address, EvmError> {
+        let chain_id = self.evm.get_chain_id().await?;
+        wrapped_native_by_chain_id(chain_id)
+            .and_then(address_of)
+            .ok_or_else(|| {
+                EvmError::ConfigError(format!(
+                    "No wrapped native token registered for chain {}",
+                    chain_id
+                ))
+            })
+    }
+
+    async fn weth(&self) -> Result<IWETH<Provider<Http>>, EvmError> {
+        if self.evm.client.wallet.is_none() {
+            return Err(EvmError::WalletError("No wallet configured".to_string()));
+        }
+        Ok(IWETH::new(
+            self.address().await?,
+            self.evm.provider().clone(),
+        ))
+    }
+
+    /// Wrap `amount` of the native token
+    ///
+    /// # Example
+    /// ```
+    /// let weth = WethService::new(evm.clone());
+    /// let tx_hash = weth.deposit(ethers::utils::parse_ether("0.5")?).await?;
+    /// ```
+    pub async fn deposit(&self, amount: U256) -> Result<H256, EvmError> {
+        let weth = self.weth().await?;
+        let tx = weth.deposit().value(amount);
+        let pending_tx = tx.send().await.map_err(|e| {
+            EvmError::TransactionError(format!("Failed to wrap native token: {}", e))
+        })?;
+        Ok(pending_tx.tx_hash())
+    }
+
+    /// Unwrap `amount` of the wrapped token back to the native token
+    pub async fn withdraw(&self, amount: U256) -> Result<H256, EvmError> {
+        let weth = self.weth().await?;
+        let tx = weth.withdraw(amount);
+        let pending_tx = tx.send().await.map_err(|e| {
+            EvmError::TransactionError(format!("Failed to unwrap native token: {}", e))
+        })?;
+        Ok(pending_tx.tx_hash())
+    }
+}
@@ -1,3 +1,10 @@
+use crate::types::ContractRole;
+use ethers::{types::Address, utils::to_checksum};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
 // Ethereum Mainnet
 pub const ETH_ETHEREUM_MAINNET: &'static str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
 pub const USDT_ETHEREUM_MAINNET: &'static str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
@@ -24,7 +31,7 @@ pub const WBTC_BSC_MAINNET: &'static str = "0x0555E30da8f98308EdB960aa94C0Db4723
 pub const ETH_BASE_MAINNET: &'static str = "0x4200000000000000000000000000000000000006";
 pub const USDC_BASE_MAINNET: &'static str = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
 pub const DAI_BASE_MAINNET: &'static str = "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb";
-pub const WBTC_BASE_MAINNET: &'static str = "0x1ceA4b36B8F8b2e40A61d0B7ed03D70Cb5AA6d8C";
+pub const WBTC_BASE_MAINNET: &'static str = "0x1ceA4b36B8f8b2e40A61D0B7eD03d70Cb5Aa6d8C";
 
 // HyperEVM Mainnet
 pub const HYPE_HYPEREVM_MAINNET: &'static str = "0x5555555555555555555555555555555555555555";
@@ -68,52 +75,149 @@ pub const USDT_AVALANCHE_MAINNET: &'static str = "0x9702230A8Ea53601f5cD2dc00fDB
 pub const USDC_AVALANCHE_MAINNET: &'static str = "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E";
 pub const DAI_AVALANCHE_MAINNET: &'static str = "0xd586E7F844cEa2F87f50152665BCbc2C279D8d70";
 
+// OP Stack predeploys (Optimism, Base and other OP Stack chains)
+pub const OP_STACK_GAS_PRICE_ORACLE: &'static str = "0x420000000000000000000000000000000000000F";
+
+// Multicall3, deployed at the same address on most EVM chains
+pub const MULTICALL3: &'static str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Quote tokens recognised by `is_quote`
+pub const QUOTE_TOKENS: &[&str] = &[
+    ETH_ETHEREUM_MAINNET,
+    USDT_ETHEREUM_MAINNET,
+    USDC_ETHEREUM_MAINNET,
+    DAI_ETHEREUM_MAINNET,
+    WBTC_ETHEREUM_MAINNET,
+    ETH_ARB_MAINNET,
+    USDT_ARB_MAINNET,
+    USDC_ARB_MAINNET,
+    DAI_ARB_MAINNET,
+    WBTC_ARB_MAINNET,
+    BNB_BSC_MAINNET,
+    USDT_BSC_MAINNET,
+    USDC_BSC_MAINNET,
+    BUSD_BSC_MAINNET,
+    ETH_BSC_MAINNET,
+    WBTC_BSC_MAINNET,
+    ETH_BASE_MAINNET,
+    USDC_BASE_MAINNET,
+    DAI_BASE_MAINNET,
+    WBTC_BASE_MAINNET,
+    HYPE_HYPEREVM_MAINNET,
+    ETH_HYPEREVM_MAINNET,
+    USDT_HYPEREVM_MAINNET,
+    USDC_HYPEREVM_MAINNET,
+    WXPL_PLASMA_MAINNET,
+    POL_POLYGON_MAINNET,
+    USDT_POLYGON_MAINNET,
+    USDC_POLYGON_MAINNET,
+    WETH_POLYGON_MAINNET,
+    ETH_OPTIMISM_MAINNET,
+    USDT_OPTIMISM_MAINNET,
+    USDC_OPTIMISM_MAINNET,
+    DAI_OPTIMISM_MAINNET,
+    ETH_ZKSYNC_MAINNET,
+    USDC_ZKSYNC_MAINNET,
+    ZK_ZKSYNC_MAINNET,
+    ETH_STARKNET_MAINNET,
+    USDT_STARKNET_MAINNET,
+    USDC_STARKNET_MAINNET,
+    WBTC_STARKNET_MAINNET,
+    WAVAX_AVALANCHE_MAINNET,
+    USDT_AVALANCHE_MAINNET,
+    USDC_AVALANCHE_MAINNET,
+    DAI_AVALANCHE_MAINNET,
+];
+
+fn quote_addresses() -> &'static HashSet<Address> {
+    static QUOTES: OnceLock<HashSet<Address>> = OnceLock::new();
+    QUOTES.get_or_init(|| {
+        QUOTE_TOKENS
+            .iter()
+            .filter_map(|quote| quote.parse().ok())
+            .collect()
+    })
+}
+
 pub fn is_quote(address: &str) -> bool {
-    let address_lower = address.to_lowercase();
-    address_lower == ETH_ETHEREUM_MAINNET.to_lowercase()
-        || address_lower == USDT_ETHEREUM_MAINNET.to_lowercase()
-        || address_lower == USDC_ETHEREUM_MAINNET.to_lowercase()
-        || address_lower == DAI_ETHEREUM_MAINNET.to_lowercase()
-        || address_lower == WBTC_ETHEREUM_MAINNET.to_lowercase()
-        || address_lower == ETH_ARB_MAINNET.to_lowercase()
-        || address_lower == USDT_ARB_MAINNET.to_lowercase()
-        || address_lower == USDC_ARB_MAINNET.to_lowercase()
-        || address_lower == DAI_ARB_MAINNET.to_lowercase()
-        || address_lower == WBTC_ARB_MAINNET.to_lowercase()
-        || address_lower == BNB_BSC_MAINNET.to_lowercase()
-        || address_lower == USDT_BSC_MAINNET.to_lowercase()
-        || address_lower == USDC_BSC_MAINNET.to_lowercase()
-        || address_lower == BUSD_BSC_MAINNET.to_lowercase()
-        || address_lower == ETH_BSC_MAINNET.to_lowercase()
-        || address_lower == WBTC_BSC_MAINNET.to_lowercase()
-        || address_lower == ETH_BASE_MAINNET.to_lowercase()
-        || address_lower == USDC_BASE_MAINNET.to_lowercase()
-        || address_lower == DAI_BASE_MAINNET.to_lowercase()
-        || address_lower == WBTC_BASE_MAINNET.to_lowercase()
-        || address_lower == HYPE_HYPEREVM_MAINNET.to_lowercase()
-        || address_lower == ETH_HYPEREVM_MAINNET.to_lowercase()
-        || address_lower == USDT_HYPEREVM_MAINNET.to_lowercase()
-        || address_lower == USDC_HYPEREVM_MAINNET.to_lowercase()
-        || address_lower == WXPL_PLASMA_MAINNET.to_lowercase()
-        || address_lower == POL_POLYGON_MAINNET.to_lowercase()
-        || address_lower == USDT_POLYGON_MAINNET.to_lowercase()
-        || address_lower == USDC_POLYGON_MAINNET.to_lowercase()
-        || address_lower == WETH_POLYGON_MAINNET.to_lowercase()
-        || address_lower == ETH_OPTIMISM_MAINNET.to_lowercase()
-        || address_lower == USDT_OPTIMISM_MAINNET.to_lowercase()
-        || address_lower == USDC_OPTIMISM_MAINNET.to_lowercase()
-        || address_lower == DAI_OPTIMISM_MAINNET.to_lowercase()
-        || address_lower == ETH_ZKSYNC_MAINNET.to_lowercase()
-        || address_lower == USDC_ZKSYNC_MAINNET.to_lowercase()
-        || address_lower == ZK_ZKSYNC_MAINNET.to_lowercase()
-        || address_lower == ETH_STARKNET_MAINNET.to_lowercase()
-        || address_lower == USDT_STARKNET_MAINNET.to_lowercase()
-        || address_lower == USDC_STARKNET_MAINNET.to_lowercase()
-        || address_lower == WBTC_STARKNET_MAINNET.to_lowercase()
-        || address_lower == WAVAX_AVALANCHE_MAINNET.to_lowercase()
-        || address_lower == USDT_AVALANCHE_MAINNET.to_lowercase()
-        || address_lower == USDC_AVALANCHE_MAINNET.to_lowercase()
-        || address_lower == DAI_AVALANCHE_MAINNET.to_lowercase()
+    match address.parse::<Address>() {
+        Ok(address) => is_quote_address(&address),
+        // Not an EVM address, e.g. a StarkNet token
+        Err(_) => QUOTE_TOKENS
+            .iter()
+            .any(|quote| quote.eq_ignore_ascii_case(address)),
+    }
+}
+
+/// Same as `is_quote` for an already parsed address, without any allocation
+pub fn is_quote_address(address: &Address) -> bool {
+    quote_addresses().contains(address)
+}
+
+/// Wrapped native gas tokens (WETH, WBNB, WAVAX, ...), whose amounts are directly
+/// comparable to gas costs
+pub const WRAPPED_NATIVE_TOKENS: &[&str] = &[
+    ETH_ETHEREUM_MAINNET,
+    ETH_ARB_MAINNET,
+    BNB_BSC_MAINNET,
+    ETH_BASE_MAINNET,
+    HYPE_HYPEREVM_MAINNET,
+    WXPL_PLASMA_MAINNET,
+    ETH_OPTIMISM_MAINNET,
+    WAVAX_AVALANCHE_MAINNET,
+];
+
+/// Chain id of a mainnet by the network name used in this module and by `EvmType`
+///
+/// The name is matched case-insensitively with or without the `_MAINNET` suffix, so
+/// `"ETHEREUM_MAINNET"`, `"Ethereum"` and `"ethereum"` all give 1. StarkNet and unknown
+/// networks yield `None`.
+pub fn chain_id_by_network(name: &str) -> Option<u64> {
+    let name = name.to_uppercase();
+    match name.strip_suffix("_MAINNET").unwrap_or(&name) {
+        "ETHEREUM" | "ETH" => Some(1),
+        "OPTIMISM" | "OP" => Some(10),
+        "BSC" | "BNB" => Some(56),
+        "POLYGON" => Some(137),
+        "ZKSYNC" => Some(324),
+        "HYPEREVM" => Some(999),
+        "BASE" => Some(8453),
+        "PLASMA" => Some(9745),
+        "ARB" | "ARBITRUM" => Some(42161),
+        "AVALANCHE" | "AVAX" => Some(43114),
+        _ => None,
+    }
+}
+
+/// Wrapped native token contract of a chain, by chain id
+///
+/// Polygon and zkSync are absent: their `*_MAINNET` native constants are system
+/// contracts, not WETH-style wrappers.
+pub fn wrapped_native_by_chain_id(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        1 => Some(ETH_ETHEREUM_MAINNET),
+        10 => Some(ETH_OPTIMISM_MAINNET),
+        56 => Some(BNB_BSC_MAINNET),
+        999 => Some(HYPE_HYPEREVM_MAINNET),
+        8453 => Some(ETH_BASE_MAINNET),
+        9745 => Some(WXPL_PLASMA_MAINNET),
+        42161 => Some(ETH_ARB_MAINNET),
+        43114 => Some(WAVAX_AVALANCHE_MAINNET),
+        _ => None,
+    }
+}
+
+/// Whether `address` is the wrapped native token of a supported chain
+pub fn is_wrapped_native(address: &Address) -> bool {
+    static WRAPPED: OnceLock<HashSet<Address>> = OnceLock::new();
+    WRAPPED
+        .get_or_init(|| {
+            WRAPPED_NATIVE_TOKENS
+                .iter()
+                .filter_map(|token| token.parse().ok())
+                .collect()
+        })
+        .contains(address)
 }
 
 pub fn get_block_time_by_address(address: &str) -> Option<u64> {
@@ -303,72 +407,94 @@ pub fn get_common_dex_event_signatures() -> Vec<(&'static str, &'static str)> {
     ]
 }
 
-pub fn get_dex_name_by_address(address: &str) -> Option<&'static str> {
-    let address_lower = address.to_lowercase();
-    let verified_dex_mappings: [(&str, &str); 43] = [
-        // Ethereum
-        (UNISWAP_V2_FACTORY_ETHEREUM, "Uniswap V2 Factory"),
-        (UNISWAP_V2_ROUTER_ETHEREUM, "Uniswap V2 Router"),
-        (UNISWAP_V3_FACTORY_ETHEREUM, "Uniswap V3 Factory"),
-        (UNISWAP_V3_ROUTER_ETHEREUM, "Uniswap V3 Router"),
-        (SUSHI_FACTORY_ETHEREUM, "SushiSwap Factory"),
-        (SUSHI_ROUTER_ETHEREUM, "SushiSwap Router"),
-        (CURVE_FACTORY_ETHEREUM, "Curve Factory"),
-        (CURVE_3POOL_ETHEREUM, "Curve 3pool"),
-        (BALANCER_VAULT_ETHEREUM, "Balancer Vault"),
-        (INCH_V4_ROUTER_ETHEREUM, "1inch V4 Router"),
-        // Arbitrum
-        (UNISWAP_V3_FACTORY_ARBITRUM, "Uniswap V3 Factory (Arbitrum)"),
-        (UNISWAP_V3_ROUTER_ARBITRUM, "Uniswap V3 Router (Arbitrum)"),
-        (SUSHI_FACTORY_ARBITRUM, "SushiSwap Factory (Arbitrum)"),
-        (SUSHI_ROUTER_ARBITRUM, "SushiSwap Router (Arbitrum)"),
-        // BSC
-        (PANCAKE_V2_FACTORY_BSC, "PancakeSwap V2 Factory"),
-        (PANCAKE_V2_ROUTER_BSC, "PancakeSwap V2 Router"),
-        (PANCAKE_V3_FACTORY_BSC, "PancakeSwap V3 Factory"),
-        (PANCAKE_V3_ROUTER_BSC, "PancakeSwap V3 Router"),
-        (BISWAP_FACTORY_BSC, "BiSwap Factory"),
-        (BISWAP_ROUTER_BSC, "BiSwap Router"),
-        // Polygon
-        (UNISWAP_V3_FACTORY_POLYGON, "Uniswap V3 Factory (Polygon)"),
-        (UNISWAP_V3_ROUTER_POLYGON, "Uniswap V3 Router (Polygon)"),
-        (QUICKSWAP_FACTORY_POLYGON, "QuickSwap Factory"),
-        (QUICKSWAP_ROUTER_POLYGON, "QuickSwap Router"),
-        (SUSHI_FACTORY_POLYGON, "SushiSwap Factory (Polygon)"),
-        (SUSHI_ROUTER_POLYGON, "SushiSwap Router (Polygon)"),
-        // Optimism
-        (UNISWAP_V3_FACTORY_OPTIMISM, "Uniswap V3 Factory (Optimism)"),
-        (UNISWAP_V3_ROUTER_OPTIMISM, "Uniswap V3 Router (Optimism)"),
-        (VELODROME_V2_FACTORY_OPTIMISM, "Velodrome V2 Factory"),
-        (VELODROME_V2_ROUTER_OPTIMISM, "Velodrome V2 Router"),
-        // Avalanche
-        (
-            UNISWAP_V3_FACTORY_AVALANCHE,
-            "Uniswap V3 Factory (Avalanche)",
-        ),
-        (UNISWAP_V3_ROUTER_AVALANCHE, "Uniswap V3 Router (Avalanche)"),
-        (TRADER_JOE_FACTORY_AVALANCHE, "Trader Joe Factory"),
-        (TRADER_JOE_ROUTER_AVALANCHE, "Trader Joe Router"),
-        // Base
-        (UNISWAP_V3_FACTORY_BASE, "Uniswap V3 Factory (Base)"),
-        (UNISWAP_V3_ROUTER_BASE, "Uniswap V3 Router (Base)"),
-        (BASESWAP_FACTORY_BASE, "BaseSwap Factory"),
-        (BASESWAP_ROUTER_BASE, "BaseSwap Router"),
-        // Fantom
-        (SPOOKYSWAP_FACTORY_FANTOM, "SpookySwap Factory"),
-        (SPOOKYSWAP_ROUTER_FANTOM, "SpookySwap Router"),
-        // zkSync
-        (SYNC_SWAP_FACTORY_ZKSYNC, "SyncSwap Factory"),
-        (SYNC_SWAP_ROUTER_ZKSYNC, "SyncSwap Router"),
-        // StarkNet
-        (MY_SWAP_ROUTER_STARKNET, "MySwap Router (StarkNet)"),
-    ];
-    for (dex_addr, dex_name) in &verified_dex_mappings {
-        if address_lower == dex_addr.to_lowercase() {
-            return Some(dex_name);
+/// Known DEX contracts and their names. When several chains share a contract
+/// address, the first entry names it.
+pub const DEX_CONTRACTS: &[(&str, &str)] = &[
+    // Ethereum
+    (UNISWAP_V2_FACTORY_ETHEREUM, "Uniswap V2 Factory"),
+    (UNISWAP_V2_ROUTER_ETHEREUM, "Uniswap V2 Router"),
+    (UNISWAP_V3_FACTORY_ETHEREUM, "Uniswap V3 Factory"),
+    (UNISWAP_V3_ROUTER_ETHEREUM, "Uniswap V3 Router"),
+    (SUSHI_FACTORY_ETHEREUM, "SushiSwap Factory"),
+    (SUSHI_ROUTER_ETHEREUM, "SushiSwap Router"),
+    (CURVE_FACTORY_ETHEREUM, "Curve Factory"),
+    (CURVE_3POOL_ETHEREUM, "Curve 3pool"),
+    (BALANCER_VAULT_ETHEREUM, "Balancer Vault"),
+    (INCH_V4_ROUTER_ETHEREUM, "1inch V4 Router"),
+    // Arbitrum
+    (UNISWAP_V3_FACTORY_ARBITRUM, "Uniswap V3 Factory (Arbitrum)"),
+    (UNISWAP_V3_ROUTER_ARBITRUM, "Uniswap V3 Router (Arbitrum)"),
+    (SUSHI_FACTORY_ARBITRUM, "SushiSwap Factory (Arbitrum)"),
+    (SUSHI_ROUTER_ARBITRUM, "SushiSwap Router (Arbitrum)"),
+    // BSC
+    (PANCAKE_V2_FACTORY_BSC, "PancakeSwap V2 Factory"),
+    (PANCAKE_V2_ROUTER_BSC, "PancakeSwap V2 Router"),
+    (PANCAKE_V3_FACTORY_BSC, "PancakeSwap V3 Factory"),
+    (PANCAKE_V3_ROUTER_BSC, "PancakeSwap V3 Router"),
+    (BISWAP_FACTORY_BSC, "BiSwap Factory"),
+    (BISWAP_ROUTER_BSC, "BiSwap Router"),
+    // Polygon
+    (UNISWAP_V3_FACTORY_POLYGON, "Uniswap V3 Factory (Polygon)"),
+    (UNISWAP_V3_ROUTER_POLYGON, "Uniswap V3 Router (Polygon)"),
+    (QUICKSWAP_FACTORY_POLYGON, "QuickSwap Factory"),
+    (QUICKSWAP_ROUTER_POLYGON, "QuickSwap Router"),
+    (SUSHI_FACTORY_POLYGON, "SushiSwap Factory (Polygon)"),
+    (SUSHI_ROUTER_POLYGON, "SushiSwap Router (Polygon)"),
+    // Optimism
+    (UNISWAP_V3_FACTORY_OPTIMISM, "Uniswap V3 Factory (Optimism)"),
+    (UNISWAP_V3_ROUTER_OPTIMISM, "Uniswap V3 Router (Optimism)"),
+    (VELODROME_V2_FACTORY_OPTIMISM, "Velodrome V2 Factory"),
+    (VELODROME_V2_ROUTER_OPTIMISM, "Velodrome V2 Router"),
+    // Avalanche
+    (
+        UNISWAP_V3_FACTORY_AVALANCHE,
+        "Uniswap V3 Factory (Avalanche)",
+    ),
+    (UNISWAP_V3_ROUTER_AVALANCHE, "Uniswap V3 Router (Avalanche)"),
+    (TRADER_JOE_FACTORY_AVALANCHE, "Trader Joe Factory"),
+    (TRADER_JOE_ROUTER_AVALANCHE, "Trader Joe Router"),
+    // Base
+    (UNISWAP_V3_FACTORY_BASE, "Uniswap V3 Factory (Base)"),
+    (UNISWAP_V3_ROUTER_BASE, "Uniswap V3 Router (Base)"),
+    (BASESWAP_FACTORY_BASE, "BaseSwap Factory"),
+    (BASESWAP_ROUTER_BASE, "BaseSwap Router"),
+    // Fantom
+    (SPOOKYSWAP_FACTORY_FANTOM, "SpookySwap Factory"),
+    (SPOOKYSWAP_ROUTER_FANTOM, "SpookySwap Router"),
+    // zkSync
+    (SYNC_SWAP_FACTORY_ZKSYNC, "SyncSwap Factory"),
+    (SYNC_SWAP_ROUTER_ZKSYNC, "SyncSwap Router"),
+    // StarkNet
+    (MY_SWAP_ROUTER_STARKNET, "MySwap Router (StarkNet)"),
+];
+
+fn dex_names() -> &'static HashMap<Address, &'static str> {
+    static DEX_NAMES: OnceLock<HashMap<Address, &'static str>> = OnceLock::new();
+    DEX_NAMES.get_or_init(|| {
+        let mut names = HashMap::new();
+        for (dex_address, dex_name) in DEX_CONTRACTS {
+            if let Ok(dex_address) = dex_address.parse::<Address>() {
+                names.entry(dex_address).or_insert(*dex_name);
+            }
         }
+        names
+    })
+}
+
+pub fn get_dex_name_by_address(address: &str) -> Option<&'static str> {
+    match address.parse::<Address>() {
+        Ok(address) => get_dex_name(&address),
+        // Not an EVM address, e.g. a StarkNet contract
+        Err(_) => DEX_CONTRACTS
+            .iter()
+            .find(|(dex_address, _)| dex_address.eq_ignore_ascii_case(address))
+            .map(|(_, dex_name)| *dex_name),
     }
-    None
+}
+
+/// Same as `get_dex_name_by_address` for an already parsed address, without any allocation
+pub fn get_dex_name(address: &Address) -> Option<&'static str> {
+    dex_names().get(address).copied()
 }
 
 pub fn get_known_dexes_for_network(evm_type: &str) -> Vec<&'static str> {
@@ -396,6 +522,237 @@ pub fn is_dex_contract(address: &str) -> bool {
     get_dex_name_by_address(address).is_some()
 }
 
+/// Every address constant of this module, by name
+pub const ADDRESS_CONSTANTS: &[(&str, &str)] = &[
+    ("ETH_ETHEREUM_MAINNET", ETH_ETHEREUM_MAINNET),
+    ("USDT_ETHEREUM_MAINNET", USDT_ETHEREUM_MAINNET),
+    ("USDC_ETHEREUM_MAINNET", USDC_ETHEREUM_MAINNET),
+    ("DAI_ETHEREUM_MAINNET", DAI_ETHEREUM_MAINNET),
+    ("WBTC_ETHEREUM_MAINNET", WBTC_ETHEREUM_MAINNET),
+    ("ETH_ARB_MAINNET", ETH_ARB_MAINNET),
+    ("USDT_ARB_MAINNET", USDT_ARB_MAINNET),
+    ("USDC_ARB_MAINNET", USDC_ARB_MAINNET),
+    ("DAI_ARB_MAINNET", DAI_ARB_MAINNET),
+    ("WBTC_ARB_MAINNET", WBTC_ARB_MAINNET),
+    ("BNB_BSC_MAINNET", BNB_BSC_MAINNET),
+    ("USDT_BSC_MAINNET", USDT_BSC_MAINNET),
+    ("USDC_BSC_MAINNET", USDC_BSC_MAINNET),
+    ("BUSD_BSC_MAINNET", BUSD_BSC_MAINNET),
+    ("ETH_BSC_MAINNET", ETH_BSC_MAINNET),
+    ("WBTC_BSC_MAINNET", WBTC_BSC_MAINNET),
+    ("ETH_BASE_MAINNET", ETH_BASE_MAINNET),
+    ("USDC_BASE_MAINNET", USDC_BASE_MAINNET),
+    ("DAI_BASE_MAINNET", DAI_BASE_MAINNET),
+    ("WBTC_BASE_MAINNET", WBTC_BASE_MAINNET),
+    ("HYPE_HYPEREVM_MAINNET", HYPE_HYPEREVM_MAINNET),
+    ("ETH_HYPEREVM_MAINNET", ETH_HYPEREVM_MAINNET),
+    ("USDT_HYPEREVM_MAINNET", USDT_HYPEREVM_MAINNET),
+    ("USDC_HYPEREVM_MAINNET", USDC_HYPEREVM_MAINNET),
+    ("WXPL_PLASMA_MAINNET", WXPL_PLASMA_MAINNET),
+    ("POL_POLYGON_MAINNET", POL_POLYGON_MAINNET),
+    ("USDT_POLYGON_MAINNET", USDT_POLYGON_MAINNET),
+    ("USDC_POLYGON_MAINNET", USDC_POLYGON_MAINNET),
+    ("WETH_POLYGON_MAINNET", WETH_POLYGON_MAINNET),
+    ("ETH_OPTIMISM_MAINNET", ETH_OPTIMISM_MAINNET),
+    ("USDT_OPTIMISM_MAINNET", USDT_OPTIMISM_MAINNET),
+    ("USDC_OPTIMISM_MAINNET", USDC_OPTIMISM_MAINNET),
+    ("DAI_OPTIMISM_MAINNET", DAI_OPTIMISM_MAINNET),
+    ("ETH_ZKSYNC_MAINNET", ETH_ZKSYNC_MAINNET),
+    ("USDC_ZKSYNC_MAINNET", USDC_ZKSYNC_MAINNET),
+    ("ZK_ZKSYNC_MAINNET", ZK_ZKSYNC_MAINNET),
+    ("ETH_STARKNET_MAINNET", ETH_STARKNET_MAINNET),
+    ("USDT_STARKNET_MAINNET", USDT_STARKNET_MAINNET),
+    ("USDC_STARKNET_MAINNET", USDC_STARKNET_MAINNET),
+    ("WBTC_STARKNET_MAINNET", WBTC_STARKNET_MAINNET),
+    ("WAVAX_AVALANCHE_MAINNET", WAVAX_AVALANCHE_MAINNET),
+    ("USDT_AVALANCHE_MAINNET", USDT_AVALANCHE_MAINNET),
+    ("USDC_AVALANCHE_MAINNET", USDC_AVALANCHE_MAINNET),
+    ("DAI_AVALANCHE_MAINNET", DAI_AVALANCHE_MAINNET),
+    ("OP_STACK_GAS_PRICE_ORACLE", OP_STACK_GAS_PRICE_ORACLE),
+    ("MULTICALL3", MULTICALL3),
+    ("UNISWAP_V2_FACTORY_ETHEREUM", UNISWAP_V2_FACTORY_ETHEREUM),
+    ("UNISWAP_V2_ROUTER_ETHEREUM", UNISWAP_V2_ROUTER_ETHEREUM),
+    ("UNISWAP_V3_FACTORY_ETHEREUM", UNISWAP_V3_FACTORY_ETHEREUM),
+    ("UNISWAP_V3_ROUTER_ETHEREUM", UNISWAP_V3_ROUTER_ETHEREUM),
+    ("SUSHI_FACTORY_ETHEREUM", SUSHI_FACTORY_ETHEREUM),
+    ("SUSHI_ROUTER_ETHEREUM", SUSHI_ROUTER_ETHEREUM),
+    ("CURVE_FACTORY_ETHEREUM", CURVE_FACTORY_ETHEREUM),
+    ("CURVE_3POOL_ETHEREUM", CURVE_3POOL_ETHEREUM),
+    ("BALANCER_VAULT_ETHEREUM", BALANCER_VAULT_ETHEREUM),
+    ("INCH_V4_ROUTER_ETHEREUM", INCH_V4_ROUTER_ETHEREUM),
+    ("UNISWAP_V3_FACTORY_ARBITRUM", UNISWAP_V3_FACTORY_ARBITRUM),
+    ("UNISWAP_V3_ROUTER_ARBITRUM", UNISWAP_V3_ROUTER_ARBITRUM),
+    ("SUSHI_FACTORY_ARBITRUM", SUSHI_FACTORY_ARBITRUM),
+    ("SUSHI_ROUTER_ARBITRUM", SUSHI_ROUTER_ARBITRUM),
+    ("PANCAKE_V2_FACTORY_BSC", PANCAKE_V2_FACTORY_BSC),
+    ("PANCAKE_V2_ROUTER_BSC", PANCAKE_V2_ROUTER_BSC),
+    ("PANCAKE_V3_FACTORY_BSC", PANCAKE_V3_FACTORY_BSC),
+    ("PANCAKE_V3_ROUTER_BSC", PANCAKE_V3_ROUTER_BSC),
+    ("BISWAP_FACTORY_BSC", BISWAP_FACTORY_BSC),
+    ("BISWAP_ROUTER_BSC", BISWAP_ROUTER_BSC),
+    ("UNISWAP_V3_FACTORY_POLYGON", UNISWAP_V3_FACTORY_POLYGON),
+    ("UNISWAP_V3_ROUTER_POLYGON", UNISWAP_V3_ROUTER_POLYGON),
+    ("QUICKSWAP_FACTORY_POLYGON", QUICKSWAP_FACTORY_POLYGON),
+    ("QUICKSWAP_ROUTER_POLYGON", QUICKSWAP_ROUTER_POLYGON),
+    ("SUSHI_FACTORY_POLYGON", SUSHI_FACTORY_POLYGON),
+    ("SUSHI_ROUTER_POLYGON", SUSHI_ROUTER_POLYGON),
+    ("UNISWAP_V3_FACTORY_OPTIMISM", UNISWAP_V3_FACTORY_OPTIMISM),
+    ("UNISWAP_V3_ROUTER_OPTIMISM", UNISWAP_V3_ROUTER_OPTIMISM),
+    (
+        "VELODROME_V2_FACTORY_OPTIMISM",
+        VELODROME_V2_FACTORY_OPTIMISM,
+    ),
+    ("VELODROME_V2_ROUTER_OPTIMISM", VELODROME_V2_ROUTER_OPTIMISM),
+    ("UNISWAP_V3_FACTORY_AVALANCHE", UNISWAP_V3_FACTORY_AVALANCHE),
+    ("UNISWAP_V3_ROUTER_AVALANCHE", UNISWAP_V3_ROUTER_AVALANCHE),
+    ("TRADER_JOE_FACTORY_AVALANCHE", TRADER_JOE_FACTORY_AVALANCHE),
+    ("TRADER_JOE_ROUTER_AVALANCHE", TRADER_JOE_ROUTER_AVALANCHE),
+    ("UNISWAP_V3_FACTORY_BASE", UNISWAP_V3_FACTORY_BASE),
+    ("UNISWAP_V3_ROUTER_BASE", UNISWAP_V3_ROUTER_BASE),
+    ("BASESWAP_FACTORY_BASE", BASESWAP_FACTORY_BASE),
+    ("BASESWAP_ROUTER_BASE", BASESWAP_ROUTER_BASE),
+    ("SPOOKYSWAP_FACTORY_FANTOM", SPOOKYSWAP_FACTORY_FANTOM),
+    ("SPOOKYSWAP_ROUTER_FANTOM", SPOOKYSWAP_ROUTER_FANTOM),
+    ("SYNC_SWAP_FACTORY_ZKSYNC", SYNC_SWAP_FACTORY_ZKSYNC),
+    ("SYNC_SWAP_ROUTER_ZKSYNC", SYNC_SWAP_ROUTER_ZKSYNC),
+    ("MY_SWAP_ROUTER_STARKNET", MY_SWAP_ROUTER_STARKNET),
+];
+
+fn parsed_constants() -> &'static HashMap<&'static str, Address> {
+    static PARSED: OnceLock<HashMap<&'static str, Address>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        ADDRESS_CONSTANTS
+            .iter()
+            .filter_map(|(_, value)| Some((*value, value.parse().ok()?)))
+            .collect()
+    })
+}
+
+/// Parsed `Address` of an address constant, e.g. `address_of(USDC_ETHEREUM_MAINNET)`.
+///
+/// Constants of this module are parsed once and cached; any other string is parsed
+/// on the fly. StarkNet constants are not 20-byte EVM addresses and yield `None`.
+pub fn address_of(constant: &str) -> Option<Address> {
+    parsed_constants()
+        .get(constant)
+        .copied()
+        .or_else(|| constant.parse().ok())
+}
+
+/// Role of every well-known contract constant, see `identify_address`
+///
+/// Utility contracts such as Multicall3 and the OP Stack gas price oracle have no role
+/// and are left out, as are StarkNet constants which are not EVM addresses.
+pub const ADDRESS_ROLES: &[(&str, ContractRole)] = &[
+    (ETH_ETHEREUM_MAINNET, ContractRole::Token),
+    (USDT_ETHEREUM_MAINNET, ContractRole::Token),
+    (USDC_ETHEREUM_MAINNET, ContractRole::Token),
+    (DAI_ETHEREUM_MAINNET, ContractRole::Token),
+    (WBTC_ETHEREUM_MAINNET, ContractRole::Token),
+    (ETH_ARB_MAINNET, ContractRole::Token),
+    (USDT_ARB_MAINNET, ContractRole::Token),
+    (USDC_ARB_MAINNET, ContractRole::Token),
+    (DAI_ARB_MAINNET, ContractRole::Token),
+    (WBTC_ARB_MAINNET, ContractRole::Token),
+    (BNB_BSC_MAINNET, ContractRole::Token),
+    (USDT_BSC_MAINNET, ContractRole::Token),
+    (USDC_BSC_MAINNET, ContractRole::Token),
+    (BUSD_BSC_MAINNET, ContractRole::Token),
+    (ETH_BSC_MAINNET, ContractRole::Token),
+    (WBTC_BSC_MAINNET, ContractRole::Token),
+    (ETH_BASE_MAINNET, ContractRole::Token),
+    (USDC_BASE_MAINNET, ContractRole::Token),
+    (DAI_BASE_MAINNET, ContractRole::Token),
+    (WBTC_BASE_MAINNET, ContractRole::Token),
+    (HYPE_HYPEREVM_MAINNET, ContractRole::Token),
+    (ETH_HYPEREVM_MAINNET, ContractRole::Token),
+    (USDT_HYPEREVM_MAINNET, ContractRole::Token),
+    (USDC_HYPEREVM_MAINNET, ContractRole::Token),
+    (WXPL_PLASMA_MAINNET, ContractRole::Token),
+    (POL_POLYGON_MAINNET, ContractRole::Token),
+    (USDT_POLYGON_MAINNET, ContractRole::Token),
+    (USDC_POLYGON_MAINNET, ContractRole::Token),
+    (WETH_POLYGON_MAINNET, ContractRole::Token),
+    (ETH_OPTIMISM_MAINNET, ContractRole::Token),
+    (USDT_OPTIMISM_MAINNET, ContractRole::Token),
+    (USDC_OPTIMISM_MAINNET, ContractRole::Token),
+    (DAI_OPTIMISM_MAINNET, ContractRole::Token),
+    (ETH_ZKSYNC_MAINNET, ContractRole::Token),
+    (USDC_ZKSYNC_MAINNET, ContractRole::Token),
+    (ZK_ZKSYNC_MAINNET, ContractRole::Token),
+    (WAVAX_AVALANCHE_MAINNET, ContractRole::Token),
+    (USDT_AVALANCHE_MAINNET, ContractRole::Token),
+    (USDC_AVALANCHE_MAINNET, ContractRole::Token),
+    (DAI_AVALANCHE_MAINNET, ContractRole::Token),
+    (CURVE_3POOL_ETHEREUM, ContractRole::Pool),
+    (BALANCER_VAULT_ETHEREUM, ContractRole::Pool),
+    (UNISWAP_V2_ROUTER_ETHEREUM, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_ETHEREUM, ContractRole::Router),
+    (SUSHI_ROUTER_ETHEREUM, ContractRole::Router),
+    (INCH_V4_ROUTER_ETHEREUM, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_ARBITRUM, ContractRole::Router),
+    (SUSHI_ROUTER_ARBITRUM, ContractRole::Router),
+    (PANCAKE_V2_ROUTER_BSC, ContractRole::Router),
+    (PANCAKE_V3_ROUTER_BSC, ContractRole::Router),
+    (BISWAP_ROUTER_BSC, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_POLYGON, ContractRole::Router),
+    (QUICKSWAP_ROUTER_POLYGON, ContractRole::Router),
+    (SUSHI_ROUTER_POLYGON, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_OPTIMISM, ContractRole::Router),
+    (VELODROME_V2_ROUTER_OPTIMISM, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_AVALANCHE, ContractRole::Router),
+    (TRADER_JOE_ROUTER_AVALANCHE, ContractRole::Router),
+    (UNISWAP_V3_ROUTER_BASE, ContractRole::Router),
+    (BASESWAP_ROUTER_BASE, ContractRole::Router),
+    (SPOOKYSWAP_ROUTER_FANTOM, ContractRole::Router),
+    (SYNC_SWAP_ROUTER_ZKSYNC, ContractRole::Router),
+    (UNISWAP_V2_FACTORY_ETHEREUM, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_ETHEREUM, ContractRole::Factory),
+    (SUSHI_FACTORY_ETHEREUM, ContractRole::Factory),
+    (CURVE_FACTORY_ETHEREUM, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_ARBITRUM, ContractRole::Factory),
+    (SUSHI_FACTORY_ARBITRUM, ContractRole::Factory),
+    (PANCAKE_V2_FACTORY_BSC, ContractRole::Factory),
+    (PANCAKE_V3_FACTORY_BSC, ContractRole::Factory),
+    (BISWAP_FACTORY_BSC, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_POLYGON, ContractRole::Factory),
+    (QUICKSWAP_FACTORY_POLYGON, ContractRole::Factory),
+    (SUSHI_FACTORY_POLYGON, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_OPTIMISM, ContractRole::Factory),
+    (VELODROME_V2_FACTORY_OPTIMISM, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_AVALANCHE, ContractRole::Factory),
+    (TRADER_JOE_FACTORY_AVALANCHE, ContractRole::Factory),
+    (UNISWAP_V3_FACTORY_BASE, ContractRole::Factory),
+    (BASESWAP_FACTORY_BASE, ContractRole::Factory),
+    (SPOOKYSWAP_FACTORY_FANTOM, ContractRole::Factory),
+    (SYNC_SWAP_FACTORY_ZKSYNC, ContractRole::Factory),
+];
+
+/// Role of a well-known address from `ADDRESS_ROLES`
+///
+/// Returns `None` for unknown addresses and for utility contracts such as Multicall3.
+pub fn identify_address(address: &Address) -> Option<ContractRole> {
+    static ROLES: OnceLock<HashMap<Address, ContractRole>> = OnceLock::new();
+    ROLES
+        .get_or_init(|| {
+            ADDRESS_ROLES
+                .iter()
+                .filter_map(|(constant, role)| Some((address_of(constant)?, *role)))
+                .collect()
+        })
+        .get(address)
+        .copied()
+}
+
+/// EIP-55 checksummed form of an address constant. Strings that are not EVM
+/// addresses (StarkNet constants) are returned unchanged.
+pub fn checksummed(constant: &str) -> String {
+    match address_of(constant) {
+        Some(address) => to_checksum(&address, None),
+        None => constant.to_string(),
+    }
+}
+
 pub mod dex_events {
     use ethers::core::utils::keccak256;
 
@@ -507,3 +864,234 @@ pub mod dex_events {
         }
     }
 }
+
+/// Standard Governor event signatures, shared by Compound Governor Bravo and
+/// OpenZeppelin Governor.
+pub mod governance_events {
+    use ethers::core::utils::keccak256;
+
+    pub const PROPOSAL_CREATED: &'static str = "ProposalCreated(uint256,address,address[],uint256[],string[],bytes[],uint256,uint256,string)";
+    pub const VOTE_CAST: &'static str = "VoteCast(address,uint256,uint8,uint256,string)";
+    pub const PROPOSAL_QUEUED: &'static str = "ProposalQueued(uint256,uint256)";
+    pub const PROPOSAL_EXECUTED: &'static str = "ProposalExecuted(uint256)";
+
+    pub fn proposal_created() -> [u8; 32] {
+        keccak256(PROPOSAL_CREATED.as_bytes())
+    }
+
+    pub fn vote_cast() -> [u8; 32] {
+        keccak256(VOTE_CAST.as_bytes())
+    }
+
+    pub fn proposal_queued() -> [u8; 32] {
+        keccak256(PROPOSAL_QUEUED.as_bytes())
+    }
+
+    pub fn proposal_executed() -> [u8; 32] {
+        keccak256(PROPOSAL_EXECUTED.as_bytes())
+    }
+
+    pub fn is_governance_event(topic0: &[u8]) -> bool {
+        topic0 == &proposal_created()[..]
+            || topic0 == &vote_cast()[..]
+            || topic0 == &proposal_queued()[..]
+            || topic0 == &proposal_executed()[..]
+    }
+}
+
+pub mod ownership_events {
+    use ethers::core::utils::keccak256;
+
+    /// OpenZeppelin `Ownable` ownership change, also emitted on `renounceOwnership`
+    pub const OWNERSHIP_TRANSFERRED: &'static str = "OwnershipTransferred(address,address)";
+
+    pub fn ownership_transferred() -> [u8; 32] {
+        keccak256(OWNERSHIP_TRANSFERRED.as_bytes())
+    }
+}
+
+/// ERC-4626 tokenized vault events
+pub mod vault_events {
+    use ethers::core::utils::keccak256;
+
+    /// `Deposit(sender indexed, owner indexed, assets, shares)`
+    pub const DEPOSIT: &'static str = "Deposit(address,address,uint256,uint256)";
+    /// `Withdraw(sender indexed, receiver indexed, owner indexed, assets, shares)`
+    pub const WITHDRAW: &'static str = "Withdraw(address,address,address,uint256,uint256)";
+
+    pub fn deposit() -> [u8; 32] {
+        keccak256(DEPOSIT.as_bytes())
+    }
+
+    pub fn withdraw() -> [u8; 32] {
+        keccak256(WITHDRAW.as_bytes())
+    }
+
+    pub fn is_vault_event(topic0: &[u8]) -> bool {
+        topic0 == &deposit()[..] || topic0 == &withdraw()[..]
+    }
+}
+
+/// Aave V3 pool and Compound V2 cToken events
+pub mod lending_events {
+    use ethers::core::utils::keccak256;
+
+    /// Aave V3 `Supply(reserve indexed, user, onBehalfOf indexed, amount, referralCode indexed)`
+    pub const AAVE_SUPPLY: &'static str = "Supply(address,address,address,uint256,uint16)";
+    /// Aave V3 `Withdraw(reserve indexed, user indexed, to indexed, amount)`
+    pub const AAVE_WITHDRAW: &'static str = "Withdraw(address,address,address,uint256)";
+    /// Aave V3 `Borrow(reserve indexed, user, onBehalfOf indexed, amount, interestRateMode,
+    /// borrowRate, referralCode indexed)`
+    pub const AAVE_BORROW: &'static str =
+        "Borrow(address,address,address,uint256,uint8,uint256,uint16)";
+    /// Aave V3 `Repay(reserve indexed, user indexed, repayer indexed, amount, useATokens)`
+    pub const AAVE_REPAY: &'static str = "Repay(address,address,address,uint256,bool)";
+    /// Aave V3 `LiquidationCall(collateralAsset indexed, debtAsset indexed, user indexed,
+    /// debtToCover, liquidatedCollateralAmount, liquidator, receiveAToken)`
+    pub const AAVE_LIQUIDATION_CALL: &'static str =
+        "LiquidationCall(address,address,address,uint256,uint256,address,bool)";
+    /// Compound V2 `Mint(minter, mintAmount, mintTokens)`, same topic as Uniswap V2 `Mint`
+    pub const COMPOUND_MINT: &'static str = "Mint(address,uint256,uint256)";
+    /// Compound V2 `Redeem(redeemer, redeemAmount, redeemTokens)`
+    pub const COMPOUND_REDEEM: &'static str = "Redeem(address,uint256,uint256)";
+    /// Compound V2 `Borrow(borrower, borrowAmount, accountBorrows, totalBorrows)`
+    pub const COMPOUND_BORROW: &'static str = "Borrow(address,uint256,uint256,uint256)";
+
+    pub fn aave_supply() -> [u8; 32] {
+        keccak256(AAVE_SUPPLY.as_bytes())
+    }
+
+    pub fn aave_withdraw() -> [u8; 32] {
+        keccak256(AAVE_WITHDRAW.as_bytes())
+    }
+
+    pub fn aave_borrow() -> [u8; 32] {
+        keccak256(AAVE_BORROW.as_bytes())
+    }
+
+    pub fn aave_repay() -> [u8; 32] {
+        keccak256(AAVE_REPAY.as_bytes())
+    }
+
+    pub fn aave_liquidation_call() -> [u8; 32] {
+        keccak256(AAVE_LIQUIDATION_CALL.as_bytes())
+    }
+
+    pub fn compound_mint() -> [u8; 32] {
+        keccak256(COMPOUND_MINT.as_bytes())
+    }
+
+    pub fn compound_redeem() -> [u8; 32] {
+        keccak256(COMPOUND_REDEEM.as_bytes())
+    }
+
+    pub fn compound_borrow() -> [u8; 32] {
+        keccak256(COMPOUND_BORROW.as_bytes())
+    }
+
+    pub fn is_lending_event(topic0: &[u8]) -> bool {
+        [
+            aave_supply(),
+            aave_withdraw(),
+            aave_borrow(),
+            aave_repay(),
+            aave_liquidation_call(),
+            compound_mint(),
+            compound_redeem(),
+            compound_borrow(),
+        ]
+        .iter()
+        .any(|topic| topic0 == &topic[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_constants_are_checksummed() {
+        for (name, value) in ADDRESS_CONSTANTS {
+            if name.ends_with("_STARKNET") || name.contains("_STARKNET_") {
+                assert!(
+                    address_of(value).is_none(),
+                    "{} is not an EVM address",
+                    name
+                );
+                assert_eq!(checksummed(value), *value);
+                continue;
+            }
+            assert!(address_of(value).is_some(), "{} does not parse", name);
+            assert_eq!(checksummed(value), *value, "{} is not checksummed", name);
+        }
+    }
+
+    #[test]
+    fn test_is_quote_lookup() {
+        assert!(is_quote(USDT_ETHEREUM_MAINNET));
+        assert!(is_quote(&USDT_ETHEREUM_MAINNET.to_lowercase()));
+        assert!(is_quote(
+            &USDT_ETHEREUM_MAINNET.to_uppercase().replace("0X", "0x")
+        ));
+        assert!(is_quote(USDC_STARKNET_MAINNET));
+        assert!(!is_quote(UNISWAP_V2_ROUTER_ETHEREUM));
+        assert!(!is_quote("not an address"));
+    }
+
+    #[test]
+    fn test_dex_name_lookup() {
+        assert_eq!(
+            get_dex_name_by_address(&UNISWAP_V2_ROUTER_ETHEREUM.to_lowercase()),
+            Some("Uniswap V2 Router")
+        );
+        // The Uniswap V3 factory shares one address across chains; the first entry wins
+        assert_eq!(
+            get_dex_name_by_address(UNISWAP_V3_FACTORY_POLYGON),
+            Some("Uniswap V3 Factory")
+        );
+        assert_eq!(
+            get_dex_name_by_address(MY_SWAP_ROUTER_STARKNET),
+            Some("MySwap Router (StarkNet)")
+        );
+        assert_eq!(get_dex_name_by_address(USDC_ETHEREUM_MAINNET), None);
+    }
+
+    #[test]
+    fn test_address_of_accepts_any_case() {
+        let lower = USDC_ETHEREUM_MAINNET.to_lowercase();
+        assert_eq!(address_of(&lower), address_of(USDC_ETHEREUM_MAINNET));
+        assert_eq!(checksummed(&lower), USDC_ETHEREUM_MAINNET);
+    }
+
+    #[test]
+    fn test_wrapped_native_by_chain_id() {
+        for chain_id in [1, 10, 56, 999, 8453, 9745, 42161, 43114] {
+            let token = wrapped_native_by_chain_id(chain_id).unwrap();
+            assert!(is_wrapped_native(&address_of(token).unwrap()));
+        }
+        assert_eq!(wrapped_native_by_chain_id(137), None);
+    }
+
+    #[test]
+    fn test_chain_id_by_network() {
+        assert_eq!(chain_id_by_network("ETHEREUM_MAINNET"), Some(1));
+        assert_eq!(chain_id_by_network("Ethereum"), Some(1));
+        assert_eq!(chain_id_by_network("Base"), Some(8453));
+        assert_eq!(chain_id_by_network("ARB_MAINNET"), Some(42161));
+        assert_eq!(chain_id_by_network("STARKNET_MAINNET"), None);
+        assert_eq!(chain_id_by_network("SEPOLIA"), None);
+    }
+
+    #[test]
+    fn test_identify_address() {
+        let role = |constant: &str| identify_address(&address_of(constant).unwrap());
+        assert_eq!(role(USDC_ETHEREUM_MAINNET), Some(ContractRole::Token));
+        assert_eq!(role(BALANCER_VAULT_ETHEREUM), Some(ContractRole::Pool));
+        assert_eq!(role(CURVE_3POOL_ETHEREUM), Some(ContractRole::Pool));
+        assert_eq!(role(INCH_V4_ROUTER_ETHEREUM), Some(ContractRole::Router));
+        assert_eq!(role(PANCAKE_V3_FACTORY_BSC), Some(ContractRole::Factory));
+        assert_eq!(role(MULTICALL3), None);
+        assert_eq!(role(OP_STACK_GAS_PRICE_ORACLE), None);
+        assert_eq!(identify_address(&Address::repeat_byte(0x11)), None);
+    }
+}
@@ -4,13 +4,16 @@ pub mod contract;
 pub mod erc;
 pub mod global;
 pub mod mempool;
+pub mod onchain;
 pub mod safe;
 pub mod tool;
 pub mod trade;
 pub mod types;
-pub mod onchain;
 
-use std::sync::Arc;
+use std::sync::{
+    Arc, RwLock,
+    atomic::{AtomicUsize, Ordering},
+};
 
 use crate::block::BlockService;
 use crate::mempool::MempoolListener;
@@ -18,36 +21,231 @@ use crate::mempool::MempoolService;
 use crate::trade::Trade;
 use crate::trade::TradeEventListener;
 use crate::types::EvmError;
+use crate::types::HealthReport;
+use crate::types::RetryConfig;
+use crate::types::TraceCall;
+use crate::types::TxFees;
+use crate::types::{BundleHash, BundleParams};
 use ethers::providers::Middleware;
 use ethers::providers::StreamExt;
+use ethers::providers::{Http, Provider, ProviderError, Ws};
 use ethers::types::Block;
 use ethers::types::BlockNumber;
 use ethers::{
-    signers::Signer,
-    types::{Address, H256, TransactionRequest, U256},
+    signers::{LocalWallet, Signer},
+    types::{
+        Address, Bytes, H256, TransactionRequest, U256, transaction::eip2718::TypedTransaction,
+    },
+    utils::keccak256,
 };
 use evm_client::EvmClient;
 use evm_client::EvmType;
 
 use ethers::types::Transaction;
 
+/// Blocks sampled by `recommended_fees` when the node has no `eth_maxPriorityFeePerGas`
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Factor applied to the next base fee in `recommended_fees`' `max_fee_per_gas`
+const BASE_FEE_MULTIPLIER: u64 = 2;
+
+/// Safety margin `send_transaction` applies to estimated gas limits by default
+const DEFAULT_GAS_LIMIT_MULTIPLIER: f64 = 1.2;
+
+/// Delay between receipt checks in `send_and_confirm`
+const CONFIRMATION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Maximum ENS lookups in flight during a batch resolution
+const ENS_BATCH_CONCURRENCY: usize = 8;
+
 /// EVM Client for interacting with various EVM chains
 #[derive(Clone)]
 pub struct Evm {
     pub client: EvmClient,
+    /// Maximum gas price `send_transaction` is allowed to pay, shared between clones
+    gas_ceiling: Arc<RwLock<Option<U256>>>,
+    /// Factor applied to estimated gas limits by `send_transaction`, shared between clones
+    gas_limit_multiplier: Arc<RwLock<f64>>,
+    /// Serializes nonce assignment for transactions sent from the wallet
+    nonce_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Key used to sign relay requests, see `set_flashbots_identity`
+    flashbots_identity: Arc<RwLock<Option<LocalWallet>>>,
+    /// Chain id the endpoint must serve, checked by `health_check`
+    expected_chain_id: Option<u64>,
+    /// Backoff applied to failed reads, see `with_retry`
+    retry: Option<RetryConfig>,
+    /// WebSocket connection used for subscriptions, see `with_ws`
+    ws: Option<Provider<Ws>>,
+    /// Endpoints given to `with_rpc_urls`, empty when `client` is the only one
+    endpoints: Arc<Vec<Provider<Http>>>,
+    /// Index in `endpoints` of the endpoint in use, shared between clones
+    active_endpoint: Arc<AtomicUsize>,
 }
 
 impl Evm {
+    fn from_client(client: EvmClient) -> Self {
+        Self {
+            client,
+            gas_ceiling: Arc::new(RwLock::new(None)),
+            gas_limit_multiplier: Arc::new(RwLock::new(DEFAULT_GAS_LIMIT_MULTIPLIER)),
+            nonce_lock: Arc::new(tokio::sync::Mutex::new(())),
+            flashbots_identity: Arc::new(RwLock::new(None)),
+            expected_chain_id: None,
+            retry: None,
+            ws: None,
+            endpoints: Arc::new(Vec::new()),
+            active_endpoint: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Provider of the endpoint requests are sent to
+    ///
+    /// This is the provider of `client`, unless the `Evm` was built by `with_rpc_urls`:
+    /// then it is the endpoint currently in use, which changes on failover.
+    pub fn provider(&self) -> &Provider<Http> {
+        self.endpoints
+            .get(self.active_endpoint.load(Ordering::SeqCst))
+            .unwrap_or(&self.client.provider)
+    }
+
+    /// Switch from the endpoint at `failed` to the next one of `endpoints`
+    ///
+    /// Only the first of several reads failing on the same endpoint moves the index,
+    /// so concurrent failures do not skip a healthy endpoint.
+    fn fail_over(&self, failed: usize) {
+        let next = (failed + 1) % self.endpoints.len().max(1);
+        if self
+            .active_endpoint
+            .compare_exchange(failed, next, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            log::warn!(
+                "RPC endpoint {} is unreachable, failing over to {}",
+                self.endpoints[failed].url(),
+                self.endpoints[next].url()
+            );
+        }
+    }
+
+    /// Attach a WebSocket endpoint used for push-based streams
+    ///
+    /// Requests keep going through the HTTP `provider`; the WebSocket is only
+    /// used for `eth_subscribe` by `listen_latest_blocks`, the transfer watcher of
+    /// `TradeEventListener` and `MempoolListener`, which otherwise poll. The endpoint
+    /// must serve the same chain as the HTTP provider.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let evm = Evm::new(EvmType::ETHEREUM_MAINNET)
+    ///     .await?
+    ///     .with_ws("wss://ethereum-rpc.publicnode.com")
+    ///     .await?;
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn with_ws(mut self, url: &str) -> Result<Self, EvmError> {
+        let ws = Provider::<Ws>::connect(url).await.map_err(|e| {
+            EvmError::ConnectionError(format!("Failed to connect to {}: {}", url, e))
+        })?;
+        let ws_chain_id = ws
+            .get_chainid()
+            .await
+            .map_err(|e| EvmError::ConnectionError(format!("{} is not usable: {}", url, e)))?
+            .as_u64();
+        let chain_id = self.get_chain_id().await?;
+        if ws_chain_id != chain_id {
+            return Err(EvmError::ConfigError(format!(
+                "WebSocket endpoint is on chain {}, HTTP provider on chain {}",
+                ws_chain_id, chain_id
+            )));
+        }
+        self.ws = Some(ws);
+        Ok(self)
+    }
+
+    /// WebSocket provider attached with `with_ws`, if any
+    pub fn ws_provider(&self) -> Option<&Provider<Ws>> {
+        self.ws.as_ref()
+    }
+
+    /// Retry transient read failures (rate limits, gateway errors, timeouts)
+    ///
+    /// Applies to the read-only calls of `Evm` and `Trade`: blocks, balances, nonces,
+    /// receipts, logs and `eth_call`. Submitting transactions is never retried, since a
+    /// request that timed out may still have reached the node. Once the retries are used
+    /// up the last error is returned wrapped in `EvmError::RetriesExhausted`.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let evm = Evm::new(EvmType::ETHEREUM_MAINNET)
+    ///     .await?
+    ///     .with_retry(RetryConfig::default());
+    /// Ok(())
+    /// }
+    /// ```
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Run an idempotent read, retrying transient failures per the `RetryConfig`
+    ///
+    /// `read` marks retryable failures with `EvmError::from_provider`; the marker is
+    /// removed from the error returned. `read` must send its requests through `provider`:
+    /// with several endpoints (`with_rpc_urls`), an unreachable endpoint is replaced by
+    /// the next one and the read is repeated on it right away, once per endpoint, before
+    /// the `RetryConfig` backoff applies.
+    pub(crate) async fn read_with_retry<T, F, Fut>(&self, mut read: F) -> Result<T, EvmError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, EvmError>>,
+    {
+        let mut retries = 0;
+        let mut failovers = 0;
+        loop {
+            let endpoint = self.active_endpoint.load(Ordering::SeqCst);
+            let e = match read().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            if e.is_unreachable() && failovers + 1 < self.endpoints.len() {
+                self.fail_over(endpoint);
+                failovers += 1;
+                continue;
+            }
+            match self.retry.as_ref() {
+                Some(config) if e.is_transient() && retries < config.max_retries => {
+                    let delay = config.backoff(retries);
+                    log::debug!("Retrying in {:?} after transient error: {}", delay, e);
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                    failovers = 0;
+                }
+                Some(_) if retries > 0 && e.is_transient() => {
+                    return Err(EvmError::RetriesExhausted(
+                        retries,
+                        Box::new(e.into_inner()),
+                    ));
+                }
+                _ => return Err(e.into_inner()),
+            }
+        }
+    }
+
     /// Get block by number (height) - returns block with transaction hashes
     pub async fn get_block_by_number(
         &self,
         block_number: BlockNumber,
     ) -> Result<Option<Block<H256>>, EvmError> {
-        self.client
-            .provider
-            .get_block(block_number)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get block: {}", e)))
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_block(block_number)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get block", e))
+        })
+        .await
     }
 
     /// Get block by hash - returns block with transaction hashes
@@ -55,11 +253,13 @@ impl Evm {
         &self,
         block_hash: H256,
     ) -> Result<Option<Block<H256>>, EvmError> {
-        self.client
-            .provider
-            .get_block(block_hash)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get block: {}", e)))
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_block(block_hash)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get block", e))
+        })
+        .await
     }
 
     /// Get block with full transaction details
@@ -67,11 +267,13 @@ impl Evm {
         &self,
         block_number: BlockNumber,
     ) -> Result<Option<Block<Transaction>>, EvmError> {
-        self.client
-            .provider
-            .get_block_with_txs(block_number)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get block with txs: {}", e)))
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_block_with_txs(block_number)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get block with txs", e))
+        })
+        .await
     }
 
     /// Get block with full transaction details by hash
@@ -105,8 +307,12 @@ impl Evm {
     /// }
     /// ```
     pub async fn new(evm_type: EvmType) -> Result<Self, EvmError> {
+        let expected_chain_id = Self::chain_id_of(&evm_type);
         match EvmClient::from_type(evm_type).await {
-            Ok(client) => Ok(Self { client: client }),
+            Ok(client) => Ok(Self {
+                expected_chain_id,
+                ..Self::from_client(client)
+            }),
             Err(e) => Err(EvmError::RpcError(format!("Rpc Error:{:?}", e))),
         }
     }
@@ -127,153 +333,1394 @@ impl Evm {
     /// }
     /// ```
     pub async fn with_wallet(evm_type: EvmType, private_key: &str) -> Result<Self, EvmError> {
+        let expected_chain_id = Self::chain_id_of(&evm_type);
         match EvmClient::from_wallet(evm_type, private_key).await {
-            Ok(client) => Ok(Self { client: client }),
+            Ok(client) => Ok(Self {
+                expected_chain_id,
+                ..Self::from_client(client)
+            }),
             Err(e) => Err(EvmError::RpcError(format!("Rpc Error:{:?}", e))),
         }
     }
 
-    /// Get chain ID
+    /// Chain id the built-in endpoints of `evm_type` serve, see `global::chain_id_by_network`
+    fn chain_id_of(evm_type: &EvmType) -> Option<u64> {
+        global::chain_id_by_network(&format!("{:?}", evm_type))
+    }
+
+    /// Create a new EVM client connected to a custom RPC endpoint, without wallet
+    ///
+    /// The built-in endpoint table of `EvmType` is bypassed entirely. The chain is
+    /// detected from the endpoint with `eth_chainId`, so an unreachable or invalid URL
+    /// fails here rather than on the first call.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let evm = Evm::with_rpc_url("https://mainnet.base.org").await?;
+    /// println!("Connected to chain ID: {}", evm.get_chain_id().await?);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn with_rpc_url(url: &str) -> Result<Self, EvmError> {
+        Self::from_client(Self::client_for_url(url, None)?)
+            .detect_chain(url)
+            .await
+    }
+
+    /// Create a new EVM client connected to a custom RPC endpoint, with wallet
+    ///
+    /// # Example
+    /// ```
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let evm = Evm::with_rpc_url_and_wallet("http://localhost:8545", "your_private_key_here").await?;
+    /// let balance = evm.get_wallet_balance().await?;
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn with_rpc_url_and_wallet(url: &str, private_key: &str) -> Result<Self, EvmError> {
+        let wallet = private_key
+            .trim_start_matches("0x")
+            .parse::<LocalWallet>()
+            .map_err(|e| EvmError::WalletError(format!("Invalid private key: {}", e)))?;
+        Self::from_client(Self::client_for_url(url, Some(wallet))?)
+            .detect_chain(url)
+            .await
+    }
+
+    /// Client sending requests to `url`, built like the endpoints of `with_rpc_urls`
+    fn client_for_url(url: &str, wallet: Option<LocalWallet>) -> Result<EvmClient, EvmError> {
+        let provider = Provider::<Http>::try_from(url)
+            .map_err(|e| EvmError::ConfigError(format!("Invalid RPC endpoint {}: {}", url, e)))?;
+        Ok(EvmClient { provider, wallet })
+    }
+
+    /// Create a new EVM client connected to the first usable endpoint of a list
+    ///
+    /// Endpoints are tried in order and the first one that passes `health_check` is used.
+    /// When a read later fails because the endpoint is unreachable or timed out, it is
+    /// repeated on the next endpoint of the list, wrapping around, and that endpoint
+    /// stays in use for every request afterwards, so requests do not alternate between
+    /// nodes with different views of the chain. Every endpoint must serve the same chain.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let evm = Evm::with_rpc_urls(vec![
+    ///     "https://eth.llamarpc.com".to_string(),
+    ///     "https://rpc.ankr.com/eth".to_string(),
+    /// ])
+    /// .await?;
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn with_rpc_urls(urls: Vec<String>) -> Result<Self, EvmError> {
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                Provider::<Http>::try_from(url.as_str()).map_err(|e| {
+                    EvmError::ConfigError(format!("Invalid RPC endpoint {}: {}", url, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let endpoints = Arc::new(endpoints);
+        let mut errors = Vec::new();
+        for (index, url) in urls.iter().enumerate() {
+            let report = match Self::with_rpc_url(url).await {
+                Ok(evm) => evm.health_check().await.map(|report| (evm, report)),
+                Err(e) => Err(e),
+            };
+            match report {
+                Ok((mut evm, report)) if report.healthy => {
+                    evm.endpoints = endpoints;
+                    evm.active_endpoint = Arc::new(AtomicUsize::new(index));
+                    return Ok(evm);
+                }
+                Ok((_, report)) => {
+                    log::warn!("Skipping unhealthy RPC endpoint {}: {:?}", url, report);
+                    errors.push(format!("{} is unhealthy", url));
+                }
+                Err(e) => {
+                    log::warn!("Skipping RPC endpoint {}: {}", url, e);
+                    errors.push(e.to_string());
+                }
+            }
+        }
+        Err(EvmError::ConnectionError(if urls.is_empty() {
+            "No RPC endpoints given".to_string()
+        } else {
+            format!("No usable RPC endpoint: {}", errors.join("; "))
+        }))
+    }
+
+    /// Query the chain id of a freshly connected endpoint
+    async fn detect_chain(mut self, url: &str) -> Result<Self, EvmError> {
+        let chain_id = self.get_chain_id().await.map_err(|e| {
+            EvmError::ConnectionError(format!("RPC endpoint {} is not usable: {}", url, e))
+        })?;
+        log::info!("Connected to chain {} via {}", chain_id, url);
+        self.expected_chain_id = Some(chain_id);
+        Ok(self)
+    }
+
+    /// Probe the endpoint: head block latency, chain id and sync status
+    ///
+    /// The endpoint is healthy when it is not syncing and reports the expected chain id:
+    /// the chain of the configured `EvmType`, or for custom URLs the chain reported at
+    /// construction. This catches a load balancer routing to a node of another network.
+    /// An `EvmType` without a known chain id only has its sync status checked.
+    /// Nodes that do not implement `eth_syncing` are assumed synced. An unreachable
+    /// endpoint is an error rather than an unhealthy report.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = evm.health_check().await?;
+    /// println!("healthy: {} ({:?})", report.healthy, report.latency);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn health_check(&self) -> Result<HealthReport, EvmError> {
+        let started = std::time::Instant::now();
+        let block_number = self.get_block_number().await?;
+        let latency = started.elapsed();
+        let chain_id = self.get_chain_id().await?;
+        let expected_chain_id = self.expected_chain_id.unwrap_or(chain_id);
+        let syncing = match self
+            .read_with_retry(|| async {
+                self.provider()
+                    .syncing()
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get sync status", e))
+            })
+            .await
+        {
+            Ok(status) => matches!(status, ethers::types::SyncingStatus::IsSyncing(_)),
+            Err(e) => {
+                log::debug!("eth_syncing unavailable, assuming synced: {}", e);
+                false
+            }
+        };
+        Ok(HealthReport {
+            healthy: !syncing && chain_id == expected_chain_id,
+            latency,
+            block_number,
+            chain_id,
+            expected_chain_id,
+            syncing,
+        })
+    }
+
+    /// Create a new EVM client with wallet and a gas price ceiling
+    ///
+    /// `send_transaction` refuses to submit while the gas price exceeds `max_gas_price`.
+    ///
+    /// # Example
+    /// ```
+    /// use ethers::utils::parse_units;
+    ///
+    /// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let max_gas_price = parse_units(80, "gwei")?.into();
+    /// let evm = Evm::with_gas_ceiling(EvmType::ETHEREUM_MAINNET, "your_private_key_here", max_gas_price).await?;
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn with_gas_ceiling(
+        evm_type: EvmType,
+        private_key: &str,
+        max_gas_price: U256,
+    ) -> Result<Self, EvmError> {
+        let evm = Self::with_wallet(evm_type, private_key).await?;
+        evm.set_gas_ceiling(Some(max_gas_price));
+        Ok(evm)
+    }
+
+    /// Set or clear the gas price ceiling used by `send_transaction`
+    ///
+    /// The ceiling is shared by every clone of this `Evm`, so it can be adjusted
+    /// at runtime while services hold an `Arc<Evm>`.
+    pub fn set_gas_ceiling(&self, max_gas_price: Option<U256>) {
+        let mut ceiling = self
+            .gas_ceiling
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *ceiling = max_gas_price;
+    }
+
+    /// Get the configured gas price ceiling
+    pub fn gas_ceiling(&self) -> Option<U256> {
+        *self
+            .gas_ceiling
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Set the factor `send_transaction` applies to estimated gas limits (default 1.2)
+    ///
+    /// Only transactions sent without `gas` are affected. Factors below 1 would
+    /// underfund the estimate and are rejected.
+    pub fn set_gas_limit_multiplier(&self, multiplier: f64) -> Result<(), EvmError> {
+        if !multiplier.is_finite() || multiplier < 1.0 {
+            return Err(EvmError::InvalidInput(format!(
+                "Gas limit multiplier must be at least 1, got {}",
+                multiplier
+            )));
+        }
+        let mut current = self
+            .gas_limit_multiplier
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *current = multiplier;
+        Ok(())
+    }
+
+    /// Get the factor applied to estimated gas limits
+    pub fn gas_limit_multiplier(&self) -> f64 {
+        *self
+            .gas_limit_multiplier
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check_gas_ceiling(&self, gas_price: U256) -> Result<(), EvmError> {
+        match self.gas_ceiling() {
+            Some(ceiling) if gas_price > ceiling => Err(EvmError::TransactionError(
+                "gas price above configured ceiling".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get chain ID
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let chain_id = evm.get_chain_id().await?;
+    /// println!("Chain ID: {}", chain_id);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_chain_id(&self) -> Result<u64, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_chainid()
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get chain ID", e))
+        })
+        .await
+        .map(|id| id.as_u64())
+    }
+
+    /// Get block number
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let block_number = evm.get_block_number().await?;
+    /// println!("Current block number: {}", block_number);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_block_number(&self) -> Result<u64, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_block_number()
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get block number", e))
+        })
+        .await
+        .map(|num| num.as_u64())
+    }
+
+    /// Get balance of an address
+    ///
+    /// # Example
+    /// ```
+    /// use ethers::types::Address;
+    ///
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
+    /// let balance = evm.get_balance(address).await?;
+    /// println!("Balance: {}", balance);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_balance(&self, address: Address) -> Result<U256, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_balance(address, None)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get balance", e))
+        })
+        .await
+    }
+
+    /// Get transaction count (nonce) for an address
+    ///
+    /// # Example
+    /// ```
+    /// use ethers::types::Address;
+    ///
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
+    /// let nonce = evm.get_transaction_count(address).await?;
+    /// println!("Nonce: {}", nonce);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_transaction_count(&self, address: Address) -> Result<u64, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_transaction_count(address, None)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get transaction count", e))
+        })
+        .await
+        .map(|nonce| nonce.as_u64())
+    }
+
+    /// Get the transaction count (nonce) of an address as of the end of `block`
+    ///
+    /// Blocks older than the node's pruning window need an archive node.
+    ///
+    /// # Example
+    /// ```
+    /// let nonce = evm.get_transaction_count_at(address, 18_000_000).await?;
+    /// ```
+    pub async fn get_transaction_count_at(
+        &self,
+        address: Address,
+        block: u64,
+    ) -> Result<u64, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_transaction_count(address, Some(block.into()))
+                .await
+                .map_err(|e| {
+                    let error = EvmError::RpcError(format!(
+                        "Failed to get transaction count at block {}: {}",
+                        block, e
+                    ));
+                    EvmError::from_provider(&e, error)
+                })
+        })
+        .await
+        .map(|nonce| nonce.as_u64())
+    }
+
+    /// Get gas price
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let gas_price = evm.get_gas_price().await?;
+    /// println!("Gas price: {}", gas_price);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_gas_price(&self) -> Result<U256, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_gas_price()
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get gas price", e))
+        })
+        .await
+    }
+
+    /// Get gas price in Gwei
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let gas_price = evm.get_gas_price_gwei().await?;
+    /// println!("Gas price: {:.2} Gwei", gas_price);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_gas_price_gwei(&self) -> Result<f64, EvmError> {
+        let gas_price = self.get_gas_price().await?;
+        Ok(tool::num::u256_to_f64(gas_price, 9))
+    }
+
+    /// Get gas price formatted for display, e.g. `"23.4 Gwei"`
+    pub async fn get_gas_price_display(&self) -> Result<String, EvmError> {
+        let gas_price = self.get_gas_price_gwei().await?;
+        Ok(format!("{:.1} Gwei", gas_price))
+    }
+
+    /// Get the EIP-1559 base fee of the latest block in Gwei
+    ///
+    /// Returns `None` on chains whose blocks carry no base fee.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(base_fee) = evm.get_base_fee_gwei().await? {
+    ///     println!("Base fee: {:.2} Gwei", base_fee);
+    /// }
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_base_fee_gwei(&self) -> Result<Option<f64>, EvmError> {
+        let block = self.get_block_by_number(BlockNumber::Latest).await?;
+        Ok(block
+            .and_then(|b| b.base_fee_per_gas)
+            .map(|base_fee| tool::num::u256_to_f64(base_fee, 9)))
+    }
+
+    /// Get suggested fees for a transaction in the next block
+    ///
+    /// The next base fee follows from the latest block's gas usage. The priority fee is
+    /// the node's `eth_maxPriorityFeePerGas`, or the median tip of the last
+    /// `FEE_HISTORY_BLOCKS` blocks when the node does not support it. `max_fee_per_gas`
+    /// is `BASE_FEE_MULTIPLIER` times the next base fee plus the tip, which stays valid
+    /// through several consecutive full blocks. On chains without EIP-1559 only
+    /// `legacy_gas_price` is filled in.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let fees = evm.recommended_fees().await?;
+    /// if let (Some(max_fee), Some(tip)) = (fees.max_fee_per_gas, fees.priority_fee) {
+    ///     println!("maxFee {} / tip {}", max_fee, tip);
+    /// } else {
+    ///     println!("gasPrice {}", fees.legacy_gas_price);
+    /// }
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn recommended_fees(&self) -> Result<TxFees, EvmError> {
+        let legacy_gas_price = self.get_gas_price().await?;
+        let block = self
+            .get_block_by_number(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| EvmError::RpcError("Latest block not found".to_string()))?;
+        let (Some(base_fee), Some(next_base_fee)) =
+            (block.base_fee_per_gas, block.next_block_base_fee())
+        else {
+            return Ok(TxFees {
+                legacy_gas_price,
+                ..Default::default()
+            });
+        };
+        let priority_fee = match self
+            .read_with_retry(|| async {
+                self.provider()
+                    .request::<_, U256>("eth_maxPriorityFeePerGas", ())
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get priority fee", e))
+            })
+            .await
+        {
+            Ok(priority_fee) => priority_fee,
+            Err(_) => self.median_priority_fee().await?,
+        };
+        Ok(TxFees {
+            base_fee: Some(base_fee),
+            next_base_fee: Some(next_base_fee),
+            priority_fee: Some(priority_fee),
+            max_fee_per_gas: Some(next_base_fee * BASE_FEE_MULTIPLIER + priority_fee),
+            legacy_gas_price,
+        })
+    }
+
+    /// Median priority fee paid in the last `FEE_HISTORY_BLOCKS` blocks
+    async fn median_priority_fee(&self) -> Result<U256, EvmError> {
+        let history = self
+            .read_with_retry(|| async {
+                self.provider()
+                    .fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &[50.0])
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get fee history", e))
+            })
+            .await?;
+        let mut rewards: Vec<U256> = history
+            .reward
+            .iter()
+            .filter_map(|block| block.first().copied())
+            .collect();
+        rewards.sort();
+        Ok(rewards.get(rewards.len() / 2).copied().unwrap_or_default())
+    }
+
+    /// Estimate the L1 data fee an OP Stack chain (Optimism, Base, ...) charges for `tx`
+    ///
+    /// OP Stack transactions pay for posting their data to L1 on top of the L2 execution
+    /// fee, and `eth_estimateGas` does not include it. The fee is quoted by the
+    /// `GasPriceOracle` predeploy from the RLP-encoded transaction, so fill in nonce, gas and
+    /// fee fields beforehand for the most accurate quote. Chains without the predeploy
+    /// return zero.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, tx: TransactionRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let l1_fee = evm.estimate_l1_data_fee(&tx).await?;
+    /// println!("L1 data fee: {} wei", l1_fee);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn estimate_l1_data_fee(&self, tx: &TransactionRequest) -> Result<U256, EvmError> {
+        let oracle: Address = global::OP_STACK_GAS_PRICE_ORACLE
+            .parse()
+            .map_err(|e| EvmError::ConfigError(format!("Invalid gas price oracle: {}", e)))?;
+        let code = self
+            .read_with_retry(|| async {
+                self.provider()
+                    .get_code(oracle, None)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get code", e))
+            })
+            .await?;
+        if code.is_empty() {
+            return Ok(U256::zero());
+        }
+        let mut tx = tx.clone();
+        if tx.chain_id.is_none() {
+            tx.chain_id = Some(self.get_chain_id().await?.into());
+        }
+        let encoded_tx = TypedTransaction::Legacy(tx).rlp();
+        let mut data = keccak256("getL1Fee(bytes)")[..4].to_vec();
+        data.extend(ethers::abi::encode(&[ethers::abi::Token::Bytes(
+            encoded_tx.to_vec(),
+        )]));
+        let call: TypedTransaction = TransactionRequest::new().to(oracle).data(data).into();
+        let output = self
+            .read_with_retry(|| async {
+                self.provider().call(&call, None).await.map_err(|e| {
+                    let error = EvmError::ContractError(format!("Failed to get L1 fee: {}", e));
+                    EvmError::from_provider(&e, error)
+                })
+            })
+            .await?;
+        if output.len() < 32 {
+            return Err(EvmError::ContractError(
+                "Invalid getL1Fee response".to_string(),
+            ));
+        }
+        Ok(U256::from_big_endian(&output[..32]))
+    }
+
+    /// Estimate the total cost of `tx` in wei: L2 execution fee plus, on OP Stack chains,
+    /// the L1 data fee
+    ///
+    /// The execution fee is `estimate_gas * gas_price`, using the gas limit and gas price
+    /// of `tx` when set.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, tx: TransactionRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let fee = evm.total_fee(&tx).await?;
+    /// println!("Total fee: {} ETH", ethers::utils::format_ether(fee));
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn total_fee(&self, tx: &TransactionRequest) -> Result<U256, EvmError> {
+        let mut tx = tx.clone();
+        if tx.gas.is_none() {
+            let typed: TypedTransaction = tx.clone().into();
+            let gas = self
+                .read_with_retry(|| async {
+                    self.provider()
+                        .estimate_gas(&typed, None)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to estimate gas", e))
+                })
+                .await?;
+            tx.gas = Some(gas);
+        }
+        if tx.gas_price.is_none() {
+            tx.gas_price = Some(self.get_gas_price().await?);
+        }
+        let execution_fee = tx.gas.unwrap_or_default() * tx.gas_price.unwrap_or_default();
+        let l1_fee = self.estimate_l1_data_fee(&tx).await?;
+        Ok(execution_fee + l1_fee)
+    }
+
+    /// Estimate the gas a transaction would use if sent now
+    ///
+    /// Without `from` the estimate is made from the wallet address when one is
+    /// configured. An estimate that fails because the call reverts is reported as a
+    /// `TransactionError` starting with "Gas estimation reverted", so it can be told
+    /// apart from RPC failures and from errors while sending.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, tx: TransactionRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let gas = evm.estimate_gas(&tx).await?;
+    /// println!("Estimated gas: {}", gas);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn estimate_gas(&self, tx: &TransactionRequest) -> Result<U256, EvmError> {
+        let mut tx = tx.clone();
+        if tx.from.is_none() {
+            tx.from = self.client.wallet.as_ref().map(|wallet| wallet.address());
+        }
+        let typed: TypedTransaction = tx.into();
+        self.read_with_retry(|| async {
+            self.provider()
+                .estimate_gas(&typed, None)
+                .await
+                .map_err(|e| {
+                    let message = e.to_string();
+                    if message.to_lowercase().contains("revert") {
+                        EvmError::TransactionError(format!("Gas estimation reverted: {}", message))
+                    } else {
+                        EvmError::rpc("Failed to estimate gas", e)
+                    }
+                })
+        })
+        .await
+    }
+
+    /// Send a raw transaction
+    ///
+    /// A missing `gas` is filled from `estimate_gas`, scaled by the gas limit
+    /// multiplier (see `set_gas_limit_multiplier`). A missing `nonce` is taken from the
+    /// pending transaction count while holding the wallet's nonce lock, so concurrent
+    /// sends from clones of this client get distinct nonces.
+    ///
+    /// # Example
+    /// ```
+    /// use ethers::types::{TransactionRequest, Address, U256};
+    ///
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let to_address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
+    /// let tx = TransactionRequest::new()
+    ///     .to(to_address)
+    ///     .value(U256::from(1000000000000000u64));
+    ///     
+    /// let tx_hash = evm.send_transaction(tx).await?;
+    /// println!("Transaction sent: {:?}", tx_hash);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn send_transaction(&self, mut tx: TransactionRequest) -> Result<H256, EvmError> {
+        if self.client.wallet.is_none() {
+            return Err(EvmError::WalletError("No wallet configured".to_string()));
+        }
+        let wallet = self.client.wallet.as_ref().unwrap();
+        tx.from = Some(wallet.address());
+        let chain_id = self.get_chain_id().await?;
+        tx.chain_id = Some(chain_id.into());
+        // Held until the transaction is broadcast. A nonce set by the caller is used as is;
+        // `deploy_contract` holds the lock itself while choosing one.
+        let _nonce_guard = match tx.nonce {
+            Some(_) => None,
+            None => Some(self.nonce_lock.lock().await),
+        };
+        if tx.nonce.is_none() {
+            let nonce = self
+                .read_with_retry(|| async {
+                    self.provider()
+                        .get_transaction_count(wallet.address(), Some(BlockNumber::Pending.into()))
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get transaction count", e))
+                })
+                .await?;
+            tx.nonce = Some(nonce);
+        }
+        if tx.gas_price.is_none() {
+            let gas_price = self.get_gas_price().await?;
+            tx.gas_price = Some(gas_price);
+        }
+        if tx.gas.is_none() {
+            let estimate = self.estimate_gas(&tx).await?;
+            let percent = (self.gas_limit_multiplier() * 100.0).round() as u64;
+            tx.gas = Some(estimate * percent / 100);
+        }
+        if let Some(gas_price) = tx.gas_price {
+            self.check_gas_ceiling(gas_price)?;
+        }
+        let pending_tx = self
+            .provider()
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| {
+                EvmError::TransactionError(format!("Failed to send transaction: {}", e))
+            })?;
+        Ok(pending_tx.tx_hash())
+    }
+
+    /// Broadcast a transaction signed elsewhere (hardware wallet, MPC signer, ...)
+    ///
+    /// Needs no wallet on this client. `raw` is the signed transaction as broadcast
+    /// by `eth_sendRawTransaction`: RLP for legacy transactions, the type byte followed
+    /// by the RLP payload for typed ones. The returned hash is computed from `raw`.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, signed: Bytes) -> Result<(), Box<dyn std::error::Error>> {
+    /// let tx_hash = evm.send_raw_transaction(signed).await?;
+    /// println!("Broadcast: {:?}", tx_hash);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn send_raw_transaction(&self, raw: Bytes) -> Result<H256, EvmError> {
+        if raw.is_empty() {
+            return Err(EvmError::InvalidInput("Empty raw transaction".to_string()));
+        }
+        let tx_hash = H256::from(keccak256(&raw));
+        self.provider()
+            .send_raw_transaction(raw)
+            .await
+            .map_err(|e| {
+                EvmError::TransactionError(format!("Failed to send raw transaction: {}", e))
+            })?;
+        Ok(tx_hash)
+    }
+
+    /// Send a transaction and wait until it is `confirmations` blocks deep
+    ///
+    /// One confirmation means included in a block; 0 is treated the same. The receipt
+    /// is fetched again on every check, so a transaction moved by a reorg is followed
+    /// to its new block. A reverted transaction is an error carrying the revert reason,
+    /// recovered by replaying the transaction on the state of its parent block; the
+    /// reason may be missing or differ if earlier transactions of the same block
+    /// changed that state.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, tx: TransactionRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let receipt = evm.send_and_confirm(tx, 3, Duration::from_secs(120)).await?;
+    /// println!("Mined in block {:?}", receipt.block_number);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn send_and_confirm(
+        &self,
+        tx: TransactionRequest,
+        confirmations: usize,
+        timeout: std::time::Duration,
+    ) -> Result<ethers::types::TransactionReceipt, EvmError> {
+        let tx_hash = self.send_transaction(tx).await?;
+        self.confirm_transaction(tx_hash, confirmations, timeout)
+            .await
+    }
+
+    /// Wait until an already sent transaction is `confirmations` blocks deep
+    ///
+    /// Behaves like `send_and_confirm` for a transaction sent by other means, e.g. a
+    /// contract call: a revert or a timeout is an error.
+    ///
+    /// # Example
+    /// ```
+    /// let approval = erc20.approve(usdc, router, amount).await?;
+    /// evm.confirm_transaction(approval, 1, Duration::from_secs(120)).await?;
+    /// ```
+    pub async fn confirm_transaction(
+        &self,
+        tx_hash: H256,
+        confirmations: usize,
+        timeout: std::time::Duration,
+    ) -> Result<ethers::types::TransactionReceipt, EvmError> {
+        let receipt = tokio::time::timeout(timeout, self.wait_for_receipt(tx_hash, confirmations))
+            .await
+            .map_err(|_| {
+                EvmError::TransactionError(format!(
+                    "Transaction {:?} not confirmed within {:?}",
+                    tx_hash, timeout
+                ))
+            })??;
+        if receipt.status.is_some_and(|status| status.is_zero()) {
+            let reason = self
+                .revert_reason(tx_hash)
+                .await
+                .unwrap_or_else(|| "no revert reason".to_string());
+            return Err(EvmError::TransactionError(format!(
+                "Transaction {:?} reverted in block {:?}: {}",
+                tx_hash,
+                receipt.block_number.unwrap_or_default(),
+                reason
+            )));
+        }
+        Ok(receipt)
+    }
+
+    async fn wait_for_receipt(
+        &self,
+        tx_hash: H256,
+        confirmations: usize,
+    ) -> Result<ethers::types::TransactionReceipt, EvmError> {
+        loop {
+            if let Some(receipt) = self
+                .get_confirmed_receipt(tx_hash, confirmations as u64)
+                .await?
+            {
+                return Ok(receipt);
+            }
+            tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Replay a mined transaction with `eth_call` and decode why it reverted
+    async fn revert_reason(&self, tx_hash: H256) -> Option<String> {
+        use ethers::providers::RpcError;
+        let tx = self.get_transaction(tx_hash).await.ok()??;
+        let parent = tx.block_number?.as_u64().checked_sub(1)?;
+        let call: TypedTransaction = (&tx).into();
+        let error = self
+            .provider()
+            .call(&call, Some(BlockNumber::Number(parent.into()).into()))
+            .await
+            .err()?;
+        let data = error.as_error_response()?.as_revert_data()?;
+        tool::decode_revert_reason(&data)
+    }
+
+    /// Estimate the gas needed to deploy a contract
+    ///
+    /// `constructor_args` are the ABI-encoded constructor arguments, appended to the
+    /// creation bytecode. The estimate is made from the wallet address when one is
+    /// configured, since constructors often depend on `msg.sender`.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, bytecode: Bytes, args: Bytes) -> Result<(), Box<dyn std::error::Error>> {
+    /// let gas = evm.estimate_deployment_gas(bytecode, args).await?;
+    /// println!("Deployment gas: {}", gas);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn estimate_deployment_gas(
+        &self,
+        bytecode: Bytes,
+        constructor_args: Bytes,
+    ) -> Result<U256, EvmError> {
+        let mut tx =
+            TransactionRequest::new().data([bytecode.as_ref(), constructor_args.as_ref()].concat());
+        if let Some(wallet) = &self.client.wallet {
+            tx.from = Some(wallet.address());
+        }
+        let typed: TypedTransaction = tx.into();
+        self.read_with_retry(|| async {
+            self.provider()
+                .estimate_gas(&typed, None)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to estimate deployment gas", e))
+        })
+        .await
+    }
+
+    /// Deploy a contract from the wallet
+    ///
+    /// Returns the transaction hash and the address the contract will be created at,
+    /// derived from the wallet address and the nonce used. The address is only valid
+    /// once the transaction is mined successfully.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, bytecode: Bytes, args: Bytes) -> Result<(), Box<dyn std::error::Error>> {
+    /// let (tx_hash, address) = evm.deploy_contract(bytecode, args, U256::zero()).await?;
+    /// println!("Deploying {:?} in {:?}", address, tx_hash);
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn deploy_contract(
+        &self,
+        bytecode: Bytes,
+        constructor_args: Bytes,
+        value: U256,
+    ) -> Result<(H256, Address), EvmError> {
+        let from = self
+            .client
+            .wallet
+            .as_ref()
+            .ok_or_else(|| EvmError::WalletError("No wallet configured".to_string()))?
+            .address();
+        let _nonce_guard = self.nonce_lock.lock().await;
+        let nonce = self
+            .read_with_retry(|| async {
+                self.provider()
+                    .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transaction count", e))
+            })
+            .await?;
+        let tx = TransactionRequest::new()
+            .data([bytecode.as_ref(), constructor_args.as_ref()].concat())
+            .value(value)
+            .nonce(nonce);
+        let tx_hash = self.send_transaction(tx).await?;
+        Ok((tx_hash, ethers::utils::get_contract_address(from, nonce)))
+    }
+
+    /// Execute a read-only call (`eth_call`) against the latest state
+    ///
+    /// `from` sets `msg.sender` for the call, so functions that depend on the caller
+    /// (access control, per-user views) can be simulated as any address without its
+    /// key. Nothing is signed or sent and the result reflects current state only. For a
+    /// full "what if this address called this" simulation, combine the same request
+    /// with state overrides through `provider().call_raw(&tx).state(&state)`. A revert
+    /// fails with a `ContractError`, a node or network failure with an `RpcError`.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, vault: Address, keeper: Address, data: Bytes) -> Result<(), Box<dyn std::error::Error>> {
+    /// let output = evm.call_contract(vault, data, Some(keeper)).await?;
+    /// println!("Returned {} bytes", output.len());
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn call_contract(
+        &self,
+        to: Address,
+        data: Bytes,
+        from: Option<Address>,
+    ) -> Result<Bytes, EvmError> {
+        let mut tx = TransactionRequest::new().to(to).data(data);
+        tx.from = from;
+        let typed: TypedTransaction = tx.into();
+        use ethers::providers::RpcError as _;
+        self.read_with_retry(|| async {
+            self.provider().call(&typed, None).await.map_err(|e| {
+                let error =
+                    EvmError::from_provider(&e, EvmError::RpcError(format!("Call failed: {}", e)));
+                // Only an error answered by the node for the call itself is a revert
+                if error.is_transient() || e.as_error_response().is_none() {
+                    error
+                } else {
+                    EvmError::ContractError(format!("Call failed: {}", e))
+                }
+            })
+        })
+        .await
+    }
+
+    /// Batch read-only calls into one `eth_call` through Multicall3's `tryAggregate`
+    ///
+    /// Each `(target, calldata)` pair yields `(success, return_data)` in the same order;
+    /// a reverting call only marks its own entry as failed, with the revert data as its
+    /// return data. All calls see the same block state. Fails with a `ContractError`
+    /// on chains where Multicall3 is not deployed.
+    ///
+    /// # Example
+    /// ```
+    /// let calls = vec![(token, balance_of_alice), (token, balance_of_bob)];
+    /// for (success, data) in evm.multicall(calls).await? {
+    ///     if success {
+    ///         println!("{}", U256::from_big_endian(&data));
+    ///     }
+    /// }
+    /// ```
+    pub async fn multicall(
+        &self,
+        calls: Vec<(Address, Bytes)>,
+    ) -> Result<Vec<(bool, Bytes)>, EvmError> {
+        use ethers::abi::Token;
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let call_count = calls.len();
+        let calls = calls
+            .into_iter()
+            .map(|(target, data)| {
+                Token::Tuple(vec![Token::Address(target), Token::Bytes(data.to_vec())])
+            })
+            .collect();
+        let mut data = keccak256("tryAggregate(bool,(address,bytes)[])")[..4].to_vec();
+        data.extend(ethers::abi::encode(&[
+            Token::Bool(false),
+            Token::Array(calls),
+        ]));
+        self.call_multicall3(data, call_count).await
+    }
+
+    /// Batch arbitrary read-only calls into one `eth_call` through Multicall3's `aggregate3`
+    ///
+    /// Calls may target different contracts and functions and are all sent with
+    /// `allowFailure` set. Results come back in the order of `calls`, with `None` for a
+    /// call that reverted. Fails with a `ContractError` on chains where Multicall3 is
+    /// not deployed.
+    ///
+    /// # Example
+    /// ```
+    /// let calls = vec![
+    ///     (usdc, Bytes::from(keccak256("decimals()")[..4].to_vec())),
+    ///     (pair, Bytes::from(keccak256("getReserves()")[..4].to_vec())),
+    /// ];
+    /// let results = evm.multicall_reads(calls).await?;
+    /// if let Some(decimals) = &results[0] {
+    ///     println!("USDC decimals: {}", U256::from_big_endian(decimals));
+    /// }
+    /// ```
+    pub async fn multicall_reads(
+        &self,
+        calls: Vec<(Address, Bytes)>,
+    ) -> Result<Vec<Option<Bytes>>, EvmError> {
+        use ethers::abi::Token;
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let call_count = calls.len();
+        let calls = calls
+            .into_iter()
+            .map(|(target, data)| {
+                Token::Tuple(vec![
+                    Token::Address(target),
+                    Token::Bool(true),
+                    Token::Bytes(data.to_vec()),
+                ])
+            })
+            .collect();
+        let mut data = keccak256("aggregate3((address,bool,bytes)[])")[..4].to_vec();
+        data.extend(ethers::abi::encode(&[Token::Array(calls)]));
+        Ok(self
+            .call_multicall3(data, call_count)
+            .await?
+            .into_iter()
+            .map(|(success, data)| success.then_some(data))
+            .collect())
+    }
+
+    /// Send encoded Multicall3 calldata and decode its `call_count` results
+    async fn call_multicall3(
+        &self,
+        data: Vec<u8>,
+        call_count: usize,
+    ) -> Result<Vec<(bool, Bytes)>, EvmError> {
+        let multicall3: Address = global::MULTICALL3
+            .parse()
+            .map_err(|e| EvmError::ConfigError(format!("Invalid Multicall3 address: {}", e)))?;
+        let output = self.call_contract(multicall3, data.into(), None).await?;
+        if output.is_empty() {
+            return Err(EvmError::ContractError(
+                "Multicall3 is not deployed on this chain".to_string(),
+            ));
+        }
+        Self::decode_aggregate_results(&output, call_count)
+    }
+
+    /// Decode the `(bool success, bytes returnData)[]` returned by Multicall3's
+    /// `tryAggregate` and `aggregate3`
+    ///
+    /// Fails with a `ContractError` unless exactly `call_count` results are decoded.
+    pub fn decode_aggregate_results(
+        output: &[u8],
+        call_count: usize,
+    ) -> Result<Vec<(bool, Bytes)>, EvmError> {
+        use ethers::abi::{ParamType, Token};
+        let result_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])));
+        let results: Vec<(bool, Bytes)> = ethers::abi::decode(&[result_type], output)
+            .ok()
+            .and_then(|mut tokens| tokens.pop())
+            .and_then(Token::into_array)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|result| match result.into_tuple()?.as_slice() {
+                [Token::Bool(success), Token::Bytes(data)] => Some((*success, data.clone().into())),
+                _ => None,
+            })
+            .collect();
+        if results.len() != call_count {
+            return Err(EvmError::ContractError(format!(
+                "Invalid aggregate response: {} results for {} calls",
+                results.len(),
+                call_count
+            )));
+        }
+        Ok(results)
+    }
+
+    /// Call a function by its human-readable signature and decode the outputs
+    ///
+    /// The signature must declare the outputs to decode them, e.g.
+    /// `"balanceOf(address) returns (uint256)"`. `from` overrides `msg.sender` as in
+    /// `call_contract`.
     ///
     /// # Example
     /// ```
-    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let chain_id = evm.get_chain_id().await?;
-    /// println!("Chain ID: {}", chain_id);
+    /// async fn example(evm: Evm, token: Address, owner: Address) -> Result<(), Box<dyn std::error::Error>> {
+    /// let outputs = evm
+    ///     .call_function(token, "balanceOf(address) returns (uint256)", vec![Token::Address(owner)], None)
+    ///     .await?;
+    /// println!("Balance: {:?}", outputs[0]);
     /// Ok(())
     /// }
     /// ```
-    pub async fn get_chain_id(&self) -> Result<u64, EvmError> {
-        self.client
-            .provider
-            .get_chainid()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get chain ID: {}", e)))
-            .map(|id| id.as_u64())
+    pub async fn call_function(
+        &self,
+        to: Address,
+        signature: &str,
+        args: Vec<ethers::abi::Token>,
+        from: Option<Address>,
+    ) -> Result<Vec<ethers::abi::Token>, EvmError> {
+        let function =
+            ethers::abi::HumanReadableParser::parse_function(signature).map_err(|e| {
+                EvmError::InvalidInput(format!("Invalid function signature {}: {}", signature, e))
+            })?;
+        let data = function
+            .encode_input(&args)
+            .map_err(|e| EvmError::InvalidInput(format!("Invalid call arguments: {}", e)))?;
+        let output = self.call_contract(to, data.into(), from).await?;
+        function
+            .decode_output(&output)
+            .map_err(|e| EvmError::ContractError(format!("Failed to decode call output: {}", e)))
     }
 
-    /// Get block number
+    /// Sign and broadcast several transactions from the wallet with sequential nonces
+    ///
+    /// Nonces start at the wallet's pending transaction count and are assigned
+    /// while holding a lock, so concurrent batches never reuse a nonce. A failing
+    /// transaction does not abort the batch and does not consume its nonce, so
+    /// the following transactions stay contiguous. Transactions without `gas`
+    /// are estimated against the current state; set `gas` explicitly for
+    /// transactions that depend on earlier ones in the same batch (e.g. a swap
+    /// after an approve).
     ///
     /// # Example
     /// ```
-    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let block_number = evm.get_block_number().await?;
-    /// println!("Current block number: {}", block_number);
+    /// async fn example(evm: Evm, approve: TransactionRequest, swap: TransactionRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let results = evm.send_batch(vec![approve, swap]).await?;
+    /// for result in results {
+    ///     match result {
+    ///         Ok(hash) => println!("Sent: {:?}", hash),
+    ///         Err(e) => println!("Failed: {}", e),
+    ///     }
+    /// }
     /// Ok(())
     /// }
     /// ```
-    pub async fn get_block_number(&self) -> Result<u64, EvmError> {
-        self.client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get block number: {}", e)))
-            .map(|num| num.as_u64())
+    pub async fn send_batch(
+        &self,
+        txs: Vec<TransactionRequest>,
+    ) -> Result<Vec<Result<H256, EvmError>>, EvmError> {
+        let wallet = self
+            .client
+            .wallet
+            .as_ref()
+            .ok_or_else(|| EvmError::WalletError("No wallet configured".to_string()))?;
+        let from = wallet.address();
+        let chain_id = self.get_chain_id().await?;
+        let gas_price = self.get_gas_price().await?;
+        let _nonce_guard = self.nonce_lock.lock().await;
+        let mut nonce = self
+            .read_with_retry(|| async {
+                self.provider()
+                    .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transaction count", e))
+            })
+            .await?;
+        let mut results = Vec::with_capacity(txs.len());
+        for mut tx in txs {
+            tx.from = Some(from);
+            tx.chain_id = Some(chain_id.into());
+            tx.nonce = Some(nonce);
+            let tx_gas_price = *tx.gas_price.get_or_insert(gas_price);
+            if let Err(e) = self.check_gas_ceiling(tx_gas_price) {
+                results.push(Err(e));
+                continue;
+            }
+            let mut typed: TypedTransaction = tx.into();
+            if typed.gas().is_none() {
+                let estimate = self
+                    .read_with_retry(|| async {
+                        self.provider()
+                            .estimate_gas(&typed, None)
+                            .await
+                            .map_err(|e| {
+                                let error = EvmError::TransactionError(format!(
+                                    "Failed to estimate gas: {}",
+                                    e
+                                ));
+                                EvmError::from_provider(&e, error)
+                            })
+                    })
+                    .await;
+                match estimate {
+                    Ok(gas) => {
+                        typed.set_gas(gas);
+                    }
+                    Err(e) => {
+                        results.push(Err(e));
+                        continue;
+                    }
+                }
+            }
+            let signature = match wallet.sign_transaction(&typed).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    results.push(Err(EvmError::WalletError(format!(
+                        "Failed to sign transaction: {}",
+                        e
+                    ))));
+                    continue;
+                }
+            };
+            match self
+                .provider()
+                .send_raw_transaction(typed.rlp_signed(&signature))
+                .await
+            {
+                Ok(pending_tx) => {
+                    nonce += U256::one();
+                    results.push(Ok(pending_tx.tx_hash()));
+                }
+                Err(e) => results.push(Err(EvmError::TransactionError(format!(
+                    "Failed to send transaction: {}",
+                    e
+                )))),
+            }
+        }
+        Ok(results)
     }
 
-    /// Get balance of an address
+    /// Set the Flashbots identity key used to sign relay requests.
+    ///
+    /// The identity key and the wallet private key serve different purposes. The wallet key
+    /// signs the transactions inside a bundle and holds funds. The identity key only signs the
+    /// `eth_sendBundle` request (`X-Flashbots-Signature` header) so the relay can attribute
+    /// bundles to a searcher and build its reputation; it never needs funds and should be a
+    /// separate, dedicated key. Without an identity key the wallet key signs relay requests.
     ///
     /// # Example
     /// ```
-    /// use ethers::types::Address;
-    ///
     /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
-    /// let balance = evm.get_balance(address).await?;
-    /// println!("Balance: {}", balance);
+    /// evm.set_flashbots_identity("0x...")?;
     /// Ok(())
     /// }
     /// ```
-    pub async fn get_balance(&self, address: Address) -> Result<U256, EvmError> {
-        self.client
-            .provider
-            .get_balance(address, None)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get balance: {}", e)))
+    pub fn set_flashbots_identity(&self, identity_key: &str) -> Result<(), EvmError> {
+        let identity = identity_key
+            .parse::<LocalWallet>()
+            .map_err(|e| EvmError::WalletError(format!("Invalid identity key: {}", e)))?;
+        let mut current = self
+            .flashbots_identity
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *current = Some(identity);
+        Ok(())
     }
 
-    /// Get transaction count (nonce) for an address
+    /// Submit a bundle of signed transactions to a Flashbots-compatible relay.
+    ///
+    /// The request is signed with the Flashbots identity key (see `set_flashbots_identity`),
+    /// or with the wallet key when no identity is set.
     ///
     /// # Example
     /// ```
-    /// use ethers::types::Address;
-    ///
-    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
-    /// let nonce = evm.get_transaction_count(address).await?;
-    /// println!("Nonce: {}", nonce);
+    /// async fn example(evm: Evm, signed_txs: Vec<Bytes>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let target = evm.get_block_number().await? + 1;
+    /// let bundle_hash = evm
+    ///     .submit_bundle(signed_txs, target, "https://relay.flashbots.net")
+    ///     .await?;
+    /// println!("Bundle: {:?}", bundle_hash);
     /// Ok(())
     /// }
     /// ```
-    pub async fn get_transaction_count(&self, address: Address) -> Result<u64, EvmError> {
-        self.client
-            .provider
-            .get_transaction_count(address, None)
+    pub async fn submit_bundle(
+        &self,
+        txs: Vec<Bytes>,
+        target_block: u64,
+        relay_url: &str,
+    ) -> Result<BundleHash, EvmError> {
+        self.submit_bundle_with_params(BundleParams::new(txs, target_block), relay_url)
             .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transaction count: {}", e)))
-            .map(|nonce| nonce.as_u64())
     }
 
-    /// Get gas price
+    /// Submit a bundle with explicit `eth_sendBundle` parameters (timestamps, reverting txs)
     ///
     /// # Example
     /// ```
-    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let gas_price = evm.get_gas_price().await?;
-    /// println!("Gas price: {}", gas_price);
+    /// async fn example(evm: Evm, signed_txs: Vec<Bytes>, now: u64) -> Result<(), Box<dyn std::error::Error>> {
+    /// let target = evm.get_block_number().await? + 1;
+    /// let params = BundleParams::new(signed_txs, target).timestamps(None, Some(now + 60));
+    /// let bundle_hash = evm
+    ///     .submit_bundle_with_params(params, "https://relay.flashbots.net")
+    ///     .await?;
     /// Ok(())
     /// }
     /// ```
-    pub async fn get_gas_price(&self) -> Result<U256, EvmError> {
-        self.client
-            .provider
-            .get_gas_price()
+    pub async fn submit_bundle_with_params(
+        &self,
+        params: BundleParams,
+        relay_url: &str,
+    ) -> Result<BundleHash, EvmError> {
+        if params.txs.is_empty() {
+            return Err(EvmError::InvalidInput(
+                "Bundle has no transactions".to_string(),
+            ));
+        }
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sendBundle",
+            "params": [params],
+        })
+        .to_string();
+        let identity = self
+            .flashbots_identity
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let signature_header = match (identity, self.client.wallet.as_ref()) {
+            (Some(identity), _) => Self::flashbots_signature(&identity, &body).await?,
+            (None, Some(wallet)) => Self::flashbots_signature(wallet, &body).await?,
+            (None, None) => {
+                return Err(EvmError::WalletError(
+                    "No flashbots identity or wallet configured".to_string(),
+                ));
+            }
+        };
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(relay_url)
+            .header("Content-Type", "application/json")
+            .header("X-Flashbots-Signature", signature_header)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| EvmError::ConnectionError(format!("Failed to reach relay: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| EvmError::RpcError(format!("Invalid relay response: {}", e)))?;
+        if let Some(error) = response.get("error") {
+            return Err(EvmError::RpcError(format!(
+                "Relay rejected bundle: {}",
+                error
+            )));
+        }
+        response
+            .get("result")
+            .and_then(|result| result.get("bundleHash"))
+            .and_then(|hash| hash.as_str())
+            .and_then(|hash| hash.parse::<BundleHash>().ok())
+            .ok_or_else(|| EvmError::RpcError(format!("Unexpected relay response: {}", response)))
+    }
+
+    /// `X-Flashbots-Signature` header value: the signer address and its signature over the
+    /// hex-encoded keccak256 hash of the request body
+    async fn flashbots_signature<S: Signer>(signer: &S, body: &str) -> Result<String, EvmError> {
+        let body_hash = format!("{:?}", H256::from(keccak256(body.as_bytes())));
+        let signature = signer
+            .sign_message(body_hash)
             .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get gas price: {}", e)))
+            .map_err(|e| EvmError::WalletError(format!("Failed to sign bundle: {}", e)))?;
+        Ok(format!("{:?}:0x{}", signer.address(), signature))
     }
 
-    /// Send a raw transaction
+    /// Get a transaction by hash, `None` when the node does not know it
     ///
     /// # Example
     /// ```
-    /// use ethers::types::{TransactionRequest, Address, U256};
-    ///
-    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
-    /// let to_address: Address = "0x742d35Cc6634C0532925a3b8D6B5d7a4C03a3a7d".parse()?;
-    /// let tx = TransactionRequest::new()
-    ///     .to(to_address)
-    ///     .value(U256::from(1000000000000000u64));
-    ///     
-    /// let tx_hash = evm.send_transaction(tx).await?;
-    /// println!("Transaction sent: {:?}", tx_hash);
-    /// Ok(())
+    /// if let Some(tx) = evm.get_transaction(tx_hash).await? {
+    ///     println!("Sent by {:?} with nonce {}", tx.from, tx.nonce);
     /// }
     /// ```
-    pub async fn send_transaction(&self, mut tx: TransactionRequest) -> Result<H256, EvmError> {
-        if self.client.wallet.is_none() {
-            return Err(EvmError::WalletError("No wallet configured".to_string()));
-        }
-        let wallet = self.client.wallet.as_ref().unwrap();
-        tx.from = Some(wallet.address());
-        let chain_id = self.get_chain_id().await?;
-        tx.chain_id = Some(chain_id.into());
-        if tx.nonce.is_none() {
-            let nonce = self.get_transaction_count(wallet.address()).await?;
-            tx.nonce = Some(nonce.into());
-        }
-        if tx.gas_price.is_none() {
-            let gas_price = self.get_gas_price().await?;
-            tx.gas_price = Some(gas_price);
-        }
-        let pending_tx = self
-            .client
-            .provider
-            .send_transaction(tx, None)
-            .await
-            .map_err(|e| {
-                EvmError::TransactionError(format!("Failed to send transaction: {}", e))
-            })?;
-        Ok(pending_tx.tx_hash())
+    pub async fn get_transaction(&self, tx_hash: H256) -> Result<Option<Transaction>, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_transaction(tx_hash)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get transaction", e))
+        })
+        .await
     }
 
     /// Get transaction receipt
@@ -295,11 +1742,97 @@ impl Evm {
         &self,
         tx_hash: H256,
     ) -> Result<Option<ethers::types::TransactionReceipt>, EvmError> {
-        self.client
-            .provider
-            .get_transaction_receipt(tx_hash)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transaction receipt: {}", e)))
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_transaction_receipt(tx_hash)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get transaction receipt", e))
+        })
+        .await
+    }
+
+    /// Get a transaction receipt only once it has at least `min_confirmations`
+    ///
+    /// The block containing the transaction counts as the first confirmation, measured
+    /// against the latest block, so 0 and 1 both accept any mined receipt. Returns
+    /// `Ok(None)` while the transaction is pending, unknown or not buried deep enough.
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(receipt) = evm.get_confirmed_receipt(tx_hash, 12).await? {
+    ///     println!("Final in block {:?}", receipt.block_number);
+    /// }
+    /// ```
+    pub async fn get_confirmed_receipt(
+        &self,
+        tx_hash: H256,
+        min_confirmations: u64,
+    ) -> Result<Option<ethers::types::TransactionReceipt>, EvmError> {
+        let Some(receipt) = self.get_transaction_receipt(tx_hash).await? else {
+            return Ok(None);
+        };
+        let Some(block_number) = receipt.block_number else {
+            return Ok(None);
+        };
+        let confirmations = self
+            .get_block_number()
+            .await?
+            .saturating_add(1)
+            .saturating_sub(block_number.as_u64());
+        Ok((confirmations >= min_confirmations).then_some(receipt))
+    }
+
+    /// Get all transaction receipts of a block in one `eth_getBlockReceipts` call
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let receipts = evm.get_block_receipts(19_000_000).await?;
+    /// println!("Block has {} receipts", receipts.len());
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Vec<ethers::types::TransactionReceipt>, EvmError> {
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_block_receipts(BlockNumber::Number(block_number.into()))
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get block receipts", e))
+        })
+        .await
+    }
+
+    /// Receipts of the transactions `tx_hashes` mined in `block_number`, keyed by hash
+    ///
+    /// Fetched in one `eth_getBlockReceipts` call; receipts it does not return, or all
+    /// of them on nodes without the method, are fetched with one call per transaction.
+    /// Transactions the node has no receipt for are missing from the map.
+    pub(crate) async fn receipts_of(
+        &self,
+        block_number: u64,
+        tx_hashes: &[H256],
+    ) -> Result<std::collections::HashMap<H256, ethers::types::TransactionReceipt>, EvmError> {
+        let mut receipts: std::collections::HashMap<_, _> =
+            match self.get_block_receipts(block_number).await {
+                Ok(receipts) => receipts
+                    .into_iter()
+                    .map(|receipt| (receipt.transaction_hash, receipt))
+                    .collect(),
+                Err(e) if e.is_transient() => return Err(e),
+                Err(_) => std::collections::HashMap::new(),
+            };
+        for tx_hash in tx_hashes {
+            if receipts.contains_key(tx_hash) {
+                continue;
+            }
+            if let Some(receipt) = self.get_transaction_receipt(*tx_hash).await? {
+                receipts.insert(*tx_hash, receipt);
+            }
+        }
+        Ok(receipts)
     }
 
     /// Get logs by filter
@@ -325,11 +1858,131 @@ impl Evm {
         &self,
         filter: ethers::types::Filter,
     ) -> Result<Vec<ethers::types::Log>, EvmError> {
-        self.client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))
+        self.read_with_retry(|| async {
+            self.provider()
+                .get_logs(&filter)
+                .await
+                .map_err(|e| EvmError::rpc("Failed to get logs", e))
+        })
+        .await
+    }
+
+    /// Get logs matching a filter, grouped by transaction hash
+    ///
+    /// Logs keep the order returned by the node within each transaction. Pending
+    /// logs have no transaction hash yet and are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// async fn example(evm: Evm, filter: Filter) -> Result<(), Box<dyn std::error::Error>> {
+    /// for (tx_hash, logs) in evm.get_logs_grouped(filter).await? {
+    ///     println!("{:?}: {} logs", tx_hash, logs.len());
+    /// }
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn get_logs_grouped(
+        &self,
+        filter: ethers::types::Filter,
+    ) -> Result<std::collections::HashMap<H256, Vec<ethers::types::Log>>, EvmError> {
+        let mut grouped: std::collections::HashMap<H256, Vec<ethers::types::Log>> =
+            std::collections::HashMap::new();
+        for log in self.get_logs(filter).await? {
+            match log.transaction_hash {
+                Some(tx_hash) => grouped.entry(tx_hash).or_default().push(log),
+                None => log::warn!(
+                    "Skipping pending log from {:?} without transaction hash",
+                    log.address
+                ),
+            }
+        }
+        Ok(grouped)
+    }
+
+    /// Find all calls from and/or to a set of addresses with the `trace_filter` RPC
+    ///
+    /// Traces include internal calls (e.g. a contract forwarding ETH) that never
+    /// show up in logs. Empty address lists do not restrict that side of the call.
+    /// Requires a node with the Parity/Erigon `trace` module enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use ethers::types::Address;
+    ///
+    /// async fn example(evm: Evm) -> Result<(), Box<dyn std::error::Error>> {
+    /// let target: Address = "0x...".parse()?;
+    /// let calls = evm.trace_filter(19_000_000, 19_000_100, vec![], vec![target]).await?;
+    /// for call in calls {
+    ///     println!("{:?} -> {:?} value {}", call.from, call.to, call.value);
+    /// }
+    /// Ok(())
+    /// }
+    /// ```
+    pub async fn trace_filter(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        from_addresses: Vec<Address>,
+        to_addresses: Vec<Address>,
+    ) -> Result<Vec<TraceCall>, EvmError> {
+        use ethers::types::{Action, Res, TraceFilter};
+        let mut filter = TraceFilter::default()
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()));
+        if !from_addresses.is_empty() {
+            filter = filter.from_address(from_addresses);
+        }
+        if !to_addresses.is_empty() {
+            filter = filter.to_address(to_addresses);
+        }
+        let traces = self
+            .read_with_retry(|| async {
+                self.provider()
+                    .trace_filter(filter.clone())
+                    .await
+                    .map_err(|e| {
+                        let message = e.to_string();
+                        if message.contains("-32601")
+                            || message.to_lowercase().contains("method not found")
+                            || message.to_lowercase().contains("does not exist")
+                        {
+                            EvmError::RpcError(format!(
+                                "trace_filter is not supported by this node (trace module not enabled); \
+                                 use debug_traceTransaction with the callTracer instead: {}",
+                                message
+                            ))
+                        } else {
+                            EvmError::rpc("Failed to trace filter", e)
+                        }
+                    })
+            })
+            .await?;
+        Ok(traces
+            .into_iter()
+            .filter_map(|trace| match trace.action {
+                Action::Call(call) => {
+                    let (gas_used, output) = match trace.result {
+                        Some(Res::Call(result)) => (Some(result.gas_used), Some(result.output)),
+                        _ => (None, None),
+                    };
+                    Some(TraceCall {
+                        from: call.from,
+                        to: call.to,
+                        value: call.value,
+                        gas: call.gas,
+                        gas_used,
+                        input: call.input,
+                        output,
+                        call_type: format!("{:?}", call.call_type).to_lowercase(),
+                        trace_address: trace.trace_address,
+                        transaction_hash: trace.transaction_hash,
+                        block_number: trace.block_number,
+                        error: trace.error,
+                    })
+                }
+                _ => None,
+            })
+            .collect())
     }
 
     /// Get native token balance for the wallet
@@ -435,9 +2088,13 @@ impl Evm {
     pub fn get_block_service(self: Arc<Self>) -> BlockService {
         BlockService::new(self.clone())
     }
-    
+
     /// Listen to the latest block (listen to newly generated blocks in real time)
     ///
+    /// With a WebSocket attached (`with_ws`) blocks come from a `newHeads` subscription
+    /// as soon as the node pushes them; otherwise, or once the subscription fails or
+    /// ends, new block hashes are polled.
+    ///
     /// # Example
     /// ```
     /// let mut block_receiver = trade_service.listen_latest_blocks().await?;
@@ -453,8 +2110,25 @@ impl Evm {
         use ethers::providers::Middleware;
         use tokio::sync::broadcast;
         let (sender, receiver) = broadcast::channel(1024);
-        let provider = self.client.provider.clone();
+        let ws = self.ws.clone();
+        let provider = self.provider().clone();
         tokio::spawn(async move {
+            if let Some(ws) = ws {
+                match ws.subscribe_blocks().await {
+                    Ok(mut stream) => {
+                        while let Some(block) = stream.next().await {
+                            if sender.send(block).is_err() {
+                                return;
+                            }
+                        }
+                        log::error!("New block subscription ended, polling instead");
+                    }
+                    Err(e) => log::error!(
+                        "Failed to subscribe to new blocks, polling instead: {:?}",
+                        e
+                    ),
+                }
+            }
             if let Ok(mut stream) = provider.watch_blocks().await {
                 while let Some(block_hash) = stream.next().await {
                     match provider.get_block(block_hash).await {
@@ -477,4 +2151,319 @@ impl Evm {
         });
         Ok(receiver)
     }
+
+    /// Listen to blocks once they are buried under `confirmations` newer blocks
+    ///
+    /// Heads from `watch_blocks` only advance the confirmed height; each block at
+    /// `head - confirmations` is then fetched by number from the canonical chain,
+    /// so blocks orphaned within that depth are never emitted. Emission starts at
+    /// the first head seen, and a gap after a stall is filled in order.
+    ///
+    /// # Example
+    /// ```
+    /// let mut block_receiver = evm.listen_confirmed_blocks(12).await?;
+    ///
+    /// while let Ok(block) = block_receiver.recv().await {
+    ///     println!("Confirmed block: #{}", block.number.unwrap_or_default());
+    /// }
+    /// ```
+    pub async fn listen_confirmed_blocks(
+        &self,
+        confirmations: u64,
+    ) -> Result<tokio::sync::broadcast::Receiver<Block<H256>>, EvmError> {
+        use tokio::sync::broadcast;
+        let (sender, receiver) = broadcast::channel(1024);
+        let provider = self.provider().clone();
+        let mut stream = provider
+            .watch_blocks()
+            .await
+            .map_err(|e| EvmError::RpcError(format!("Failed to create block watcher: {}", e)))?;
+        tokio::spawn(async move {
+            let mut next_number: Option<u64> = None;
+            let mut last_hash: Option<H256> = None;
+            'heads: while let Some(head_hash) = stream.next().await {
+                let head_number = match provider.get_block(head_hash).await {
+                    Ok(Some(head)) => match head.number {
+                        Some(number) => number.as_u64(),
+                        None => continue,
+                    },
+                    Ok(None) => {
+                        log::warn!("Block not found for hash: {:?}", head_hash);
+                        continue;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to get block: {:?}", e);
+                        continue;
+                    }
+                };
+                let Some(confirmed) = head_number.checked_sub(confirmations) else {
+                    continue;
+                };
+                let mut number = next_number.unwrap_or(confirmed);
+                while number <= confirmed {
+                    let block = match provider.get_block(number).await {
+                        Ok(Some(block)) => block,
+                        Ok(None) => {
+                            log::warn!("Confirmed block #{} not found", number);
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Failed to get block #{}: {:?}", number, e);
+                            break;
+                        }
+                    };
+                    if let Some(last_hash) = last_hash {
+                        if block.parent_hash != last_hash {
+                            log::warn!(
+                                "Reorg deeper than {} confirmations at block #{}",
+                                confirmations,
+                                number
+                            );
+                        }
+                    }
+                    last_hash = block.hash;
+                    if sender.send(block).is_err() {
+                        break 'heads;
+                    }
+                    number += 1;
+                    next_number = Some(number);
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    /// Resolve many ENS names concurrently
+    ///
+    /// At most `ENS_BATCH_CONCURRENCY` lookups are in flight at once. Names that do not
+    /// resolve map to `None`; results keep the input order. An RPC failure fails the
+    /// whole batch rather than being reported as a missing name.
+    ///
+    /// # Example
+    /// ```
+    /// let names = vec!["vitalik.eth".to_string(), "nick.eth".to_string()];
+    /// for (name, address) in evm.resolve_ens_batch(names).await? {
+    ///     println!("{} -> {:?}", name, address);
+    /// }
+    /// ```
+    pub async fn resolve_ens_batch(
+        &self,
+        names: Vec<String>,
+    ) -> Result<Vec<(String, Option<Address>)>, EvmError> {
+        use futures::stream::{self, StreamExt, TryStreamExt};
+        self.ensure_ens_chain().await?;
+        stream::iter(names)
+            .map(|name| async move {
+                let address = self.ens_address(&name).await?;
+                Ok((name, address))
+            })
+            .buffered(ENS_BATCH_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Reverse-resolve many addresses to their primary ENS names concurrently
+    ///
+    /// Addresses without a reverse record (or whose record does not resolve back to
+    /// them) map to `None`; results keep the input order. An RPC failure fails the
+    /// whole batch.
+    ///
+    /// # Example
+    /// ```
+    /// for (address, name) in evm.lookup_ens_batch(holders).await? {
+    ///     println!("{:?} -> {}", address, name.unwrap_or_default());
+    /// }
+    /// ```
+    pub async fn lookup_ens_batch(
+        &self,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<(Address, Option<String>)>, EvmError> {
+        use futures::stream::{self, StreamExt, TryStreamExt};
+        self.ensure_ens_chain().await?;
+        stream::iter(addresses)
+            .map(|address| async move {
+                let name = self
+                    .read_with_retry(|| async {
+                        match self.provider().lookup_address(address).await {
+                            Ok(name) => Ok(Some(name)),
+                            Err(e) if is_missing_ens_record(&e) => Ok(None),
+                            Err(e) => Err(EvmError::rpc(
+                                &format!("Failed to look up ENS name of {:?}", address),
+                                e,
+                            )),
+                        }
+                    })
+                    .await?;
+                Ok((address, name))
+            })
+            .buffered(ENS_BATCH_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Resolve an ENS name to the address it points to
+    ///
+    /// Fails with a `ConfigError` on chains without ENS, with `InvalidInput` when the
+    /// name has no resolver or no address record, and with an `RpcError` when the node
+    /// could not be queried.
+    ///
+    /// # Example
+    /// ```
+    /// let address = evm.resolve_name("vitalik.eth").await?;
+    /// ```
+    pub async fn resolve_name(&self, name: &str) -> Result<Address, EvmError> {
+        self.ensure_ens_chain().await?;
+        self.ens_address(name)
+            .await?
+            .ok_or_else(|| EvmError::InvalidInput(format!("ENS name {:?} does not resolve", name)))
+    }
+
+    /// Parse a hex address, or resolve it through ENS when it looks like a name
+    ///
+    /// Anything containing a dot that is not `0x`-prefixed is treated as an ENS name;
+    /// everything else goes through `tool::address::str_to_address`, so hex addresses
+    /// never cost an RPC call.
+    ///
+    /// # Example
+    /// ```
+    /// let treasury = evm.resolve_or_parse("nick.eth").await?;
+    /// let router = evm.resolve_or_parse("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D").await?;
+    /// ```
+    pub async fn resolve_or_parse(&self, address_or_name: &str) -> Result<Address, EvmError> {
+        let trimmed = address_or_name.trim();
+        if trimmed.contains('.') && !trimmed.starts_with("0x") {
+            return self.resolve_name(trimmed).await;
+        }
+        tool::address::str_to_address(trimmed)
+    }
+
+    /// Address record of an ENS name, `None` when the name has no resolver or record
+    async fn ens_address(&self, name: &str) -> Result<Option<Address>, EvmError> {
+        self.read_with_retry(|| async {
+            match self.provider().resolve_name(name).await {
+                Ok(address) if address.is_zero() => Ok(None),
+                Ok(address) => Ok(Some(address)),
+                Err(e) if is_missing_ens_record(&e) => Ok(None),
+                Err(e) => Err(EvmError::rpc(
+                    &format!("Failed to resolve ENS name {:?}", name),
+                    e,
+                )),
+            }
+        })
+        .await
+    }
+
+    /// ENS registry is only deployed on Ethereum mainnet and its testnets
+    async fn ensure_ens_chain(&self) -> Result<(), EvmError> {
+        const ENS_CHAINS: [u64; 3] = [1, 11_155_111, 17_000];
+        let chain_id = self.get_chain_id().await?;
+        if !ENS_CHAINS.contains(&chain_id) {
+            return Err(EvmError::ConfigError(format!(
+                "ENS is not available on chain {}",
+                chain_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// ENS lookup failure that means the record is missing rather than the node failing:
+/// no resolver, a resolver without the record, a reverse record that does not point
+/// back, or a resolver that reverted
+fn is_missing_ens_record(e: &ProviderError) -> bool {
+    use ethers::providers::RpcError as _;
+    matches!(
+        e,
+        ProviderError::EnsError(_) | ProviderError::EnsNotOwned(_)
+    ) || e
+        .as_error_response()
+        .is_some_and(|response| response.as_revert_data().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Evm;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    /// Serve a synced chain 1 node at `block_number`, answering one request per connection
+    async fn mock_node(block_number: u64) -> (String, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let node = tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body = loop {
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break None;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let Some((headers, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length = headers
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break Some(body.to_string());
+                    }
+                };
+                let Some(call) =
+                    body.and_then(|body| serde_json::from_str::<serde_json::Value>(&body).ok())
+                else {
+                    continue;
+                };
+                let result = match call["method"].as_str() {
+                    Some("eth_chainId") => serde_json::json!("0x1"),
+                    Some("eth_blockNumber") => serde_json::json!(format!("0x{:x}", block_number)),
+                    Some("eth_syncing") => serde_json::json!(false),
+                    _ => serde_json::Value::Null,
+                };
+                let response =
+                    serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+                        .to_string();
+                let _ = stream
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            response.len(),
+                            response
+                        )
+                        .as_bytes(),
+                    )
+                    .await;
+            }
+        });
+        (url, node)
+    }
+
+    #[tokio::test]
+    async fn test_with_rpc_urls_fails_over_from_dead_primary() {
+        let (primary_url, primary) = mock_node(100).await;
+        let (backup_url, _backup) = mock_node(200).await;
+        let evm = Evm::with_rpc_urls(vec![primary_url, backup_url.clone()])
+            .await
+            .unwrap();
+        assert_eq!(evm.get_block_number().await.unwrap(), 100);
+        primary.abort();
+        let _ = primary.await;
+        assert_eq!(evm.get_block_number().await.unwrap(), 200);
+        // The backup stays in use once the primary failed
+        assert_eq!(
+            evm.provider().url().as_str().trim_end_matches('/'),
+            backup_url
+        );
+        assert_eq!(evm.get_block_number().await.unwrap(), 200);
+    }
 }
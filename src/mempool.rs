@@ -1,14 +1,15 @@
 /// This module provides memory pool-related functionalities.
 use crate::Evm;
 use crate::types::EvmError;
-use ethers::providers::Middleware;
+use ethers::abi::Token;
+use ethers::providers::{Middleware, Provider, StreamExt, Ws};
 use ethers::types::Bytes;
 use ethers::types::{Address, U256};
 use ethers::types::{Filter, Transaction, TxHash};
 use sha3::{Digest, Keccak256};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, mpsc};
 use tokio::time::{Duration, sleep};
 
 /// Represents a transaction in the mempool
@@ -28,11 +29,20 @@ pub struct MempoolTransaction {
     pub transaction: Transaction,
     pub first_seen: u64,
     pub last_seen: u64,
+    /// Unix timestamp (seconds) of the last poll that saw the transaction
+    pub last_seen_at: u64,
     pub is_mev: bool,
     pub bundle_hash: Option<TxHash>,
     pub frontrunning_protection: bool,
 }
 
+impl MempoolTransaction {
+    /// Not seen pending for more than `max_reorg_depth` blocks, so it may be checked for eviction
+    fn is_stale(&self, current_block: u64, max_reorg_depth: u64) -> bool {
+        current_block.saturating_sub(self.last_seen) > max_reorg_depth
+    }
+}
+
 /// Configuration for mempool monitoring
 #[derive(Debug, Clone)]
 pub struct MempoolConfig {
@@ -43,6 +53,10 @@ pub struct MempoolConfig {
     pub max_reorg_depth: u64,
     pub simulate_transactions: bool,
     pub track_bundles: bool,
+    /// Blocks a transaction may be missing from the pending set and, on reappearing
+    /// with the same hash, still count as the same sighting. Beyond it the
+    /// transaction is treated as newly broadcast.
+    pub dedup_window: u64,
 }
 
 impl Default for MempoolConfig {
@@ -55,6 +69,7 @@ impl Default for MempoolConfig {
             max_reorg_depth: 5,
             simulate_transactions: false,
             track_bundles: false,
+            dedup_window: 10,
         }
     }
 }
@@ -77,6 +92,41 @@ struct MempoolState {
     // Transaction package tracking
     // bundle_hash -> [tx_hashes]
     transaction_bundles: HashMap<TxHash, Vec<TxHash>>,
+    // contract -> subscribers receiving its pending transactions
+    contract_subscribers: HashMap<Address, Vec<mpsc::Sender<MempoolTransaction>>>,
+}
+
+impl MempoolState {
+    /// Refresh `last_seen` of transactions that are already tracked and return the unseen ones
+    ///
+    /// A hash missing for more than `dedup_window` blocks is dropped from the state and
+    /// returned with the unseen ones, so it is ingested again as a fresh broadcast.
+    fn refresh_seen(
+        &mut self,
+        transactions: Vec<Transaction>,
+        current_block: u64,
+        now: u64,
+        dedup_window: u64,
+    ) -> Vec<Transaction> {
+        let mut unseen = Vec::new();
+        for tx in transactions {
+            match self.transactions.get_mut(&tx.hash) {
+                Some(tracked)
+                    if current_block.saturating_sub(tracked.last_seen) <= dedup_window =>
+                {
+                    tracked.last_seen = current_block;
+                    tracked.last_seen_at = now;
+                }
+                Some(_) => {
+                    self.transactions.remove(&tx.hash);
+                    self.pending_hashes.remove(&tx.hash);
+                    unseen.push(tx);
+                }
+                None => unseen.push(tx),
+            }
+        }
+        unseen
+    }
 }
 
 impl MempoolListener {
@@ -96,6 +146,7 @@ impl MempoolListener {
                 last_block_number: 0,
                 is_running: false,
                 transaction_bundles: HashMap::new(),
+                contract_subscribers: HashMap::new(),
             })),
         }
     }
@@ -126,6 +177,36 @@ impl MempoolListener {
         Ok(())
     }
 
+    /// Subscribes to pending transactions sent to `contract`
+    ///
+    /// While any contract subscription is open, the listener only tracks transactions
+    /// whose `to` is a subscribed contract. Everything else is dropped before MEV and
+    /// bundle detection, which keeps a router watcher cheap on busy chains but also
+    /// narrows what the query methods of this listener return. Subscriptions end when
+    /// their receiver is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// let mut router_txs = listener.subscribe_to_contract(router).await;
+    /// listener.start().await?;
+    /// while let Some(tx) = router_txs.recv().await {
+    ///     println!("Pending router call: {:?}", tx.hash);
+    /// }
+    /// ```
+    pub async fn subscribe_to_contract(
+        &self,
+        contract: Address,
+    ) -> mpsc::Receiver<MempoolTransaction> {
+        let (sender, receiver) = mpsc::channel(1024);
+        let mut state = self.state.write().await;
+        state
+            .contract_subscribers
+            .entry(contract)
+            .or_default()
+            .push(sender);
+        receiver
+    }
+
     /// Stops the mempool listener
     pub async fn stop(&self) {
         let mut state = self.state.write().await;
@@ -133,7 +214,18 @@ impl MempoolListener {
     }
 
     /// Main run loop
+    ///
+    /// Follows the `newPendingTransactions` subscription when a WebSocket is attached to
+    /// the `Evm`, and falls back to polling the pending block if it fails or ends.
     async fn run(&self) {
+        if let Some(ws) = self.evm.ws_provider().cloned() {
+            if let Err(e) = self.run_subscription(&ws).await {
+                eprintln!(
+                    "Pending transaction subscription failed, polling instead: {}",
+                    e
+                );
+            }
+        }
         while self.is_running().await {
             if let Err(e) = self.poll_mempool().await {
                 eprintln!("Error polling mempool: {}", e);
@@ -142,6 +234,48 @@ impl MempoolListener {
         }
     }
 
+    /// Track transactions as the node announces them, cleaning up every `poll_interval`
+    async fn run_subscription(&self, ws: &Provider<Ws>) -> Result<(), EvmError> {
+        let mut stream = ws.subscribe_pending_txs().await.map_err(|e| {
+            EvmError::MempoolError(format!(
+                "Failed to subscribe to pending transactions: {}",
+                e
+            ))
+        })?;
+        let mut cleanup = tokio::time::interval(self.config.poll_interval);
+        while self.is_running().await {
+            tokio::select! {
+                tx_hash = stream.next() => {
+                    let Some(tx_hash) = tx_hash else {
+                        return Err(EvmError::MempoolError(
+                            "Pending transaction subscription ended".to_string(),
+                        ));
+                    };
+                    match ws.get_transaction(tx_hash).await {
+                        Ok(Some(tx)) if tx.block_number.is_none() => {
+                            let current_block = self.state.read().await.last_block_number;
+                            self.update_mempool_state(vec![tx], current_block).await;
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("Failed to get transaction {}: {}", tx_hash, e),
+                    }
+                }
+                _ = cleanup.tick() => {
+                    match self.evm.get_block_number().await {
+                        Ok(current_block) => {
+                            self.state.write().await.last_block_number = current_block;
+                        }
+                        Err(e) => eprintln!("Error getting block number: {}", e),
+                    }
+                    if let Err(e) = self.clean_confirmed_transactions().await {
+                        eprintln!("Error cleaning mempool: {}", e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Checks if the listener is running
     async fn is_running(&self) -> bool {
         self.state.read().await.is_running
@@ -190,24 +324,28 @@ impl MempoolListener {
     ///
     async fn get_pending_transactions(&self) -> Result<Vec<Transaction>, EvmError> {
         // timeout control
-        let block = tokio::time::timeout(
-            Duration::from_secs(30),
-            self.evm
-                .client
-                .provider
-                .get_block(ethers::types::BlockId::Number(
-                    ethers::types::BlockNumber::Pending,
-                )),
-        )
-        .await
-        .map_err(|_| EvmError::RpcError("Timeout getting pending block".to_string()))?
-        .map_err(|e| EvmError::RpcError(format!("Failed to get pending block: {}", e)))?;
+        let block = self
+            .evm
+            .read_with_retry(|| async {
+                tokio::time::timeout(
+                    Duration::from_secs(30),
+                    self.evm
+                        .provider()
+                        .get_block(ethers::types::BlockId::Number(
+                            ethers::types::BlockNumber::Pending,
+                        )),
+                )
+                .await
+                .map_err(|_| EvmError::RpcError("Timeout getting pending block".to_string()))?
+                .map_err(|e| EvmError::rpc("Failed to get pending block", e))
+            })
+            .await?;
         let mut pending_txs = Vec::new();
         if let Some(block) = block {
             // parallel tasks
             let mut handles = Vec::new();
             for tx_hash in block.transactions {
-                let provider = self.evm.client.provider.clone();
+                let provider = self.evm.provider().clone();
                 let handle = tokio::spawn(async move {
                     // try again
                     for attempt in 0..3 {
@@ -250,10 +388,22 @@ impl MempoolListener {
     /// Updates the mempool state with new transactions
     async fn update_mempool_state(&self, transactions: Vec<Transaction>, current_block: u64) {
         let mut state = self.state.write().await;
+        state.contract_subscribers.retain(|_, senders| {
+            senders.retain(|sender| !sender.is_closed());
+            !senders.is_empty()
+        });
+        let contract_filter = !state.contract_subscribers.is_empty();
+        let now = unix_timestamp();
         // collect all new transactions for check package.
-        let new_transactions: Vec<Transaction> = transactions
+        let new_transactions: Vec<Transaction> = state
+            .refresh_seen(transactions, current_block, now, self.config.dedup_window)
             .into_iter()
-            .filter(|tx| !state.transactions.contains_key(&tx.hash))
+            .filter(|tx| {
+                !contract_filter
+                    || tx
+                        .to
+                        .is_some_and(|to| state.contract_subscribers.contains_key(&to))
+            })
             .collect();
         // detect transaction packages
         let bundles = if self.config.track_bundles {
@@ -290,10 +440,20 @@ impl MempoolListener {
                     transaction: tx.clone(),
                     first_seen: current_block,
                     last_seen: current_block,
+                    last_seen_at: now,
                     is_mev,
                     bundle_hash,
                     frontrunning_protection,
                 };
+                if let Some(senders) = tx.to.and_then(|to| state.contract_subscribers.get(&to)) {
+                    for sender in senders {
+                        if let Err(mpsc::error::TrySendError::Full(_)) =
+                            sender.try_send(mempool_tx.clone())
+                        {
+                            eprintln!("Contract subscriber lagging, dropped {:?}", tx.hash);
+                        }
+                    }
+                }
                 state.transactions.insert(tx.hash, mempool_tx);
                 state.pending_hashes.insert(tx.hash);
             }
@@ -306,7 +466,7 @@ impl MempoolListener {
         let mut state = self.state.write().await;
         let mut to_remove = Vec::new();
         for (tx_hash, mempool_tx) in state.transactions.iter() {
-            if current_block.saturating_sub(mempool_tx.last_seen) > self.config.max_reorg_depth {
+            if mempool_tx.is_stale(current_block, self.config.max_reorg_depth) {
                 if let Ok(Some(receipt)) = self.evm.get_transaction_receipt(*tx_hash).await {
                     if receipt.block_number.is_some() {
                         to_remove.push(*tx_hash);
@@ -492,8 +652,7 @@ impl MempoolListener {
     }
 
     fn detect_mev_transaction(tx: &Transaction) -> bool {
-        let input_str = hex::encode(&tx.input);
-        input_str.contains("0x6a761202")
+        crate::tool::decode_safe_exec(&tx.input).is_some()
             || tx.value.is_zero() && !tx.input.is_empty()
             || tx.gas_price.unwrap_or_default() > U256::from(100_000_000_000u64)
     }
@@ -620,6 +779,192 @@ impl MempoolListener {
         let state = self.state.read().await;
         state.transaction_bundles.clone()
     }
+
+    /// Capture the tracked pending transactions at this moment
+    ///
+    /// # Example
+    /// ```
+    /// let before = listener.snapshot().await;
+    /// sleep(Duration::from_secs(12)).await;
+    /// let diff = MempoolListener::diff_snapshots(&before, &listener.snapshot().await);
+    /// println!("+{} -{} ~{}", diff.added.len(), diff.removed.len(), diff.replaced.len());
+    /// ```
+    pub async fn snapshot(&self) -> MempoolSnapshot {
+        let state = self.state.read().await;
+        MempoolSnapshot {
+            taken_at: unix_timestamp(),
+            block_number: state.last_block_number,
+            transactions: state.transactions.clone(),
+        }
+    }
+
+    /// Compare two snapshots of the same listener
+    ///
+    /// A transaction that disappeared while another one from the same sender with the
+    /// same nonce appeared was replaced (sped up or cancelled) and is reported only in
+    /// `replaced`. Other disappearances are in `removed`, whether the transaction was
+    /// mined or dropped.
+    pub fn diff_snapshots(before: &MempoolSnapshot, after: &MempoolSnapshot) -> MempoolDiff {
+        let mut removed: HashMap<(Address, U256), &MempoolTransaction> = before
+            .transactions
+            .values()
+            .filter(|tx| !after.transactions.contains_key(&tx.hash))
+            .map(|tx| ((tx.from, tx.nonce), tx))
+            .collect();
+        let mut diff = MempoolDiff::default();
+        for tx in after.transactions.values() {
+            if before.transactions.contains_key(&tx.hash) {
+                continue;
+            }
+            match removed.remove(&(tx.from, tx.nonce)) {
+                Some(old) => diff.replaced.push((old.clone(), tx.clone())),
+                None => diff.added.push(tx.clone()),
+            }
+        }
+        diff.removed = removed.into_values().cloned().collect();
+        diff.added.sort_by_key(|tx| (tx.from, tx.nonce));
+        diff.removed.sort_by_key(|tx| (tx.from, tx.nonce));
+        diff.replaced.sort_by_key(|(old, _)| (old.from, old.nonce));
+        diff
+    }
+
+    /// Find pending `approve` transactions raced by a `transferFrom` of the same spender
+    ///
+    /// Pairs an `approve(spender, amount)` from an owner with a pending
+    /// `transferFrom(owner, ..)` sent by that spender on the same token. This is the
+    /// allowance-change race: a spender who sees its allowance being changed can spend
+    /// the old allowance first and the new one afterwards. `outbids_approve` marks pairs
+    /// where the `transferFrom` pays a higher tip and will likely be mined first.
+    ///
+    /// # Example
+    /// ```
+    /// for candidate in listener.detect_approval_exploits().await {
+    ///     println!("{:?} races {:?}", candidate.transfer_from_hash, candidate.approve_hash);
+    /// }
+    /// ```
+    pub async fn detect_approval_exploits(&self) -> Vec<ApprovalExploitCandidate> {
+        let state = self.state.read().await;
+        let transactions: Vec<&MempoolTransaction> = state.transactions.values().collect();
+        Self::find_approval_exploits(&transactions)
+    }
+
+    fn find_approval_exploits(
+        transactions: &[&MempoolTransaction],
+    ) -> Vec<ApprovalExploitCandidate> {
+        let decode = |tx: &MempoolTransaction, signature: &str| -> Option<Vec<Token>> {
+            let info = crate::tool::decode_calldata(&tx.input, None);
+            if info.signature.as_deref() == Some(signature) {
+                info.args
+            } else {
+                None
+            }
+        };
+        let tip = |tx: &MempoolTransaction| {
+            tx.max_priority_fee_per_gas
+                .or(tx.gas_price)
+                .unwrap_or_default()
+        };
+        // (token, owner, spender) -> pending transferFrom calls
+        let mut transfers: HashMap<(Address, Address, Address), Vec<(&MempoolTransaction, U256)>> =
+            HashMap::new();
+        for &tx in transactions {
+            let (Some(token), Some(args)) =
+                (tx.to, decode(tx, "transferFrom(address,address,uint256)"))
+            else {
+                continue;
+            };
+            if let [Token::Address(owner), _, Token::Uint(amount)] = args.as_slice() {
+                transfers
+                    .entry((token, *owner, tx.from))
+                    .or_default()
+                    .push((tx, *amount));
+            }
+        }
+        let mut candidates = Vec::new();
+        for &tx in transactions {
+            let (Some(token), Some(args)) = (tx.to, decode(tx, "approve(address,uint256)")) else {
+                continue;
+            };
+            let [Token::Address(spender), Token::Uint(approved_amount)] = args.as_slice() else {
+                continue;
+            };
+            let Some(raced_by) = transfers.get(&(token, tx.from, *spender)) else {
+                continue;
+            };
+            for &(transfer_from, transfer_amount) in raced_by {
+                candidates.push(ApprovalExploitCandidate {
+                    token,
+                    owner: tx.from,
+                    spender: *spender,
+                    approve_hash: tx.hash,
+                    transfer_from_hash: transfer_from.hash,
+                    approved_amount: *approved_amount,
+                    transfer_amount,
+                    outbids_approve: tip(transfer_from) > tip(tx),
+                });
+            }
+        }
+        candidates
+    }
+}
+
+/// `txpool_contentFrom` response: one account's transactions keyed by nonce
+#[derive(Debug, Default, serde::Deserialize)]
+struct TxpoolAccountContent {
+    #[serde(default)]
+    pending: BTreeMap<String, Transaction>,
+    #[serde(default)]
+    queued: BTreeMap<String, Transaction>,
+}
+
+impl TxpoolAccountContent {
+    fn into_transactions(self) -> Vec<Transaction> {
+        let mut transactions: Vec<Transaction> = self
+            .pending
+            .into_values()
+            .chain(self.queued.into_values())
+            .collect();
+        transactions.sort_by_key(|tx| tx.nonce);
+        transactions
+    }
+}
+
+/// Tracked pending transactions at one point in time, see `MempoolListener::snapshot`
+#[derive(Debug, Clone)]
+pub struct MempoolSnapshot {
+    /// Unix timestamp (seconds) the snapshot was taken at
+    pub taken_at: u64,
+    /// Latest block the listener had seen
+    pub block_number: u64,
+    pub transactions: HashMap<TxHash, MempoolTransaction>,
+}
+
+/// Changes between two snapshots, see `MempoolListener::diff_snapshots`
+#[derive(Debug, Clone, Default)]
+pub struct MempoolDiff {
+    /// Newly broadcast transactions
+    pub added: Vec<MempoolTransaction>,
+    /// Transactions that left the pool, mined or dropped
+    pub removed: Vec<MempoolTransaction>,
+    /// (old, new) pairs of transactions replaced with the same sender and nonce
+    pub replaced: Vec<(MempoolTransaction, MempoolTransaction)>,
+}
+
+/// A pending `approve` and a pending `transferFrom` racing it, see
+/// `MempoolListener::detect_approval_exploits`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalExploitCandidate {
+    pub token: Address,
+    /// Account changing its allowance
+    pub owner: Address,
+    /// Approved account, also the sender of the `transferFrom`
+    pub spender: Address,
+    pub approve_hash: TxHash,
+    pub transfer_from_hash: TxHash,
+    pub approved_amount: U256,
+    pub transfer_amount: U256,
+    /// The `transferFrom` pays a higher priority fee than the `approve`
+    pub outbids_approve: bool,
 }
 
 /// Statistics about the mempool state
@@ -696,4 +1041,291 @@ impl MempoolService {
     pub async fn get_suggested_gas_price(&self) -> Result<U256, EvmError> {
         self.evm.get_gas_price().await
     }
+
+    /// Gets the pending and queued transactions of an account straight from the node
+    ///
+    /// Uses `txpool_contentFrom` (Geth), then `txpool_content` filtered by sender. On
+    /// nodes exposing neither, falls back to the pending block the listener tracks,
+    /// which only holds transactions that are ready for inclusion (no queued ones).
+    /// Results are sorted by nonce.
+    ///
+    /// # Example
+    /// ```
+    /// let pending = service.get_pending_for_account(wallet).await?;
+    /// for tx in pending {
+    ///     println!("nonce {} -> {:?}", tx.nonce, tx.hash);
+    /// }
+    /// ```
+    pub async fn get_pending_for_account(
+        &self,
+        address: Address,
+    ) -> Result<Vec<Transaction>, EvmError> {
+        let provider = self.evm.provider();
+        if let Ok(content) = provider
+            .request::<_, TxpoolAccountContent>("txpool_contentFrom", [address])
+            .await
+        {
+            return Ok(content.into_transactions());
+        }
+        if let Ok(mut content) = provider.txpool_content().await {
+            let account = TxpoolAccountContent {
+                pending: content.pending.remove(&address).unwrap_or_default(),
+                queued: content.queued.remove(&address).unwrap_or_default(),
+            };
+            return Ok(account.into_transactions());
+        }
+        let listener = self.create_listener();
+        let mut transactions: Vec<Transaction> = listener
+            .get_pending_transactions()
+            .await?
+            .into_iter()
+            .filter(|tx| tx.from == address)
+            .collect();
+        transactions.sort_by_key(|tx| tx.nonce);
+        Ok(transactions)
+    }
+
+    /// Gets the balance a wallet can still spend while its transactions are in flight
+    ///
+    /// Starts from the on-chain balance and subtracts `value + gas * max fee` of every
+    /// pending outgoing transaction whose nonce has not been mined yet. Saturates at zero.
+    ///
+    /// # Example
+    /// ```
+    /// let available = service.get_effective_balance(wallet).await?;
+    /// println!("Available: {}", ethers::utils::format_ether(available));
+    /// ```
+    pub async fn get_effective_balance(&self, address: Address) -> Result<U256, EvmError> {
+        let balance = self.evm.get_balance(address).await?;
+        let mined_nonce = U256::from(self.evm.get_transaction_count(address).await?);
+        let reserved = self
+            .get_pending_for_account(address)
+            .await?
+            .iter()
+            .filter(|tx| tx.nonce >= mined_nonce)
+            .fold(U256::zero(), |total, tx| {
+                total.saturating_add(max_transaction_cost(tx))
+            });
+        Ok(balance.saturating_sub(reserved))
+    }
+
+    /// Gets the next nonce the wallet can use, accounting for its pending transactions
+    ///
+    /// Walks the pending nonces upward from the mined nonce and returns the first gap,
+    /// so queued transactions behind a gap do not push the nonce past it.
+    ///
+    /// # Example
+    /// ```
+    /// let nonce = service.get_pending_nonce(wallet).await?;
+    /// let tx = TransactionRequest::new().nonce(nonce);
+    /// ```
+    pub async fn get_pending_nonce(&self, address: Address) -> Result<U256, EvmError> {
+        let mut nonce = U256::from(self.evm.get_transaction_count(address).await?);
+        let pending: HashSet<U256> = self
+            .get_pending_for_account(address)
+            .await?
+            .into_iter()
+            .map(|tx| tx.nonce)
+            .collect();
+        while pending.contains(&nonce) {
+            nonce += U256::one();
+        }
+        Ok(nonce)
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Worst-case amount a transaction can take from its sender: value plus gas at the max fee
+fn max_transaction_cost(tx: &Transaction) -> U256 {
+    let fee_per_gas = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+    tx.value.saturating_add(tx.gas.saturating_mul(fee_per_gas))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_state() -> MempoolState {
+        MempoolState {
+            transactions: HashMap::new(),
+            pending_hashes: HashSet::new(),
+            last_block_number: 0,
+            is_running: false,
+            transaction_bundles: HashMap::new(),
+            contract_subscribers: HashMap::new(),
+        }
+    }
+
+    fn track(state: &mut MempoolState, tx: &Transaction, block: u64) {
+        state.transactions.insert(
+            tx.hash,
+            MempoolTransaction {
+                hash: tx.hash,
+                from: tx.from,
+                to: tx.to,
+                value: tx.value,
+                transaction_type: None,
+                gas_price: tx.gas_price,
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                gas: tx.gas,
+                input: tx.input.clone(),
+                nonce: tx.nonce,
+                transaction: tx.clone(),
+                first_seen: block,
+                last_seen: block,
+                last_seen_at: 0,
+                is_mev: false,
+                bundle_hash: None,
+                frontrunning_protection: false,
+            },
+        );
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let sender = Address::repeat_byte(0x01);
+        let pending = |hash: u8, nonce: u64| Transaction {
+            hash: TxHash::repeat_byte(hash),
+            from: sender,
+            nonce: U256::from(nonce),
+            ..Default::default()
+        };
+        let snapshot = |txs: &[&Transaction]| {
+            let mut state = empty_state();
+            for tx in txs {
+                track(&mut state, tx, 1);
+            }
+            MempoolSnapshot {
+                taken_at: 0,
+                block_number: 1,
+                transactions: state.transactions,
+            }
+        };
+        let (kept, mined, original, added) =
+            (pending(1, 5), pending(2, 3), pending(3, 4), pending(4, 6));
+        let speed_up = pending(5, 4);
+        let before = snapshot(&[&kept, &mined, &original]);
+        let after = snapshot(&[&kept, &speed_up, &added]);
+
+        let diff = MempoolListener::diff_snapshots(&before, &after);
+        let hashes = |txs: &[MempoolTransaction]| txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+        assert_eq!(hashes(&diff.added), vec![added.hash]);
+        assert_eq!(hashes(&diff.removed), vec![mined.hash]);
+        assert_eq!(diff.replaced.len(), 1);
+        assert_eq!(diff.replaced[0].0.hash, original.hash);
+        assert_eq!(diff.replaced[0].1.hash, speed_up.hash);
+    }
+
+    #[test]
+    fn test_find_approval_exploits() {
+        use ethers::abi::encode;
+        use ethers::utils::id;
+
+        let token = Address::repeat_byte(0x70);
+        let owner = Address::repeat_byte(0x01);
+        let spender = Address::repeat_byte(0x02);
+        let calldata = |signature: &str, args: &[Token]| {
+            let mut input = id(signature).to_vec();
+            input.extend(encode(args));
+            Bytes::from(input)
+        };
+        let approve = Transaction {
+            hash: TxHash::repeat_byte(0xa1),
+            from: owner,
+            to: Some(token),
+            gas_price: Some(U256::from(10)),
+            input: calldata(
+                "approve(address,uint256)",
+                &[Token::Address(spender), Token::Uint(U256::from(50))],
+            ),
+            ..Default::default()
+        };
+        let transfer_from = Transaction {
+            hash: TxHash::repeat_byte(0xb2),
+            from: spender,
+            to: Some(token),
+            gas_price: Some(U256::from(20)),
+            input: calldata(
+                "transferFrom(address,address,uint256)",
+                &[
+                    Token::Address(owner),
+                    Token::Address(spender),
+                    Token::Uint(U256::from(100)),
+                ],
+            ),
+            ..Default::default()
+        };
+        let unrelated = Transaction {
+            hash: TxHash::repeat_byte(0xc3),
+            from: Address::repeat_byte(0x03),
+            to: Some(token),
+            input: transfer_from.input.clone(),
+            ..Default::default()
+        };
+        let mut state = empty_state();
+        for tx in [&approve, &transfer_from, &unrelated] {
+            track(&mut state, tx, 1);
+        }
+        let transactions: Vec<&MempoolTransaction> = state.transactions.values().collect();
+        let candidates = MempoolListener::find_approval_exploits(&transactions);
+        assert_eq!(
+            candidates,
+            vec![ApprovalExploitCandidate {
+                token,
+                owner,
+                spender,
+                approve_hash: approve.hash,
+                transfer_from_hash: transfer_from.hash,
+                approved_amount: U256::from(50),
+                transfer_amount: U256::from(100),
+                outbids_approve: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reappearing_transaction_is_refreshed() {
+        let config = MempoolConfig::default();
+        let tx = Transaction {
+            hash: TxHash::repeat_byte(0x42),
+            ..Default::default()
+        };
+        let mut state = empty_state();
+        track(&mut state, &tx, 100);
+
+        // dropped from the pending set for a few polls, then rebroadcast
+        assert!(
+            state
+                .refresh_seen(vec![], 103, 1_000, config.dedup_window)
+                .is_empty()
+        );
+        let unseen = state.refresh_seen(vec![tx.clone()], 104, 1_024, config.dedup_window);
+        assert!(unseen.is_empty());
+
+        let tracked = &state.transactions[&tx.hash];
+        assert_eq!((tracked.first_seen, tracked.last_seen), (100, 104));
+        assert_eq!(tracked.last_seen_at, 1_024);
+        assert!(!tracked.is_stale(108, config.max_reorg_depth));
+        assert!(tracked.is_stale(110, config.max_reorg_depth));
+    }
+
+    #[test]
+    fn test_reappearing_after_window_is_new() {
+        let tx = Transaction {
+            hash: TxHash::repeat_byte(0x43),
+            ..Default::default()
+        };
+        let mut state = empty_state();
+        track(&mut state, &tx, 100);
+        let unseen = state.refresh_seen(vec![tx.clone()], 120, 0, 10);
+        assert_eq!(unseen.len(), 1);
+        assert!(!state.transactions.contains_key(&tx.hash));
+    }
 }
@@ -0,0 +1,356 @@
+use crate::global::lending_events;
+use ethers::{
+    abi::{ParamType, Token, decode},
+    types::{Address, H256, Log, U256},
+};
+use serde::{Deserialize, Serialize};
+
+/// Aave V3 `LiquidationCall`: `liquidator` repaid `debt_to_cover` of `user`'s debt and
+/// seized `liquidated_collateral_amount` of their collateral
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiquidationEvent {
+    pub pool: Address,
+    pub collateral_asset: Address,
+    pub debt_asset: Address,
+    pub user: Address,
+    pub debt_to_cover: U256,
+    pub liquidated_collateral_amount: U256,
+    pub liquidator: Address,
+    /// The liquidator took aTokens instead of the underlying collateral
+    pub receive_a_token: bool,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<H256>,
+    pub log_index: Option<u64>,
+}
+
+impl LiquidationEvent {
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        match LendingEvent::from_log(log)? {
+            LendingEvent::Liquidation(event) => Ok(event),
+            _ => Err("Not a LiquidationCall log".to_string()),
+        }
+    }
+}
+
+/// Aave V3 pool or Compound V2 cToken event decoded from a log
+///
+/// Aave events name the pool and the reserve (underlying asset); Compound events are
+/// emitted by the cToken itself, so `market` is the cToken and the underlying has to be
+/// looked up with `underlying()` when needed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LendingEvent {
+    /// `user` supplied `amount` of `reserve`, credited to `on_behalf_of`
+    Supply {
+        pool: Address,
+        reserve: Address,
+        user: Address,
+        on_behalf_of: Address,
+        amount: U256,
+    },
+    /// `user` withdrew `amount` of `reserve` to `to`
+    Withdraw {
+        pool: Address,
+        reserve: Address,
+        user: Address,
+        to: Address,
+        amount: U256,
+    },
+    /// `user` borrowed `amount` of `reserve`, the debt going to `on_behalf_of`
+    Borrow {
+        pool: Address,
+        reserve: Address,
+        user: Address,
+        on_behalf_of: Address,
+        amount: U256,
+    },
+    /// `repayer` repaid `amount` of `user`'s `reserve` debt
+    Repay {
+        pool: Address,
+        reserve: Address,
+        user: Address,
+        repayer: Address,
+        amount: U256,
+    },
+    Liquidation(LiquidationEvent),
+    /// `minter` supplied `amount` of the underlying and received `tokens` cTokens
+    CompoundMint {
+        market: Address,
+        minter: Address,
+        amount: U256,
+        tokens: U256,
+    },
+    /// `redeemer` burned `tokens` cTokens for `amount` of the underlying
+    CompoundRedeem {
+        market: Address,
+        redeemer: Address,
+        amount: U256,
+        tokens: U256,
+    },
+    /// `borrower` borrowed `amount`, bringing their debt to `account_borrows`
+    CompoundBorrow {
+        market: Address,
+        borrower: Address,
+        amount: U256,
+        account_borrows: U256,
+    },
+}
+
+impl LendingEvent {
+    /// Decode an Aave V3 or Compound V2 lending log
+    ///
+    /// Compound's `Mint` shares its topic with Uniswap V2's `Mint`; the two are told
+    /// apart by the indexed sender of the latter, so pair logs are rejected.
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        let topic0 = log.topics.first().ok_or("Log has no topics")?.as_bytes();
+        let address = |index: usize| Address::from(log.topics[index]);
+        if topic0 == lending_events::aave_supply() {
+            let [user, amount] = decode_log(log, 3, &[ParamType::Address, ParamType::Uint(256)])?;
+            Ok(LendingEvent::Supply {
+                pool: log.address,
+                reserve: address(1),
+                user: token_address(user)?,
+                on_behalf_of: address(2),
+                amount: token_uint(amount)?,
+            })
+        } else if topic0 == lending_events::aave_withdraw() {
+            let [amount] = decode_log(log, 3, &[ParamType::Uint(256)])?;
+            Ok(LendingEvent::Withdraw {
+                pool: log.address,
+                reserve: address(1),
+                user: address(2),
+                to: address(3),
+                amount: token_uint(amount)?,
+            })
+        } else if topic0 == lending_events::aave_borrow() {
+            let [user, amount, _, _] = decode_log(
+                log,
+                3,
+                &[
+                    ParamType::Address,
+                    ParamType::Uint(256),
+                    ParamType::Uint(8),
+                    ParamType::Uint(256),
+                ],
+            )?;
+            Ok(LendingEvent::Borrow {
+                pool: log.address,
+                reserve: address(1),
+                user: token_address(user)?,
+                on_behalf_of: address(2),
+                amount: token_uint(amount)?,
+            })
+        } else if topic0 == lending_events::aave_repay() {
+            let [amount, _] = decode_log(log, 3, &[ParamType::Uint(256), ParamType::Bool])?;
+            Ok(LendingEvent::Repay {
+                pool: log.address,
+                reserve: address(1),
+                user: address(2),
+                repayer: address(3),
+                amount: token_uint(amount)?,
+            })
+        } else if topic0 == lending_events::aave_liquidation_call() {
+            let [
+                debt_to_cover,
+                collateral_amount,
+                liquidator,
+                receive_a_token,
+            ] = decode_log(
+                log,
+                3,
+                &[
+                    ParamType::Uint(256),
+                    ParamType::Uint(256),
+                    ParamType::Address,
+                    ParamType::Bool,
+                ],
+            )?;
+            Ok(LendingEvent::Liquidation(LiquidationEvent {
+                pool: log.address,
+                collateral_asset: address(1),
+                debt_asset: address(2),
+                user: address(3),
+                debt_to_cover: token_uint(debt_to_cover)?,
+                liquidated_collateral_amount: token_uint(collateral_amount)?,
+                liquidator: token_address(liquidator)?,
+                receive_a_token: receive_a_token
+                    .into_bool()
+                    .ok_or("Invalid receiveAToken flag")?,
+                block_number: log.block_number.map(|number| number.as_u64()),
+                transaction_hash: log.transaction_hash,
+                log_index: log.log_index.map(|index| index.as_u64()),
+            }))
+        } else if topic0 == lending_events::compound_mint() {
+            let [minter, amount, tokens] = decode_log(log, 0, &compound_params(3))?;
+            Ok(LendingEvent::CompoundMint {
+                market: log.address,
+                minter: token_address(minter)?,
+                amount: token_uint(amount)?,
+                tokens: token_uint(tokens)?,
+            })
+        } else if topic0 == lending_events::compound_redeem() {
+            let [redeemer, amount, tokens] = decode_log(log, 0, &compound_params(3))?;
+            Ok(LendingEvent::CompoundRedeem {
+                market: log.address,
+                redeemer: token_address(redeemer)?,
+                amount: token_uint(amount)?,
+                tokens: token_uint(tokens)?,
+            })
+        } else if topic0 == lending_events::compound_borrow() {
+            let [borrower, amount, account_borrows, _] = decode_log(log, 0, &compound_params(4))?;
+            Ok(LendingEvent::CompoundBorrow {
+                market: log.address,
+                borrower: token_address(borrower)?,
+                amount: token_uint(amount)?,
+                account_borrows: token_uint(account_borrows)?,
+            })
+        } else {
+            Err(format!("Not a lending event: {:?}", log.topics[0]))
+        }
+    }
+
+    /// Account whose position changed: the credited supplier, the debtor or the
+    /// liquidated user
+    pub fn user(&self) -> Address {
+        match self {
+            LendingEvent::Supply { on_behalf_of, .. }
+            | LendingEvent::Borrow { on_behalf_of, .. } => *on_behalf_of,
+            LendingEvent::Withdraw { user, .. } | LendingEvent::Repay { user, .. } => *user,
+            LendingEvent::Liquidation(event) => event.user,
+            LendingEvent::CompoundMint { minter, .. } => *minter,
+            LendingEvent::CompoundRedeem { redeemer, .. } => *redeemer,
+            LendingEvent::CompoundBorrow { borrower, .. } => *borrower,
+        }
+    }
+
+    /// Asset the amount is denominated in: the Aave reserve (the debt asset for
+    /// liquidations) or the Compound cToken market
+    pub fn asset(&self) -> Address {
+        match self {
+            LendingEvent::Supply { reserve, .. }
+            | LendingEvent::Withdraw { reserve, .. }
+            | LendingEvent::Borrow { reserve, .. }
+            | LendingEvent::Repay { reserve, .. } => *reserve,
+            LendingEvent::Liquidation(event) => event.debt_asset,
+            LendingEvent::CompoundMint { market, .. }
+            | LendingEvent::CompoundRedeem { market, .. }
+            | LendingEvent::CompoundBorrow { market, .. } => *market,
+        }
+    }
+
+    /// Amount of `asset()` moved, in its smallest unit
+    pub fn amount(&self) -> U256 {
+        match self {
+            LendingEvent::Supply { amount, .. }
+            | LendingEvent::Withdraw { amount, .. }
+            | LendingEvent::Borrow { amount, .. }
+            | LendingEvent::Repay { amount, .. }
+            | LendingEvent::CompoundMint { amount, .. }
+            | LendingEvent::CompoundRedeem { amount, .. }
+            | LendingEvent::CompoundBorrow { amount, .. } => *amount,
+            LendingEvent::Liquidation(event) => event.debt_to_cover,
+        }
+    }
+}
+
+/// Compound events index nothing: an address followed by `uints` - 1 amounts
+fn compound_params(uints: usize) -> Vec<ParamType> {
+    let mut params = vec![ParamType::Address];
+    params.extend(std::iter::repeat_n(ParamType::Uint(256), uints - 1));
+    params
+}
+
+/// Check the number of indexed topics and decode the data of a log into `N` tokens
+fn decode_log<const N: usize>(
+    log: &Log,
+    indexed: usize,
+    params: &[ParamType],
+) -> Result<[Token; N], String> {
+    if log.topics.len() != indexed + 1 {
+        return Err(format!(
+            "Invalid lending event log: expected {} topics, got {}",
+            indexed + 1,
+            log.topics.len()
+        ));
+    }
+    decode(params, &log.data)
+        .map_err(|e| format!("Invalid lending event data: {}", e))?
+        .try_into()
+        .map_err(|_| "Invalid lending event data length".to_string())
+}
+
+fn token_address(token: Token) -> Result<Address, String> {
+    token
+        .into_address()
+        .ok_or_else(|| "Expected an address".to_string())
+}
+
+fn token_uint(token: Token) -> Result<U256, String> {
+    token
+        .into_uint()
+        .ok_or_else(|| "Expected an unsigned integer".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+
+    #[test]
+    fn test_lending_event_from_log() {
+        let pool = Address::repeat_byte(0x11);
+        let collateral = Address::repeat_byte(0x22);
+        let debt = Address::repeat_byte(0x33);
+        let user = Address::repeat_byte(0x44);
+        let liquidator = Address::repeat_byte(0x55);
+        let log = Log {
+            address: pool,
+            topics: vec![
+                H256::from(lending_events::aave_liquidation_call()),
+                H256::from(collateral),
+                H256::from(debt),
+                H256::from(user),
+            ],
+            data: encode(&[
+                Token::Uint(500.into()),
+                Token::Uint(520.into()),
+                Token::Address(liquidator),
+                Token::Bool(false),
+            ])
+            .into(),
+            ..Default::default()
+        };
+        let event = LiquidationEvent::from_log(&log).unwrap();
+        assert_eq!(event.collateral_asset, collateral);
+        assert_eq!(event.liquidator, liquidator);
+        assert_eq!(event.liquidated_collateral_amount, U256::from(520));
+        assert!(!event.receive_a_token);
+
+        let market = Address::repeat_byte(0x66);
+        let mint = Log {
+            address: market,
+            topics: vec![H256::from(lending_events::compound_mint())],
+            data: encode(&[
+                Token::Address(user),
+                Token::Uint(1_000.into()),
+                Token::Uint(49_000.into()),
+            ])
+            .into(),
+            ..Default::default()
+        };
+        let event = LendingEvent::from_log(&mint).unwrap();
+        assert_eq!(
+            (event.user(), event.asset(), event.amount()),
+            (user, market, U256::from(1_000))
+        );
+        // Uniswap V2 `Mint` has the same topic but an indexed sender
+        let pair_mint = Log {
+            topics: vec![
+                H256::from(lending_events::compound_mint()),
+                H256::from(user),
+            ],
+            data: encode(&[Token::Uint(1.into()), Token::Uint(2.into())]).into(),
+            ..Default::default()
+        };
+        assert!(LendingEvent::from_log(&pair_mint).is_err());
+    }
+}
@@ -1 +1,3 @@
-pub mod uniswap;
\ No newline at end of file
+pub mod lending;
+pub mod permit2;
+pub mod uniswap;
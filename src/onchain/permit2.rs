@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use crate::{Evm, EvmError};
+use ethers::{
+    abi::{Token, encode},
+    contract::abigen,
+    providers::{Middleware, Provider},
+    signers::Signer,
+    types::{
+        Address, Signature, U256,
+        transaction::eip712::{EIP712Domain, Eip712},
+    },
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+
+abigen!(
+    IPermit2,
+    r#"[
+        function allowance(address user, address token, address spender) external view returns (uint160 amount, uint48 expiration, uint48 nonce)
+        function DOMAIN_SEPARATOR() external view returns (bytes32)
+    ]"#
+);
+
+/// Canonical Permit2 deployment, at the same address on every chain it is deployed to
+pub const PERMIT2_ADDRESS: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+
+/// `keccak256("PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)")`
+const PERMIT_DETAILS_TYPEHASH: &str =
+    "0x65626cad6cb96493bf6f5ebea28756c966f023ab9e8a83a7101849d5573b3678";
+
+/// `keccak256` of the `PermitSingle` type, with `PermitDetails` appended as EIP-712 requires
+const PERMIT_SINGLE_TYPEHASH: &str =
+    "0xf3841cd1ff0085026a6327b620b67997ce40f282c88a8e905a7a5626e310f3d0";
+
+/// Allowance granted for one token, as signed in a `PermitSingle`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermitDetails {
+    pub token: Address,
+    /// Allowed amount (`uint160`)
+    pub amount: U256,
+    /// Unix timestamp at which the allowance expires (`uint48`)
+    pub expiration: u64,
+    /// Current Permit2 nonce of the (owner, token, spender) triple (`uint48`)
+    pub nonce: u64,
+}
+
+/// Permit2 `PermitSingle` message, passed with its signature to `permit(owner, permitSingle, signature)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermitSingle {
+    pub details: PermitDetails,
+    pub spender: Address,
+    /// Unix timestamp after which the signature is no longer accepted
+    pub sig_deadline: U256,
+}
+
+/// `PermitSingle` bound to the Permit2 domain of a chain, for EIP-712 signing
+struct PermitSingleTypedData<'a> {
+    permit: &'a PermitSingle,
+    chain_id: u64,
+}
+
+impl Eip712 for PermitSingleTypedData<'_> {
+    type Error = EvmError;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(permit2_domain(self.chain_id))
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        typehash(PERMIT_SINGLE_TYPEHASH)
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        let details = &self.permit.details;
+        let details_hash = keccak256(encode(&[
+            Token::FixedBytes(typehash(PERMIT_DETAILS_TYPEHASH)?.to_vec()),
+            Token::Address(details.token),
+            Token::Uint(details.amount),
+            Token::Uint(details.expiration.into()),
+            Token::Uint(details.nonce.into()),
+        ]));
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::FixedBytes(details_hash.to_vec()),
+            Token::Address(self.permit.spender),
+            Token::Uint(self.permit.sig_deadline),
+        ])))
+    }
+}
+
+/// EIP-712 domain of Permit2 on a chain: name `Permit2`, no version
+pub fn permit2_domain(chain_id: u64) -> EIP712Domain {
+    EIP712Domain {
+        name: Some("Permit2".to_string()),
+        version: None,
+        chain_id: Some(U256::from(chain_id)),
+        verifying_contract: PERMIT2_ADDRESS.parse().ok(),
+        salt: None,
+    }
+}
+
+fn typehash(hex_str: &str) -> Result<[u8; 32], EvmError> {
+    let bytes = hex::decode(hex_str.trim_start_matches("0x"))
+        .map_err(|e| EvmError::Error(format!("Invalid type hash: {}", e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| EvmError::Error("Type hash must be 32 bytes".to_string()))
+}
+
+/// Service for reading and signing Permit2 allowances
+pub struct Permit2Service {
+    evm: Arc<Evm>,
+}
+
+impl Permit2Service {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self { evm }
+    }
+
+    /// Permit2 contract, after checking it is deployed on the connected chain
+    async fn permit2(&self) -> Result<IPermit2<Provider<ethers::providers::Http>>, EvmError> {
+        let address: Address = PERMIT2_ADDRESS
+            .parse()
+            .map_err(|e| EvmError::ConfigError(format!("Invalid Permit2 address: {}", e)))?;
+        let code = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_code(address, None)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get Permit2 code", e))
+            })
+            .await?;
+        if code.is_empty() {
+            let chain_id = self.evm.get_chain_id().await?;
+            return Err(EvmError::ConfigError(format!(
+                "Permit2 is not deployed on chain {}",
+                chain_id
+            )));
+        }
+        Ok(IPermit2::new(address, self.evm.provider().clone()))
+    }
+
+    /// Read the Permit2 allowance `owner` granted `spender` for `token`
+    ///
+    /// Returns `(amount, expiration, nonce)`. The expiration is a unix timestamp; an
+    /// allowance past it is unusable even if the amount is non-zero.
+    ///
+    /// # Example
+    /// ```
+    /// let permit2 = Permit2Service::new(evm.clone());
+    /// let (amount, expiration, nonce) = permit2.allowance(owner, usdc, universal_router).await?;
+    /// ```
+    pub async fn allowance(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+    ) -> Result<(U256, u64, u64), EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.permit2()
+                    .await?
+                    .allowance(owner, token, spender)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get Permit2 allowance", &e))
+            })
+            .await
+    }
+
+    /// Build a `PermitSingle` for the wallet and sign it (EIP-712)
+    ///
+    /// The nonce is read from the wallet's current Permit2 allowance, so the permit is
+    /// valid until another permit for the same token and spender is used.
+    ///
+    /// # Example
+    /// ```
+    /// let deadline = U256::from(now + 1800);
+    /// let (permit, signature) = permit2
+    ///     .build_permit_single_signature(usdc, amount, now + 30 * 86400, router, deadline)
+    ///     .await?;
+    /// ```
+    pub async fn build_permit_single_signature(
+        &self,
+        token: Address,
+        amount: U256,
+        expiration: u64,
+        spender: Address,
+        sig_deadline: U256,
+    ) -> Result<(PermitSingle, Signature), EvmError> {
+        let wallet = self
+            .evm
+            .client
+            .wallet
+            .as_ref()
+            .ok_or_else(|| EvmError::WalletError("No wallet configured".to_string()))?;
+        let (_, _, nonce) = self.allowance(wallet.address(), token, spender).await?;
+        let permit = PermitSingle {
+            details: PermitDetails {
+                token,
+                amount,
+                expiration,
+                nonce,
+            },
+            spender,
+            sig_deadline,
+        };
+        let typed_data = PermitSingleTypedData {
+            permit: &permit,
+            chain_id: self.evm.get_chain_id().await?,
+        };
+        let signature = wallet
+            .sign_typed_data(&typed_data)
+            .await
+            .map_err(|e| EvmError::WalletError(format!("Failed to sign permit: {}", e)))?;
+        Ok((permit, signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permit_single_typehash() {
+        let permit_single = "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)\
+            PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+        assert_eq!(
+            PermitSingleTypedData::type_hash().unwrap(),
+            keccak256(permit_single)
+        );
+        assert_eq!(
+            typehash(PERMIT_DETAILS_TYPEHASH).unwrap(),
+            keccak256("PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)")
+        );
+    }
+}
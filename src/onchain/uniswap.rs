@@ -1,8 +1,16 @@
 /// Uniswap
-use crate::{Evm, EvmClient, EvmError};
-use ethers::types::{Address, Bytes, H160, H256, I256, TransactionRequest, U256};
-use ethers::{contract::abigen, providers::Provider, utils};
-use std::sync::Arc;
+use crate::{Evm, EvmClient, EvmError, tool};
+use ethers::abi::{ParamType, decode};
+use ethers::types::{
+    Address, Bytes, Filter, H160, H256, I256, Log, TransactionRequest, U256, U512,
+};
+use ethers::{
+    contract::abigen,
+    providers::{Middleware, Provider},
+    utils,
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
 
 // ==================== Uniswap V2 ABIs ====================
 
@@ -19,6 +27,7 @@ abigen!(
     r#"[ 
         function token0() external view returns (address)
         function token1() external view returns (address)
+        function totalSupply() external view returns (uint)
         function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
         function swap(uint amount0Out, uint amount1Out, address to, bytes calldata data) external
         function mint(address to) external returns (uint liquidity)
@@ -471,6 +480,9 @@ impl<'a, P: ethers::providers::Middleware> UniswapV4PoolManagerManual<'a, P> {
 
 // ==================== Uniswap Service Implementation ====================
 
+/// How long the swap helpers wait for a router approval to be mined
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(180);
+
 /// Uniswap Service
 pub struct UniswapService {
     evm: Arc<Evm>,
@@ -486,7 +498,7 @@ impl UniswapService {
         &self,
         router_address: Address,
     ) -> IUniswapV2Router02<Provider<ethers::providers::Http>> {
-        IUniswapV2Router02::new(router_address, self.evm.client.provider.clone())
+        IUniswapV2Router02::new(router_address, self.evm.provider().clone())
     }
 
     /// Create V2 Factory instance
@@ -494,12 +506,12 @@ impl UniswapService {
         &self,
         factory_address: Address,
     ) -> IUniswapV2Factory<Provider<ethers::providers::Http>> {
-        IUniswapV2Factory::new(factory_address, self.evm.client.provider.clone())
+        IUniswapV2Factory::new(factory_address, self.evm.provider().clone())
     }
 
     /// Create V2 Pair instance
     fn v2_pair(&self, pair_address: Address) -> IUniswapV2Pair<Provider<ethers::providers::Http>> {
-        IUniswapV2Pair::new(pair_address, self.evm.client.provider.clone())
+        IUniswapV2Pair::new(pair_address, self.evm.provider().clone())
     }
 
     /// V2 - Get Pair address
@@ -509,12 +521,15 @@ impl UniswapService {
         token_a: Address,
         token_b: Address,
     ) -> Result<Address, EvmError> {
-        let factory = self.v2_factory(factory_address);
-        factory
-            .get_pair(token_a, token_b)
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v2_factory(factory_address)
+                    .get_pair(token_a, token_b)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get pair", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get pair: {}", e)))
     }
 
     /// V2 - Get reserves
@@ -522,12 +537,16 @@ impl UniswapService {
         &self,
         pair_address: Address,
     ) -> Result<(u128, u128, u32), EvmError> {
-        let pair = self.v2_pair(pair_address);
-        let reserves = pair
-            .get_reserves()
-            .call()
-            .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get reserves: {}", e)))?;
+        let reserves = self
+            .evm
+            .read_with_retry(|| async {
+                self.v2_pair(pair_address)
+                    .get_reserves()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get reserves", &e))
+            })
+            .await?;
         Ok((reserves.0, reserves.1, reserves.2))
     }
 
@@ -622,7 +641,33 @@ impl UniswapService {
         Ok(pending_tx.tx_hash())
     }
 
+    /// Approve the router for `amount_in` of the first token of `path` when needed
+    ///
+    /// An approval is made for `U256::MAX` so later swaps of the same token need none,
+    /// and is waited for since the swap's gas estimation reverts without it.
+    async fn ensure_router_allowance(
+        &self,
+        router_address: Address,
+        path: &[Address],
+        amount_in: U256,
+    ) -> Result<(), EvmError> {
+        let token_in = *path
+            .first()
+            .ok_or_else(|| EvmError::InvalidInput("Swap path is empty".to_string()))?;
+        if let Some(approval) = crate::trade::Trade::new(self.evm.clone())
+            .ensure_allowance(token_in, router_address, amount_in, true)
+            .await?
+        {
+            self.evm
+                .confirm_transaction(approval, 1, APPROVAL_TIMEOUT)
+                .await?;
+        }
+        Ok(())
+    }
+
     /// V2 - Token swap (ExactIn)
+    ///
+    /// Approves the router for the input token first when its allowance is too low.
     pub async fn v2_swap_exact_tokens_for_tokens(
         &self,
         router_address: Address,
@@ -633,6 +678,8 @@ impl UniswapService {
         deadline: U256,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
+        self.ensure_router_allowance(router_address, &path, amount_in)
+            .await?;
         let router = self.v2_router(router_address);
         let tx = router.swap_exact_tokens_for_tokens(amount_in, amount_out_min, path, to, deadline);
         let pending_tx = tx
@@ -664,6 +711,8 @@ impl UniswapService {
     }
 
     /// V2 - Token to ETH swap
+    ///
+    /// Approves the router for the input token first when its allowance is too low.
     pub async fn v2_swap_tokens_for_eth(
         &self,
         router_address: Address,
@@ -674,6 +723,8 @@ impl UniswapService {
         deadline: U256,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
+        self.ensure_router_allowance(router_address, &path, amount_in)
+            .await?;
         let router = self.v2_router(router_address);
         let tx = router.swap_exact_tokens_for_eth(amount_in, amount_out_min, path, to, deadline);
         let pending_tx = tx.send().await.map_err(|e| {
@@ -689,12 +740,288 @@ impl UniswapService {
         amount_in: U256,
         path: Vec<Address>,
     ) -> Result<Vec<U256>, EvmError> {
-        let router = self.v2_router(router_address);
-        router
-            .get_amounts_out(amount_in, path)
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v2_router(router_address)
+                    .get_amounts_out(amount_in, path.clone())
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get amounts out", &e))
+            })
+            .await
+    }
+
+    /// V2 - Impermanent loss of a position versus holding, in percent
+    ///
+    /// `entry_price_ratio` is the pool price when the position was opened, expressed
+    /// like the current price: `reserve1 / reserve0` in raw token units. The result
+    /// is how much less the position is worth than simply holding the deposited
+    /// tokens, e.g. `5.72` when the price has moved 2x in either direction.
+    ///
+    /// # Example
+    /// ```
+    /// let loss = uniswap.v2_impermanent_loss(pair, entry_ratio).await?;
+    /// println!("Impermanent loss: {:.2}%", loss);
+    /// ```
+    pub async fn v2_impermanent_loss(
+        &self,
+        pair_address: Address,
+        entry_price_ratio: f64,
+    ) -> Result<f64, EvmError> {
+        if !(entry_price_ratio > 0.0 && entry_price_ratio.is_finite()) {
+            return Err(EvmError::InvalidInput(
+                "Entry price ratio must be a positive number".to_string(),
+            ));
+        }
+        let (reserve0, reserve1, _) = self.v2_get_reserves(pair_address).await?;
+        if reserve0 == 0 || reserve1 == 0 {
+            return Err(EvmError::CalculationError(
+                "Pair has no liquidity".to_string(),
+            ));
+        }
+        let current_price_ratio = reserve1 as f64 / reserve0 as f64;
+        Ok(Self::impermanent_loss(
+            current_price_ratio / entry_price_ratio,
+        ))
+    }
+
+    /// Impermanent loss in percent for a relative price change `price_change`
+    /// (current price / entry price), using `1 - 2 * sqrt(r) / (1 + r)`.
+    pub fn impermanent_loss(price_change: f64) -> f64 {
+        (1.0 - 2.0 * price_change.sqrt() / (1.0 + price_change)) * 100.0
+    }
+
+    /// V2 - Underlying token amounts (token0, token1) currently backing `lp_balance` LP tokens
+    pub async fn v2_position_amounts(
+        &self,
+        pair_address: Address,
+        lp_balance: U256,
+    ) -> Result<(U256, U256), EvmError> {
+        let total_supply = self
+            .evm
+            .read_with_retry(|| async {
+                self.v2_pair(pair_address)
+                    .total_supply()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get total supply", &e))
+            })
+            .await?;
+        if total_supply.is_zero() {
+            return Ok((U256::zero(), U256::zero()));
+        }
+        let (reserve0, reserve1, _) = self.v2_get_reserves(pair_address).await?;
+        let amount0 = lp_balance
+            .checked_mul(U256::from(reserve0))
+            .ok_or_else(|| EvmError::CalculationError("Position amount overflow".to_string()))?
+            / total_supply;
+        let amount1 = lp_balance
+            .checked_mul(U256::from(reserve1))
+            .ok_or_else(|| EvmError::CalculationError("Position amount overflow".to_string()))?
+            / total_supply;
+        Ok((amount0, amount1))
+    }
+
+    /// V2 - Volume, swap count and LP fee revenue of a pair over roughly the last 24 hours
+    ///
+    /// The window is a block range, not a time range: its length is 24h divided by the
+    /// chain's block time, averaged over the last `BLOCK_TIME_SAMPLE` blocks, or taken
+    /// from `global::get_block_time_by_address` for the pair's tokens when the chain is
+    /// too young to measure it. Irregular block times or missed slots make it a little
+    /// shorter or longer than 24h. Volume counts both what went in and what came out of
+    /// the pair, like the Uniswap subgraph; fee revenue is the 0.3% Uniswap V2 fee on
+    /// the input amounts, so it overstates forks with a lower fee (PancakeSwap V2
+    /// charges 0.25%).
+    ///
+    /// # Example
+    /// ```
+    /// let stats = uniswap.v2_pool_stats_24h(pair).await?;
+    /// println!("{} swaps, fees {:?}", stats.swap_count, stats.fee_revenue);
+    /// ```
+    pub async fn v2_pool_stats_24h(&self, pair_address: Address) -> Result<PoolStats, EvmError> {
+        const DAY_SECONDS: u64 = 24 * 60 * 60;
+        const BLOCK_TIME_SAMPLE: u64 = 1_000;
+        const LOG_CHUNK_BLOCKS: u64 = 5_000;
+        let token0 = self.v2_token0(pair_address).await?;
+        let token1 = self.v2_token1(pair_address).await?;
+        let head = self.evm.get_block_number().await?;
+        let start = head.saturating_sub(BLOCK_TIME_SAMPLE);
+        let timestamps = crate::block::BlockService::new(self.evm.clone())
+            .get_timestamps(vec![start, head])
+            .await?;
+        let block_time = match (timestamps.get(&start), timestamps.get(&head)) {
+            (Some(first), Some(last)) if head > start && last > first => {
+                (last - first) as f64 / (head - start) as f64
+            }
+            _ => crate::global::get_block_time_by_address(&format!("{:?}", token0))
+                .or_else(|| crate::global::get_block_time_by_address(&format!("{:?}", token1)))
+                .map(|seconds| seconds as f64)
+                .ok_or_else(|| {
+                    EvmError::RpcError("Failed to estimate the block time".to_string())
+                })?,
+        };
+        let window = (DAY_SECONDS as f64 / block_time).ceil() as u64;
+        let from_block = head.saturating_sub(window.saturating_sub(1));
+        let mut stats = PoolStats {
+            pair: pair_address,
+            from_block,
+            to_block: head,
+            ..Default::default()
+        };
+        let mut chunk_start = from_block;
+        while chunk_start <= head {
+            let chunk_end = head.min(chunk_start + LOG_CHUNK_BLOCKS - 1);
+            let filter = Filter::new()
+                .address(pair_address)
+                .topic0(H256::from(crate::global::dex_events::uniswap_v2_swap()))
+                .from_block(chunk_start)
+                .to_block(chunk_end);
+            let logs = self
+                .evm
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
+                        .get_logs(&filter)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get swap logs", e))
+                })
+                .await?;
+            for swap in logs
+                .iter()
+                .filter_map(|log| V2SwapEvent::from_log(log).ok())
+            {
+                stats.add_swap(&swap);
+            }
+            chunk_start = chunk_end + 1;
+        }
+        Ok(stats)
+    }
+
+    /// Price impact in percent of swapping `amount_in` of `token_in` against a V2 pair or V3 pool
+    ///
+    /// The execution price of the trade is compared to the pool's spot price, excluding the
+    /// LP fee. V2 pairs use the constant-product formula on the current reserves. V3 pools
+    /// are quoted against the liquidity active at the current tick, which is exact as long
+    /// as the swap does not cross an initialized tick; larger trades get an approximation.
+    ///
+    /// # Example
+    /// ```
+    /// let impact = uniswap.price_impact(pool, weth, U256::exp10(18) * 100).await?;
+    /// println!("Price impact: {:.2}%", impact);
+    /// ```
+    pub async fn price_impact(
+        &self,
+        pool_address: Address,
+        token_in: Address,
+        amount_in: U256,
+    ) -> Result<f64, EvmError> {
+        if amount_in.is_zero() {
+            return Ok(0.0);
+        }
+        if let Ok((reserve0, reserve1, _)) = self.v2_get_reserves(pool_address).await {
+            let token0 = self.v2_token0(pool_address).await?;
+            let (reserve_in, reserve_out) = if token_in == token0 {
+                (reserve0, reserve1)
+            } else if token_in == self.v2_token1(pool_address).await? {
+                (reserve1, reserve0)
+            } else {
+                return Err(EvmError::InvalidInput(
+                    "Token is not part of the pair".to_string(),
+                ));
+            };
+            return Self::v2_price_impact(reserve_in, reserve_out, u256_as_f64(amount_in));
+        }
+
+        let slot0 = self
+            .evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .slot_0()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get slot0", &e))
+            })
+            .await?;
+        let liquidity = self.v3_get_liquidity(pool_address).await?;
+        if liquidity == 0 || slot0.0.is_zero() {
+            return Err(EvmError::CalculationError(
+                "Pool has no active liquidity".to_string(),
+            ));
+        }
+        let zero_for_one = if token_in == self.v3_get_token0(pool_address).await? {
+            true
+        } else if token_in == self.v3_get_token1(pool_address).await? {
+            false
+        } else {
+            return Err(EvmError::InvalidInput(
+                "Token is not part of the pool".to_string(),
+            ));
+        };
+        let sqrt_price = u256_as_f64(slot0.0) / 2f64.powi(96);
+        Ok(Self::v3_price_impact(
+            sqrt_price,
+            liquidity as f64,
+            u256_as_f64(amount_in),
+            zero_for_one,
+        ))
+    }
+
+    async fn v2_token0(&self, pair_address: Address) -> Result<Address, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.v2_pair(pair_address)
+                    .token_0()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get token0", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get amounts out: {}", e)))
+    }
+
+    async fn v2_token1(&self, pair_address: Address) -> Result<Address, EvmError> {
+        self.evm
+            .read_with_retry(|| async {
+                self.v2_pair(pair_address)
+                    .token_1()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get token1", &e))
+            })
+            .await
+    }
+
+    /// Constant-product price impact in percent, fee excluded
+    pub fn v2_price_impact(
+        reserve_in: u128,
+        reserve_out: u128,
+        amount_in: f64,
+    ) -> Result<f64, EvmError> {
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(EvmError::CalculationError(
+                "Pair has no liquidity".to_string(),
+            ));
+        }
+        let (reserve_in, reserve_out) = (reserve_in as f64, reserve_out as f64);
+        let amount_out = amount_in * reserve_out / (reserve_in + amount_in);
+        let spot_price = reserve_out / reserve_in;
+        let execution_price = amount_out / amount_in;
+        Ok((1.0 - execution_price / spot_price) * 100.0)
+    }
+
+    /// Single-range V3 price impact in percent, fee excluded.
+    /// `sqrt_price` is `sqrtPriceX96 / 2^96`.
+    fn v3_price_impact(sqrt_price: f64, liquidity: f64, amount_in: f64, zero_for_one: bool) -> f64 {
+        let (amount_out, spot_price) = if zero_for_one {
+            let next = liquidity * sqrt_price / (liquidity + amount_in * sqrt_price);
+            (liquidity * (sqrt_price - next), sqrt_price * sqrt_price)
+        } else {
+            let next = sqrt_price + amount_in / liquidity;
+            (
+                liquidity * (1.0 / sqrt_price - 1.0 / next),
+                1.0 / (sqrt_price * sqrt_price),
+            )
+        };
+        (1.0 - amount_out / amount_in / spot_price) * 100.0
     }
 
     // ==================== V3 Methods ====================
@@ -704,12 +1031,12 @@ impl UniswapService {
         &self,
         factory_address: Address,
     ) -> IUniswapV3Factory<Provider<ethers::providers::Http>> {
-        IUniswapV3Factory::new(factory_address, self.evm.client.provider.clone())
+        IUniswapV3Factory::new(factory_address, self.evm.provider().clone())
     }
 
     /// Create V3 Pool instance
     fn v3_pool(&self, pool_address: Address) -> IUniswapV3Pool<Provider<ethers::providers::Http>> {
-        IUniswapV3Pool::new(pool_address, self.evm.client.provider.clone())
+        IUniswapV3Pool::new(pool_address, self.evm.provider().clone())
     }
 
     /// Create V3 Router instance
@@ -717,7 +1044,7 @@ impl UniswapService {
         &self,
         router_address: Address,
     ) -> IUniswapV3Router<Provider<ethers::providers::Http>> {
-        IUniswapV3Router::new(router_address, self.evm.client.provider.clone())
+        IUniswapV3Router::new(router_address, self.evm.provider().clone())
     }
 
     /// Create V3 Positions instance
@@ -725,7 +1052,7 @@ impl UniswapService {
         &self,
         positions_address: Address,
     ) -> IUniswapV3Positions<Provider<ethers::providers::Http>> {
-        IUniswapV3Positions::new(positions_address, self.evm.client.provider.clone())
+        IUniswapV3Positions::new(positions_address, self.evm.provider().clone())
     }
 
     /// V3 - Get Pool address
@@ -736,12 +1063,15 @@ impl UniswapService {
         token_b: Address,
         fee: u32,
     ) -> Result<Address, EvmError> {
-        let factory = self.v3_factory(factory_address);
-        factory
-            .get_pool(token_a, token_b, fee)
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v3_factory(factory_address)
+                    .get_pool(token_a, token_b, fee)
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get pool", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get pool: {}", e)))
     }
 
     /// V3 - Create Pool
@@ -767,12 +1097,16 @@ impl UniswapService {
         &self,
         pool_address: Address,
     ) -> Result<(H160, i32, u16, u16, u16, u8, bool), EvmError> {
-        let pool = self.v3_pool(pool_address);
-        let slot0 = pool
-            .slot_0()
-            .call()
-            .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get slot0: {}", e)))?;
+        let slot0 = self
+            .evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .slot_0()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get slot0", &e))
+            })
+            .await?;
 
         // Convert U256 to H160 (take lower 160 bits)
         let sqrt_price_x96 = {
@@ -794,47 +1128,67 @@ impl UniswapService {
 
     /// V3 - Get liquidity
     pub async fn v3_get_liquidity(&self, pool_address: Address) -> Result<u128, EvmError> {
-        let pool = self.v3_pool(pool_address);
-        pool.liquidity()
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .liquidity()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get liquidity", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get liquidity: {}", e)))
     }
 
     /// V3 - Get token0 address
     pub async fn v3_get_token0(&self, pool_address: Address) -> Result<Address, EvmError> {
-        let pool = self.v3_pool(pool_address);
-        pool.token_0()
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .token_0()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get token0", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get token0: {}", e)))
     }
 
     /// V3 - Get token1 address
     pub async fn v3_get_token1(&self, pool_address: Address) -> Result<Address, EvmError> {
-        let pool = self.v3_pool(pool_address);
-        pool.token_1()
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .token_1()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get token1", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get token1: {}", e)))
     }
 
     /// V3 - Get fee
     pub async fn v3_get_fee(&self, pool_address: Address) -> Result<u32, EvmError> {
-        let pool = self.v3_pool(pool_address);
-        pool.fee()
-            .call()
+        self.evm
+            .read_with_retry(|| async {
+                self.v3_pool(pool_address)
+                    .fee()
+                    .call()
+                    .await
+                    .map_err(|e| EvmError::contract("Failed to get fee", &e))
+            })
             .await
-            .map_err(|e| EvmError::ContractError(format!("Failed to get fee: {}", e)))
     }
 
     /// V3 - Single pool exact input swap using Router
+    ///
+    /// Approves the router for the input token first when its allowance is too low.
     pub async fn v3_exact_input_single(
         &self,
         router_address: Address,
         params: ExactInputSingleParams,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
+        self.ensure_router_allowance(router_address, &[params.token_in], params.amount_in)
+            .await?;
 
         let router = self.v3_router(router_address);
 
@@ -865,12 +1219,17 @@ impl UniswapService {
     }
 
     /// V3 - Single pool exact output swap using Router
+    ///
+    /// Approves the router for `amount_in_maximum` of the input token first when its
+    /// allowance is too low.
     pub async fn v3_exact_output_single(
         &self,
         router_address: Address,
         params: ExactOutputSingleParams,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
+        self.ensure_router_allowance(router_address, &[params.token_in], params.amount_in_maximum)
+            .await?;
 
         let router = self.v3_router(router_address);
 
@@ -901,6 +1260,9 @@ impl UniswapService {
     }
 
     /// V3 - Multi-hop exact input swap
+    ///
+    /// `path` is the packed `token, fee, token, ...` encoding of the router; the router
+    /// is approved for the first token first when its allowance is too low.
     pub async fn v3_exact_input(
         &self,
         router_address: Address,
@@ -911,6 +1273,14 @@ impl UniswapService {
         amount_out_minimum: U256,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
+        if path.len() < 20 {
+            return Err(EvmError::InvalidInput(
+                "Swap path is shorter than one address".to_string(),
+            ));
+        }
+        let token_in = Address::from_slice(&path[..20]);
+        self.ensure_router_allowance(router_address, &[token_in], amount_in)
+            .await?;
 
         let router = self.v3_router(router_address);
         let tx = router.exact_input(
@@ -1024,7 +1394,7 @@ impl UniswapService {
         sqrt_price_x96: H160,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
-        let manager = UniswapV4PoolManagerManual::new(manager_address, &self.evm.client.provider);
+        let manager = UniswapV4PoolManagerManual::new(manager_address, self.evm.provider());
         manager.initialize(pool_key, sqrt_price_x96).await
     }
 
@@ -1037,7 +1407,7 @@ impl UniswapService {
         data: Vec<u8>,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
-        let manager = UniswapV4PoolManagerManual::new(manager_address, &self.evm.client.provider);
+        let manager = UniswapV4PoolManagerManual::new(manager_address, self.evm.provider());
         manager.swap(pool_key, swap_params, data).await
     }
 
@@ -1050,7 +1420,7 @@ impl UniswapService {
         data: Vec<u8>,
     ) -> Result<H256, EvmError> {
         self.check_wallet()?;
-        let manager = UniswapV4PoolManagerManual::new(manager_address, &self.evm.client.provider);
+        let manager = UniswapV4PoolManagerManual::new(manager_address, self.evm.provider());
         manager
             .modify_liquidity(pool_key, liquidity_params, data)
             .await
@@ -1105,6 +1475,155 @@ impl SwapPathBuilder {
     }
 }
 
+// ==================== V2 Offline Math ====================
+
+/// Constant-product (x * y = k) quoting from cached reserves, without RPC calls
+///
+/// `fee_bps` is the pool's swap fee: 30 for Uniswap V2 and SushiSwap, 25 for
+/// PancakeSwap V2. Results match the router's `getAmountsOut` rounding.
+pub struct UniswapV2;
+
+impl UniswapV2 {
+    const BPS: u32 = 10_000;
+
+    /// Output amount for `amount_in`, zero when the pool is empty or the fee is 100% or more
+    ///
+    /// # Example
+    /// ```
+    /// let (reserve_in, reserve_out) = cached_reserves[&pair];
+    /// let amount_out = UniswapV2::get_amount_out(amount_in, reserve_in, reserve_out, 30);
+    /// ```
+    pub fn get_amount_out(
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+        fee_bps: u32,
+    ) -> U256 {
+        if amount_in.is_zero()
+            || reserve_in.is_zero()
+            || reserve_out.is_zero()
+            || fee_bps >= Self::BPS
+        {
+            return U256::zero();
+        }
+        let amount_in_with_fee = amount_in.full_mul(U256::from(Self::BPS - fee_bps));
+        let numerator = amount_in_with_fee * U512::from(reserve_out);
+        let denominator = reserve_in.full_mul(U256::from(Self::BPS)) + amount_in_with_fee;
+        // the quotient is below reserve_out, so it always fits
+        U256::try_from(numerator / denominator).unwrap_or_default()
+    }
+
+    /// Amounts along a multi-hop path, starting with `amount_in`, like `getAmountsOut`
+    ///
+    /// `path_reserves` holds `(reserve_in, reserve_out)` of each hop's pair, oriented
+    /// in the swap direction.
+    ///
+    /// # Example
+    /// ```
+    /// let amounts = UniswapV2::get_amounts_out(amount_in, &[(weth_usdc_in, weth_usdc_out)], 30);
+    /// let amount_out = *amounts.last().unwrap();
+    /// ```
+    pub fn get_amounts_out(
+        amount_in: U256,
+        path_reserves: &[(U256, U256)],
+        fee_bps: u32,
+    ) -> Vec<U256> {
+        let mut amounts = Vec::with_capacity(path_reserves.len() + 1);
+        amounts.push(amount_in);
+        for (reserve_in, reserve_out) in path_reserves {
+            let previous = amounts[amounts.len() - 1];
+            amounts.push(Self::get_amount_out(
+                previous,
+                *reserve_in,
+                *reserve_out,
+                fee_bps,
+            ));
+        }
+        amounts
+    }
+}
+
+/// Lossy conversion of a raw token amount or fixed-point value to `f64`
+fn u256_as_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or(f64::MAX)
+}
+
+// ==================== V2 Events ====================
+
+/// Uniswap V2 pair `Swap(sender indexed, amount0In, amount1In, amount0Out, amount1Out,
+/// to indexed)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V2SwapEvent {
+    pub pair: Address,
+    pub sender: Address,
+    pub to: Address,
+    pub amount0_in: U256,
+    pub amount1_in: U256,
+    pub amount0_out: U256,
+    pub amount1_out: U256,
+}
+
+impl V2SwapEvent {
+    /// Decode a Uniswap V2 (or fork) pair `Swap` log
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        if log.topics.len() != 3
+            || log.topics[0] != H256::from(crate::global::dex_events::uniswap_v2_swap())
+        {
+            return Err("Not a Uniswap V2 Swap log".to_string());
+        }
+        let uint = ParamType::Uint(256);
+        let amounts = decode(&[uint.clone(), uint.clone(), uint.clone(), uint], &log.data)
+            .map_err(|e| format!("Invalid Swap data: {}", e))?;
+        let amount = |index: usize| amounts[index].clone().into_uint().unwrap_or_default();
+        Ok(Self {
+            pair: log.address,
+            sender: Address::from(log.topics[1]),
+            to: Address::from(log.topics[2]),
+            amount0_in: amount(0),
+            amount1_in: amount(1),
+            amount0_out: amount(2),
+            amount1_out: amount(3),
+        })
+    }
+}
+
+/// Activity of a V2 pair over a block range, see `UniswapService::v2_pool_stats_24h`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStats {
+    pub pair: Address,
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Raw token0 amount swapped in and out
+    pub volume_token0: U256,
+    /// Raw token1 amount swapped in and out
+    pub volume_token1: U256,
+    pub swap_count: u64,
+    /// LP fees earned as `(token0, token1)`, in raw token units
+    pub fee_revenue: (U256, U256),
+}
+
+impl PoolStats {
+    /// Uniswap V2 swap fee in basis points.
+    const FEE_BPS: u64 = 30;
+
+    fn add_swap(&mut self, swap: &V2SwapEvent) {
+        // Amounts come straight from logs, so multiply in 512 bits; the fee is below `amount`
+        let fee = |amount: U256| {
+            U256::try_from(amount.full_mul(U256::from(Self::FEE_BPS)) / U512::from(10_000))
+                .unwrap_or(U256::MAX)
+        };
+        self.volume_token0 = self
+            .volume_token0
+            .saturating_add(swap.amount0_in.saturating_add(swap.amount0_out));
+        self.volume_token1 = self
+            .volume_token1
+            .saturating_add(swap.amount1_in.saturating_add(swap.amount1_out));
+        self.fee_revenue.0 = self.fee_revenue.0.saturating_add(fee(swap.amount0_in));
+        self.fee_revenue.1 = self.fee_revenue.1.saturating_add(fee(swap.amount1_in));
+        self.swap_count += 1;
+    }
+}
+
 // ==================== Fee Tiers ====================
 
 /// Common fee tiers for V3
@@ -1121,6 +1640,243 @@ impl FeeTier {
     }
 }
 
+// ==================== Router Calldata Decoder ====================
+
+/// A Uniswap router call decoded from transaction input
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouterCall {
+    /// V2 swap of an exact input amount. `amount_in` is `None` for
+    /// `swapExactETHForTokens`, where the input is the transaction value.
+    V2SwapExactIn {
+        function: String,
+        amount_in: Option<U256>,
+        amount_out_min: U256,
+        path: Vec<Address>,
+        recipient: Address,
+        deadline: U256,
+    },
+    /// V2 swap for an exact output amount. `amount_in_max` is `None` for
+    /// `swapETHForExactTokens`, where the maximum is the transaction value.
+    V2SwapExactOut {
+        function: String,
+        amount_out: U256,
+        amount_in_max: Option<U256>,
+        path: Vec<Address>,
+        recipient: Address,
+        deadline: U256,
+    },
+    /// V2 `addLiquidity` / `addLiquidityETH`; `token_b` is `None` for the ETH variant
+    V2AddLiquidity {
+        token_a: Address,
+        token_b: Option<Address>,
+        amount_a_desired: U256,
+        amount_b_desired: Option<U256>,
+        amount_a_min: U256,
+        amount_b_min: U256,
+        recipient: Address,
+        deadline: U256,
+    },
+    /// V2 `removeLiquidity` / `removeLiquidityETH`; `token_b` is `None` for the ETH variant
+    V2RemoveLiquidity {
+        token_a: Address,
+        token_b: Option<Address>,
+        liquidity: U256,
+        amount_a_min: U256,
+        amount_b_min: U256,
+        recipient: Address,
+        deadline: U256,
+    },
+    /// V3 `exactInputSingle`
+    V3ExactInputSingle {
+        token_in: Address,
+        token_out: Address,
+        fee: u32,
+        recipient: Address,
+        deadline: U256,
+        amount_in: U256,
+        amount_out_minimum: U256,
+        sqrt_price_limit_x96: U256,
+    },
+    /// V3 `exactInput`, with the encoded path split into tokens and fees
+    V3ExactInput {
+        tokens: Vec<Address>,
+        fees: Vec<u32>,
+        recipient: Address,
+        deadline: U256,
+        amount_in: U256,
+        amount_out_minimum: U256,
+    },
+}
+
+impl RouterCall {
+    /// Minimum output the sender accepted, i.e. the slippage bound of a swap
+    pub fn amount_out_min(&self) -> Option<U256> {
+        match self {
+            RouterCall::V2SwapExactIn { amount_out_min, .. } => Some(*amount_out_min),
+            RouterCall::V3ExactInputSingle {
+                amount_out_minimum, ..
+            }
+            | RouterCall::V3ExactInput {
+                amount_out_minimum, ..
+            } => Some(*amount_out_minimum),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes Uniswap V2 router and V3 `SwapRouter` calldata by function selector
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouterDecoder;
+
+impl RouterDecoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Decode router calldata, returning `None` for unrecognized selectors or
+    /// arguments that do not match the expected signature
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(call) = RouterDecoder::new().decode(&tx_info.input) {
+    ///     println!("{:?}, min out {:?}", call, call.amount_out_min());
+    /// }
+    /// ```
+    pub fn decode(&self, input: &[u8]) -> Option<RouterCall> {
+        let info = tool::decode_calldata(input, None);
+        let function = info.function_name?;
+        let args = info.args?;
+        let uint = |i: usize| args.get(i)?.clone().into_uint();
+        let address = |i: usize| args.get(i)?.clone().into_address();
+        let path = |i: usize| -> Option<Vec<Address>> {
+            args.get(i)?
+                .clone()
+                .into_array()?
+                .into_iter()
+                .map(|token| token.into_address())
+                .collect()
+        };
+        let call = match function.as_str() {
+            "swapExactTokensForTokens" | "swapExactTokensForETH" => RouterCall::V2SwapExactIn {
+                amount_in: Some(uint(0)?),
+                amount_out_min: uint(1)?,
+                path: path(2)?,
+                recipient: address(3)?,
+                deadline: uint(4)?,
+                function,
+            },
+            "swapExactETHForTokens" => RouterCall::V2SwapExactIn {
+                amount_in: None,
+                amount_out_min: uint(0)?,
+                path: path(1)?,
+                recipient: address(2)?,
+                deadline: uint(3)?,
+                function,
+            },
+            "swapTokensForExactTokens" | "swapTokensForExactETH" => RouterCall::V2SwapExactOut {
+                amount_out: uint(0)?,
+                amount_in_max: Some(uint(1)?),
+                path: path(2)?,
+                recipient: address(3)?,
+                deadline: uint(4)?,
+                function,
+            },
+            "swapETHForExactTokens" => RouterCall::V2SwapExactOut {
+                amount_out: uint(0)?,
+                amount_in_max: None,
+                path: path(1)?,
+                recipient: address(2)?,
+                deadline: uint(3)?,
+                function,
+            },
+            "addLiquidity" => RouterCall::V2AddLiquidity {
+                token_a: address(0)?,
+                token_b: Some(address(1)?),
+                amount_a_desired: uint(2)?,
+                amount_b_desired: Some(uint(3)?),
+                amount_a_min: uint(4)?,
+                amount_b_min: uint(5)?,
+                recipient: address(6)?,
+                deadline: uint(7)?,
+            },
+            "addLiquidityETH" => RouterCall::V2AddLiquidity {
+                token_a: address(0)?,
+                token_b: None,
+                amount_a_desired: uint(1)?,
+                amount_b_desired: None,
+                amount_a_min: uint(2)?,
+                amount_b_min: uint(3)?,
+                recipient: address(4)?,
+                deadline: uint(5)?,
+            },
+            "removeLiquidity" => RouterCall::V2RemoveLiquidity {
+                token_a: address(0)?,
+                token_b: Some(address(1)?),
+                liquidity: uint(2)?,
+                amount_a_min: uint(3)?,
+                amount_b_min: uint(4)?,
+                recipient: address(5)?,
+                deadline: uint(6)?,
+            },
+            "removeLiquidityETH" => RouterCall::V2RemoveLiquidity {
+                token_a: address(0)?,
+                token_b: None,
+                liquidity: uint(1)?,
+                amount_a_min: uint(2)?,
+                amount_b_min: uint(3)?,
+                recipient: address(4)?,
+                deadline: uint(5)?,
+            },
+            "exactInputSingle" => {
+                let params = args.first()?.clone().into_tuple()?;
+                let uint = |i: usize| params.get(i)?.clone().into_uint();
+                let address = |i: usize| params.get(i)?.clone().into_address();
+                RouterCall::V3ExactInputSingle {
+                    token_in: address(0)?,
+                    token_out: address(1)?,
+                    fee: uint(2)?.low_u32(),
+                    recipient: address(3)?,
+                    deadline: uint(4)?,
+                    amount_in: uint(5)?,
+                    amount_out_minimum: uint(6)?,
+                    sqrt_price_limit_x96: uint(7)?,
+                }
+            }
+            "exactInput" => {
+                let params = args.first()?.clone().into_tuple()?;
+                let (tokens, fees) = Self::split_v3_path(&params.first()?.clone().into_bytes()?)?;
+                let uint = |i: usize| params.get(i)?.clone().into_uint();
+                RouterCall::V3ExactInput {
+                    tokens,
+                    fees,
+                    recipient: params.get(1)?.clone().into_address()?,
+                    deadline: uint(2)?,
+                    amount_in: uint(3)?,
+                    amount_out_minimum: uint(4)?,
+                }
+            }
+            _ => return None,
+        };
+        Some(call)
+    }
+
+    /// Split a V3 encoded path (`token (20 bytes) | fee (3 bytes) | token | ...`)
+    fn split_v3_path(path: &[u8]) -> Option<(Vec<Address>, Vec<u32>)> {
+        const ADDRESS_LEN: usize = 20;
+        const HOP_LEN: usize = 23;
+        if path.len() < ADDRESS_LEN || (path.len() - ADDRESS_LEN) % HOP_LEN != 0 {
+            return None;
+        }
+        let mut tokens = vec![Address::from_slice(&path[..ADDRESS_LEN])];
+        let mut fees = Vec::new();
+        for hop in path[ADDRESS_LEN..].chunks(HOP_LEN) {
+            fees.push(u32::from_be_bytes([0, hop[0], hop[1], hop[2]]));
+            tokens.push(Address::from_slice(&hop[3..]));
+        }
+        Some((tokens, fees))
+    }
+}
+
 // ==================== Type Aliases ====================
 
 pub type U128 = u128;
@@ -1230,6 +1986,41 @@ mod tests {
     const MOCK_TOKEN_B: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
     const MOCK_RECIPIENT: &str = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
 
+    #[test]
+    fn test_pool_stats_add_swap() {
+        use ethers::abi::{Token, encode};
+        let sender = H256::from(Address::repeat_byte(0x11));
+        let log = Log {
+            topics: vec![
+                H256::from(crate::global::dex_events::uniswap_v2_swap()),
+                sender,
+                sender,
+            ],
+            data: encode(&[
+                Token::Uint(U256::from(10_000)),
+                Token::Uint(U256::zero()),
+                Token::Uint(U256::zero()),
+                Token::Uint(U256::from(500)),
+            ])
+            .into(),
+            ..Default::default()
+        };
+        let swap = V2SwapEvent::from_log(&log).unwrap();
+        let mut stats = PoolStats::default();
+        stats.add_swap(&swap);
+        stats.add_swap(&swap);
+        assert_eq!(stats.swap_count, 2);
+        assert_eq!(stats.volume_token0, U256::from(20_000));
+        assert_eq!(stats.volume_token1, U256::from(1_000));
+        assert_eq!(stats.fee_revenue, (U256::from(60), U256::zero()));
+        let mut stats = PoolStats::default();
+        stats.add_swap(&V2SwapEvent {
+            amount0_in: U256::MAX,
+            ..swap
+        });
+        assert_eq!(stats.fee_revenue.0, U256::MAX / 10_000 * 30 + 29);
+    }
+
     ///  Get Pair Address
     #[tokio::test]
     async fn test_v2_get_pair() {
@@ -1349,6 +2140,114 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_impermanent_loss() {
+        assert!(UniswapService::impermanent_loss(1.0).abs() < 1e-9);
+        let doubled = UniswapService::impermanent_loss(2.0);
+        let halved = UniswapService::impermanent_loss(0.5);
+        assert!((doubled - 5.719).abs() < 1e-3);
+        assert!((doubled - halved).abs() < 1e-9);
+        assert!((UniswapService::impermanent_loss(4.0) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_v2_price_impact() {
+        let impact = UniswapService::v2_price_impact(1_000_000, 2_000_000, 10_000.0).unwrap();
+        assert!((impact - 0.990099).abs() < 1e-6);
+        let tiny = UniswapService::v2_price_impact(1_000_000, 2_000_000, 1.0).unwrap();
+        assert!(tiny < 1e-3);
+        assert!(matches!(
+            UniswapService::v2_price_impact(0, 2_000_000, 1.0),
+            Err(EvmError::CalculationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_uniswap_v2_get_amounts_out() {
+        let eth = U256::exp10(18);
+        let usdc = U256::exp10(6);
+        let (weth_reserve, usdc_reserve) = (eth * 100, usdc * 200_000);
+        // 997/1000 router math
+        assert_eq!(
+            UniswapV2::get_amount_out(eth, weth_reserve, usdc_reserve, 30),
+            U256::from(1_974_316_068u64)
+        );
+        assert_eq!(
+            UniswapV2::get_amount_out(eth, weth_reserve, usdc_reserve, 25),
+            U256::from(1_975_296_418u64)
+        );
+        assert!(UniswapV2::get_amount_out(eth, U256::zero(), usdc_reserve, 30).is_zero());
+
+        let amounts = UniswapV2::get_amounts_out(
+            eth,
+            &[(weth_reserve, usdc_reserve), (usdc * 5_000, eth * 3)],
+            30,
+        );
+        assert_eq!(
+            amounts,
+            vec![
+                eth,
+                U256::from(1_974_316_068u64),
+                U256::from(847_423_395_590_642_907u64)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_router_decoder() {
+        use ethers::abi::{Token, encode};
+
+        let decoder = RouterDecoder::new();
+        let token_a = Address::from_str(MOCK_TOKEN_A).unwrap();
+        let token_b = Address::from_str(MOCK_TOKEN_B).unwrap();
+        let recipient = Address::repeat_byte(0x11);
+
+        let mut input =
+            utils::id("swapExactTokensForTokens(uint256,uint256,address[],address,uint256)")
+                .to_vec();
+        input.extend(encode(&[
+            Token::Uint(U256::from(1000)),
+            Token::Uint(U256::from(990)),
+            Token::Array(vec![Token::Address(token_a), Token::Address(token_b)]),
+            Token::Address(recipient),
+            Token::Uint(U256::from(1_700_000_000u64)),
+        ]));
+        let call = decoder.decode(&input).unwrap();
+        assert_eq!(call.amount_out_min(), Some(U256::from(990)));
+        assert_eq!(
+            call,
+            RouterCall::V2SwapExactIn {
+                function: "swapExactTokensForTokens".to_string(),
+                amount_in: Some(U256::from(1000)),
+                amount_out_min: U256::from(990),
+                path: vec![token_a, token_b],
+                recipient,
+                deadline: U256::from(1_700_000_000u64),
+            }
+        );
+
+        let mut path = token_a.as_bytes().to_vec();
+        path.extend_from_slice(&3000u32.to_be_bytes()[1..]);
+        path.extend_from_slice(token_b.as_bytes());
+        let mut input = utils::id("exactInput((bytes,address,uint256,uint256,uint256))").to_vec();
+        input.extend(encode(&[Token::Tuple(vec![
+            Token::Bytes(path),
+            Token::Address(recipient),
+            Token::Uint(U256::from(1_700_000_000u64)),
+            Token::Uint(U256::from(5000)),
+            Token::Uint(U256::from(4900)),
+        ])]));
+        match decoder.decode(&input).unwrap() {
+            RouterCall::V3ExactInput { tokens, fees, .. } => {
+                assert_eq!(tokens, vec![token_a, token_b]);
+                assert_eq!(fees, vec![3000]);
+            }
+            other => panic!("unexpected call {:?}", other),
+        }
+
+        assert!(decoder.decode(&[0xde, 0xad, 0xbe, 0xef]).is_none());
+    }
+
     /// Build Swap Path and Get Amounts
     #[test]
     fn test_v3_build_path_and_simulation() {
@@ -1,6 +1,7 @@
 use crate::{Evm, EvmError};
 use ethers::providers::{Http, Middleware};
-use ethers::types::{Address, U256};
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::id;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -78,6 +79,34 @@ pub enum RiskLevel {
     Critical,
 }
 
+/// EIP-1967 storage slot of the implementation, `keccak256("eip1967.proxy.implementation") - 1`
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// EIP-1967 storage slot of the proxy admin, `keccak256("eip1967.proxy.admin") - 1`
+const EIP1967_ADMIN_SLOT: &str =
+    "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103";
+
+/// EIP-1967 storage slot of the beacon, `keccak256("eip1967.proxy.beacon") - 1`
+const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+
+/// Who controls a contract and whether they can upgrade or pause it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlSurface {
+    /// Result of `owner()`; `None` when there is no such function or ownership was
+    /// renounced to the zero address
+    pub owner: Option<Address>,
+    /// Admin stored in the EIP-1967 admin slot
+    pub proxy_admin: Option<Address>,
+    /// Logic contract from the EIP-1967 implementation slot, or from the beacon
+    pub implementation: Option<Address>,
+    /// Result of `paused()`; `None` when the contract is not pausable
+    pub is_paused: Option<bool>,
+    /// The contract is an EIP-1967 proxy whose logic can be replaced
+    pub is_upgradeable: bool,
+}
+
 /// Security checker for smart contract analysis
 pub struct SecurityChecker {
     evm: Arc<Evm>,
@@ -195,10 +224,92 @@ impl SecurityChecker {
         Ok(metrics)
     }
 
+    /// Read who controls a contract in one call: its owner, EIP-1967 proxy admin and
+    /// implementation, and its pause state
+    ///
+    /// `owner()` and `paused()` are probed with plain calls, so a contract without them
+    /// simply reports `None`. Beacon proxies report the implementation their beacon
+    /// currently points to.
+    ///
+    /// # Example
+    /// ```
+    /// let surface = checker.get_control_surface(token).await?;
+    /// if surface.is_upgradeable {
+    ///     println!("Upgradeable by {:?}", surface.proxy_admin);
+    /// }
+    /// ```
+    pub async fn get_control_surface(&self, contract: Address) -> Result<ControlSurface, EvmError> {
+        let proxy_admin = self.slot_address(contract, EIP1967_ADMIN_SLOT).await?;
+        let mut implementation = self
+            .slot_address(contract, EIP1967_IMPLEMENTATION_SLOT)
+            .await?;
+        if implementation.is_none() {
+            if let Some(beacon) = self.slot_address(contract, EIP1967_BEACON_SLOT).await? {
+                implementation = self
+                    .probe(beacon, "implementation()")
+                    .await?
+                    .and_then(|data| word_to_address(&data));
+            }
+        }
+        let owner = self
+            .probe(contract, "owner()")
+            .await?
+            .and_then(|data| word_to_address(&data));
+        let is_paused = self
+            .probe(contract, "paused()")
+            .await?
+            .and_then(|data| word_to_bool(&data));
+        Ok(ControlSurface {
+            owner,
+            proxy_admin,
+            implementation,
+            is_paused,
+            is_upgradeable: implementation.is_some(),
+        })
+    }
+
+    /// Non-zero address stored in the low 20 bytes of a storage slot
+    async fn slot_address(
+        &self,
+        contract: Address,
+        slot: &str,
+    ) -> Result<Option<Address>, EvmError> {
+        let slot: H256 = slot
+            .parse()
+            .map_err(|e| EvmError::ConfigError(format!("Invalid storage slot: {}", e)))?;
+        let value = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_storage_at(contract, slot, None)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get storage", e))
+            })
+            .await?;
+        Ok(Some(Address::from(value)).filter(|address| !address.is_zero()))
+    }
+
+    /// Return data of a parameterless view call, `None` if it reverts
+    ///
+    /// Node and network failures are returned as errors, so an unreachable endpoint is
+    /// never mistaken for a missing function.
+    async fn probe(&self, contract: Address, signature: &str) -> Result<Option<Bytes>, EvmError> {
+        match self
+            .evm
+            .call_contract(contract, Bytes::from(id(signature).to_vec()), None)
+            .await
+        {
+            Ok(data) => Ok(Some(data)),
+            Err(EvmError::ContractError(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     async fn check_owner_renounced(&self, address: Address) -> Result<bool, EvmError> {
-        // Try to get owner via Ownable pattern
-        // If owner is address(0), it's renounced
-        Ok(false) // Placeholder - implement actual check
+        // Renounced when nobody can change the contract: no owner and no proxy admin
+        let surface = self.get_control_surface(address).await?;
+        Ok(surface.owner.is_none() && surface.proxy_admin.is_none() && !surface.is_upgradeable)
     }
 
     async fn get_holder_count(&self, address: Address) -> Result<u64, EvmError> {
@@ -520,6 +631,23 @@ impl SecurityChecker {
     }
 }
 
+/// Non-zero address returned as a single ABI word
+fn word_to_address(data: &[u8]) -> Option<Address> {
+    if data.len() != 32 || data[..12].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(Address::from_slice(&data[12..])).filter(|address| !address.is_zero())
+}
+
+/// Boolean returned as a single ABI word
+fn word_to_bool(data: &[u8]) -> Option<bool> {
+    match U256::from_big_endian(data.get(..32)?) {
+        value if value.is_zero() => Some(false),
+        value if value == U256::one() => Some(true),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,6 +655,21 @@ mod tests {
     use std::str::FromStr;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    #[test]
+    fn test_decode_probe_words() {
+        let owner = Address::repeat_byte(0x42);
+        assert_eq!(word_to_address(H256::from(owner).as_bytes()), Some(owner));
+        assert_eq!(word_to_address(&[0u8; 32]), None);
+        assert_eq!(word_to_address(&[0xff; 32]), None);
+        assert_eq!(
+            word_to_bool(H256::from_low_u64_be(1).as_bytes()),
+            Some(true)
+        );
+        assert_eq!(word_to_bool(&[0u8; 32]), Some(false));
+        assert_eq!(word_to_bool(H256::from_low_u64_be(2).as_bytes()), None);
+        assert_eq!(word_to_bool(&[]), None);
+    }
+
     #[tokio::test]
     async fn test_real_contract_security_checks() {
         let evm = Evm::new(crate::EvmType::ETHEREUM_MAINNET).await.unwrap();
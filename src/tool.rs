@@ -1,12 +1,46 @@
 /// address tool module
 pub mod address {
+    use crate::EvmError;
     use ethers::types::Address;
+    use ethers::utils::to_checksum;
     use std::str::FromStr;
 
     /// Convert string to Address
-    pub fn str_to_address(address_str: &str) -> Result<Address, String> {
-        Address::from_str(address_str.trim())
-            .map_err(|e| format!("Invalid Ethereum address: {}", e))
+    ///
+    /// All-lowercase and all-uppercase hex are accepted as unchecksummed input.
+    /// Mixed-case input must carry a valid EIP-55 checksum, since a wrong one
+    /// usually means a mistyped address.
+    pub fn str_to_address(address_str: &str) -> Result<Address, EvmError> {
+        parse_address(address_str, false)
+    }
+
+    /// Convert string to Address, requiring a valid EIP-55 checksum
+    pub fn str_to_address_strict(address_str: &str) -> Result<Address, EvmError> {
+        parse_address(address_str, true)
+    }
+
+    fn parse_address(address_str: &str, strict: bool) -> Result<Address, EvmError> {
+        let trimmed = address_str.trim();
+        let hex = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+        if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(EvmError::InvalidInput(format!(
+                "Invalid address {:?}: expected 40 hex characters",
+                address_str
+            )));
+        }
+        let address = Address::from_str(hex).map_err(|e| {
+            EvmError::InvalidInput(format!("Invalid address {:?}: {}", address_str, e))
+        })?;
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase())
+            && hex.chars().any(|c| c.is_ascii_uppercase());
+        if (strict || mixed_case) && to_checksum(&address, None)[2..] != *hex {
+            return Err(EvmError::InvalidInput(format!(
+                "Invalid address checksum for {:?}, expected {}",
+                address_str,
+                to_checksum(&address, None)
+            )));
+        }
+        Ok(address)
     }
 
     /// Convert Address to checksum format
@@ -65,6 +99,59 @@ pub mod num {
             Err(_) => Err("Number conversion failed".to_string()),
         }
     }
+
+    /// Parse a decimal string into its integer amount with `decimals` decimals, without
+    /// going through `f64`
+    ///
+    /// Like ethers' `parse_units`, digits beyond `decimals` are truncated. Underscores
+    /// are accepted as digit separators; signs and exponents are not.
+    ///
+    /// # Example
+    /// ```
+    /// let wei = parse_units("1.5", 18)?; // 1500000000000000000
+    /// ```
+    pub fn parse_units(value: &str, decimals: u8) -> Result<ethers::types::U256, String> {
+        let value = value.trim().replace('_', "");
+        let (integer, fraction) = value.split_once('.').unwrap_or((value.as_str(), ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(format!("Invalid amount: {:?}", value));
+        }
+        if !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(format!("Invalid amount: {:?}", value));
+        }
+        let fraction = &fraction[..fraction.len().min(decimals as usize)];
+        let digits = format!(
+            "{}{}{}",
+            integer,
+            fraction,
+            "0".repeat(decimals as usize - fraction.len())
+        );
+        let digits = digits.trim_start_matches('0');
+        if digits.is_empty() {
+            return Ok(ethers::types::U256::zero());
+        }
+        ethers::types::U256::from_dec_str(digits)
+            .map_err(|_| format!("Amount {} overflows with {} decimals", value, decimals))
+    }
+
+    /// Format an integer amount with `decimals` decimals as an exact decimal string
+    ///
+    /// All `decimals` fraction digits are kept, as ethers' `format_units` does, so
+    /// `format_units(1500000000000000000.into(), 18)` is `"1.500000000000000000"`.
+    pub fn format_units(value: ethers::types::U256, decimals: u8) -> String {
+        let digits = value.to_string();
+        let width = decimals as usize;
+        let (integer, fraction) = if digits.len() > width {
+            digits.split_at(digits.len() - width)
+        } else {
+            ("0", digits.as_str())
+        };
+        format!("{}.{:0>width$}", integer, fraction, width = width.max(1))
+    }
 }
 
 /// price tool module
@@ -110,3 +197,661 @@ pub mod price {
         }
     }
 }
+
+pub use events::decode_known_log;
+
+/// event log tool module
+pub mod events {
+    use crate::contract::DecodedEvent;
+    use ethers::abi::{Event, HumanReadableParser};
+    use ethers::types::{H256, Log};
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    /// Event signatures recognised without an ABI. Events sharing a topic (ERC20 and
+    /// ERC721 `Transfer`) are told apart by their number of indexed parameters.
+    pub const KNOWN_EVENTS: &[&str] = &[
+        // ERC20 / ERC721 / ERC1155
+        "event Transfer(address indexed from, address indexed to, uint256 value)",
+        "event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)",
+        "event Approval(address indexed owner, address indexed spender, uint256 value)",
+        "event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)",
+        "event ApprovalForAll(address indexed owner, address indexed operator, bool approved)",
+        "event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)",
+        "event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)",
+        // WETH
+        "event Deposit(address indexed dst, uint256 wad)",
+        "event Withdrawal(address indexed src, uint256 wad)",
+        // ERC-4626 vaults
+        "event Deposit(address indexed sender, address indexed owner, uint256 assets, uint256 shares)",
+        "event Withdraw(address indexed sender, address indexed receiver, address indexed owner, uint256 assets, uint256 shares)",
+        // Uniswap V2 / V3
+        "event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to)",
+        "event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick)",
+        "event Sync(uint112 reserve0, uint112 reserve1)",
+        "event Mint(address indexed sender, uint256 amount0, uint256 amount1)",
+        "event Burn(address indexed sender, uint256 amount0, uint256 amount1, address indexed to)",
+        "event PairCreated(address indexed token0, address indexed token1, address pair, uint256 index)",
+        "event PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool)",
+        // Aave V3 pool / Compound V2 cTokens
+        "event Supply(address indexed reserve, address user, address indexed onBehalfOf, uint256 amount, uint16 indexed referralCode)",
+        "event Withdraw(address indexed reserve, address indexed user, address indexed to, uint256 amount)",
+        "event Borrow(address indexed reserve, address user, address indexed onBehalfOf, uint256 amount, uint8 interestRateMode, uint256 borrowRate, uint16 indexed referralCode)",
+        "event Repay(address indexed reserve, address indexed user, address indexed repayer, uint256 amount, bool useATokens)",
+        "event LiquidationCall(address indexed collateralAsset, address indexed debtAsset, address indexed user, uint256 debtToCover, uint256 liquidatedCollateralAmount, address liquidator, bool receiveAToken)",
+        "event Mint(address minter, uint256 mintAmount, uint256 mintTokens)",
+        "event Redeem(address redeemer, uint256 redeemAmount, uint256 redeemTokens)",
+        "event Borrow(address borrower, uint256 borrowAmount, uint256 accountBorrows, uint256 totalBorrows)",
+        // Ownership, access control and proxies
+        "event OwnershipTransferred(address indexed previousOwner, address indexed newOwner)",
+        "event RoleGranted(bytes32 indexed role, address indexed account, address indexed sender)",
+        "event RoleRevoked(bytes32 indexed role, address indexed account, address indexed sender)",
+        "event Paused(address account)",
+        "event Unpaused(address account)",
+        "event Upgraded(address indexed implementation)",
+        "event AdminChanged(address previousAdmin, address newAdmin)",
+        "event BeaconUpgraded(address indexed beacon)",
+    ];
+
+    fn known_events() -> &'static HashMap<H256, Vec<Event>> {
+        static EVENTS: OnceLock<HashMap<H256, Vec<Event>>> = OnceLock::new();
+        EVENTS.get_or_init(|| {
+            let mut events: HashMap<H256, Vec<Event>> = HashMap::new();
+            for event in KNOWN_EVENTS
+                .iter()
+                .filter_map(|signature| HumanReadableParser::parse_event(signature).ok())
+            {
+                events.entry(event.signature()).or_default().push(event);
+            }
+            events
+        })
+    }
+
+    /// Decode a log against `KNOWN_EVENTS`, without the emitting contract's ABI
+    ///
+    /// Returns `None` when `topics[0]` is not a known event or the log does not fit
+    /// any variant of it.
+    ///
+    /// # Example
+    /// ```
+    /// for log in &receipt.logs {
+    ///     if let Some(event) = decode_known_log(log) {
+    ///         println!("{}", event.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn decode_known_log(log: &Log) -> Option<DecodedEvent> {
+        known_events()
+            .get(log.topics.first()?)?
+            .iter()
+            .find_map(|event| DecodedEvent::decode(event, log.clone()))
+    }
+}
+
+pub use calldata::{
+    CalldataInfo, SafeExecCall, SafeOperation, decode_calldata, decode_revert_reason,
+    decode_safe_exec,
+};
+
+/// calldata tool module
+pub mod calldata {
+    use crate::contract::ContractABI;
+    use ethers::abi::{Function, HumanReadableParser, Token};
+    use ethers::types::{Address, Bytes, U256};
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    /// Function signatures recognised without an ABI
+    pub const KNOWN_FUNCTIONS: &[&str] = &[
+        // ERC20 / ERC721
+        "transfer(address,uint256)",
+        "transferFrom(address,address,uint256)",
+        "approve(address,uint256)",
+        "safeTransferFrom(address,address,uint256)",
+        "safeTransferFrom(address,address,uint256,bytes)",
+        "setApprovalForAll(address,bool)",
+        // ERC20 permit (EIP-2612 and DAI)
+        PERMIT_EIP2612,
+        PERMIT_DAI,
+        // WETH
+        "deposit()",
+        "withdraw(uint256)",
+        // ERC-4626 vaults
+        "deposit(uint256,address)",
+        "mint(uint256,address)",
+        "withdraw(uint256,address,address)",
+        "redeem(uint256,address,address)",
+        // Uniswap V2 router
+        "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+        "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+        "swapExactETHForTokens(uint256,address[],address,uint256)",
+        "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+        "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+        "swapETHForExactTokens(uint256,address[],address,uint256)",
+        "addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)",
+        "addLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+        "removeLiquidity(address,address,uint256,uint256,uint256,address,uint256)",
+        "removeLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+        // Uniswap V3 router
+        "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+        "exactOutputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+        "exactInput((bytes,address,uint256,uint256,uint256))",
+        "exactOutput((bytes,address,uint256,uint256,uint256))",
+        "multicall(bytes[])",
+        "multicall(uint256,bytes[])",
+        // Safe (Gnosis Safe)
+        SAFE_EXEC_TRANSACTION,
+    ];
+
+    /// EIP-2612 `permit(owner, spender, value, deadline, v, r, s)`, selector `0xd505accf`
+    pub const PERMIT_EIP2612: &str =
+        "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)";
+
+    /// DAI `permit(holder, spender, nonce, expiry, allowed, v, r, s)`, selector `0x8fcbaf0c`
+    pub const PERMIT_DAI: &str =
+        "permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)";
+
+    /// Safe `execTransaction`, selector `0x6a761202`
+    pub const SAFE_EXEC_TRANSACTION: &str = "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
+
+    /// How a Safe executes its transaction
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SafeOperation {
+        Call,
+        /// Runs `data` in the Safe's own context, able to modify its storage
+        DelegateCall,
+    }
+
+    /// Decoded Safe `execTransaction` call
+    #[derive(Debug, Clone)]
+    pub struct SafeExecCall {
+        /// Target of the inner call
+        pub to: Address,
+        pub value: U256,
+        /// Inner calldata executed by the Safe
+        pub data: Bytes,
+        pub operation: SafeOperation,
+        pub safe_tx_gas: U256,
+        pub base_gas: U256,
+        pub gas_price: U256,
+        /// Token the refund is paid in, zero for the native token
+        pub gas_token: Address,
+        pub refund_receiver: Address,
+        /// Concatenated owner signatures (65 bytes each for ECDSA signatures)
+        pub signatures: Bytes,
+        /// Decoded view of `data`
+        pub inner: CalldataInfo,
+    }
+
+    /// Decode Safe `execTransaction` calldata, including the selector of the inner call
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(exec) = decode_safe_exec(&tx.input) {
+    ///     println!("Safe call to {:?}: {:?}", exec.to, exec.inner.function_name);
+    /// }
+    /// ```
+    pub fn decode_safe_exec(input: &[u8]) -> Option<SafeExecCall> {
+        let function = parse_signature(SAFE_EXEC_TRANSACTION)?;
+        if input.len() < 4 || input[..4] != function.short_signature() {
+            return None;
+        }
+        let mut args = function.decode_input(&input[4..]).ok()?.into_iter();
+        let to = args.next()?.into_address()?;
+        let value = args.next()?.into_uint()?;
+        let data: Bytes = args.next()?.into_bytes()?.into();
+        let operation = match args.next()?.into_uint()?.as_u64() {
+            0 => SafeOperation::Call,
+            1 => SafeOperation::DelegateCall,
+            _ => return None,
+        };
+        let inner = decode_calldata(&data, None);
+        Some(SafeExecCall {
+            to,
+            value,
+            operation,
+            safe_tx_gas: args.next()?.into_uint()?,
+            base_gas: args.next()?.into_uint()?,
+            gas_price: args.next()?.into_uint()?,
+            gas_token: args.next()?.into_address()?,
+            refund_receiver: args.next()?.into_address()?,
+            signatures: args.next()?.into_bytes()?.into(),
+            data,
+            inner,
+        })
+    }
+
+    /// Decode the revert data of a failed call into a readable reason
+    ///
+    /// Handles `Error(string)` (`require`/`revert` messages) and `Panic(uint256)`
+    /// (failed asserts, overflows, ...). Custom errors are returned as their selector,
+    /// empty data as `None`.
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(reason) = decode_revert_reason(&revert_data) {
+    ///     println!("reverted: {}", reason);
+    /// }
+    /// ```
+    pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+        use ethers::abi::{ParamType, decode};
+
+        const ERROR_STRING: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+        const PANIC: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+        let (selector, payload) = (data.get(..4)?, &data[4..]);
+        if selector == ERROR_STRING {
+            if let Some(Token::String(reason)) = decode(&[ParamType::String], payload)
+                .ok()
+                .and_then(|mut tokens| tokens.pop())
+            {
+                return Some(reason);
+            }
+        } else if selector == PANIC {
+            if let Some(Token::Uint(code)) = decode(&[ParamType::Uint(256)], payload)
+                .ok()
+                .and_then(|mut tokens| tokens.pop())
+            {
+                return Some(format!("panic 0x{:x}", code));
+            }
+        }
+        Some(format!("custom error 0x{}", hex::encode(selector)))
+    }
+
+    /// Decoded view of a calldata blob
+    #[derive(Debug, Clone)]
+    pub struct CalldataInfo {
+        /// First four bytes of the calldata, `None` for empty or short input
+        pub selector: Option<[u8; 4]>,
+        /// Resolved function name
+        pub function_name: Option<String>,
+        /// Canonical signature, e.g. `transfer(address,uint256)`
+        pub signature: Option<String>,
+        /// Decoded arguments when the signature is known and the payload matches it
+        pub args: Option<Vec<Token>>,
+    }
+
+    impl CalldataInfo {
+        /// Selector as a `0x`-prefixed hex string
+        pub fn selector_hex(&self) -> Option<String> {
+            self.selector.map(|s| format!("0x{}", hex::encode(s)))
+        }
+
+        /// Calldata carries no function call (plain value transfer)
+        pub fn is_empty(&self) -> bool {
+            self.selector.is_none()
+        }
+    }
+
+    /// Decode a raw calldata blob.
+    ///
+    /// The selector is resolved against `abi` first, then against `KNOWN_FUNCTIONS`.
+    /// Arguments are only returned when they decode cleanly with the matched signature.
+    ///
+    /// # Example
+    /// ```
+    /// let info = decode_calldata(&tx_info.input, None);
+    /// println!("{:?} {:?}", info.function_name, info.args);
+    /// ```
+    pub fn decode_calldata(calldata: &[u8], abi: Option<&ContractABI>) -> CalldataInfo {
+        if calldata.len() < 4 {
+            return CalldataInfo {
+                selector: None,
+                function_name: None,
+                signature: None,
+                args: None,
+            };
+        }
+        let selector = [calldata[0], calldata[1], calldata[2], calldata[3]];
+        let function = abi
+            .and_then(|abi| find_in_abi(abi, selector))
+            .or_else(|| find_known_function(selector));
+        match function {
+            Some(function) => CalldataInfo {
+                selector: Some(selector),
+                function_name: Some(function.name.clone()),
+                signature: Some(function.signature()),
+                args: function.decode_input(&calldata[4..]).ok(),
+            },
+            None => CalldataInfo {
+                selector: Some(selector),
+                function_name: None,
+                signature: None,
+                args: None,
+            },
+        }
+    }
+
+    fn known_functions() -> &'static HashMap<[u8; 4], Function> {
+        static FUNCTIONS: OnceLock<HashMap<[u8; 4], Function>> = OnceLock::new();
+        FUNCTIONS.get_or_init(|| {
+            let mut functions = HashMap::new();
+            for function in KNOWN_FUNCTIONS
+                .iter()
+                .filter_map(|signature| parse_signature(signature))
+            {
+                functions
+                    .entry(function.short_signature())
+                    .or_insert(function);
+            }
+            functions
+        })
+    }
+
+    /// Look up a selector in the built-in signature list
+    pub fn find_known_function(selector: [u8; 4]) -> Option<Function> {
+        known_functions().get(&selector).cloned()
+    }
+
+    fn find_in_abi(abi: &ContractABI, selector: [u8; 4]) -> Option<Function> {
+        abi.functions
+            .iter()
+            .filter_map(|f| {
+                let types: Vec<&str> = f.inputs.iter().map(|p| p.type_.as_str()).collect();
+                parse_signature(&format!("{}({})", f.name, types.join(",")))
+            })
+            .find(|function| function.short_signature() == selector)
+    }
+
+    fn parse_signature(signature: &str) -> Option<Function> {
+        HumanReadableParser::parse_function(signature).ok()
+    }
+}
+
+/// transaction hash tool module
+pub mod tx {
+    use ethers::types::{H256, Signature, transaction::eip2718::TypedTransaction};
+    use ethers::utils::keccak256;
+
+    /// Hash a transaction will have once broadcast with `signature`
+    ///
+    /// The hash is the keccak256 of the signed encoding: the RLP list for legacy
+    /// transactions, the type byte followed by the RLP list for EIP-2930 and EIP-1559.
+    /// A legacy signature may carry a plain `v` of 27/28 (or 0/1); it is converted to
+    /// the EIP-155 form when the transaction has a chain id, as the signer would. For
+    /// typed transactions any of 0/1, 27/28 or an EIP-155 `v` is reduced to the y-parity.
+    ///
+    /// # Example
+    /// ```
+    /// let signature = wallet.sign_transaction(&tx).await?;
+    /// let tx_hash = compute_tx_hash(&tx, &signature);
+    /// ```
+    pub fn compute_tx_hash(tx: &TypedTransaction, signature: &Signature) -> H256 {
+        let mut signature = *signature;
+        match tx {
+            TypedTransaction::Legacy(request) => {
+                let parity = match signature.v {
+                    0 | 1 => Some(signature.v),
+                    27 | 28 => Some(signature.v - 27),
+                    _ => None,
+                };
+                if let Some(parity) = parity {
+                    signature.v = match request.chain_id {
+                        Some(chain_id) => parity + 35 + chain_id.as_u64() * 2,
+                        None => parity + 27,
+                    };
+                }
+            }
+            // Typed transactions encode the bare y-parity
+            _ => {
+                signature.v = match signature.v {
+                    0 | 1 => signature.v,
+                    27 | 28 => signature.v - 27,
+                    v => v.saturating_sub(35) % 2,
+                };
+            }
+        }
+        H256::from(keccak256(tx.rlp_signed(&signature)))
+    }
+
+    /// Hash a transaction is signed over (its signing hash), before any signature
+    pub fn compute_unsigned_hash(tx: &TypedTransaction) -> H256 {
+        H256::from(keccak256(tx.rlp()))
+    }
+}
+
+pub use tx::{compute_tx_hash, compute_unsigned_hash};
+
+#[cfg(test)]
+mod tests {
+    use super::address::{str_to_address, str_to_address_strict};
+    use super::{SafeOperation, decode_revert_reason, decode_safe_exec};
+    use ethers::abi::{Token, encode};
+    use ethers::types::{Address, U256};
+    use ethers::utils::id;
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let mut data = id("Error(string)").to_vec();
+        data.extend(encode(&[Token::String("STF".to_string())]));
+        assert_eq!(decode_revert_reason(&data).as_deref(), Some("STF"));
+        let mut data = id("Panic(uint256)").to_vec();
+        data.extend(encode(&[Token::Uint(U256::from(0x11))]));
+        assert_eq!(decode_revert_reason(&data).as_deref(), Some("panic 0x11"));
+        assert_eq!(
+            decode_revert_reason(&id("InsufficientBalance()")).as_deref(),
+            Some(format!(
+                "custom error 0x{}",
+                hex::encode(id("InsufficientBalance()"))
+            ))
+            .as_deref()
+        );
+        assert_eq!(decode_revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn test_compute_tx_hash() {
+        use super::{compute_tx_hash, compute_unsigned_hash};
+        use ethers::types::{
+            Eip1559TransactionRequest, H256, Signature, TransactionRequest,
+            transaction::eip2718::TypedTransaction,
+            transaction::eip2930::{AccessList, AccessListItem, Eip2930TransactionRequest},
+        };
+        let to = Address::repeat_byte(0x35);
+        let hash = |hex: &str| hex.parse::<H256>().unwrap();
+        let signature = |v: u64| Signature {
+            r: U256::from_str_radix(
+                "28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+                16,
+            )
+            .unwrap(),
+            s: U256::from_str_radix(
+                "67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+                16,
+            )
+            .unwrap(),
+            v,
+        };
+
+        // The example transaction of EIP-155
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .nonce(9)
+            .gas_price(20_000_000_000u64)
+            .gas(21_000)
+            .to(to)
+            .value(U256::exp10(18))
+            .chain_id(1)
+            .into();
+        assert_eq!(
+            compute_unsigned_hash(&legacy),
+            hash("0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+        let legacy_hash =
+            hash("0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788");
+        assert_eq!(compute_tx_hash(&legacy, &signature(37)), legacy_hash);
+        assert_eq!(compute_tx_hash(&legacy, &signature(27)), legacy_hash);
+
+        let access_list = AccessList(vec![AccessListItem {
+            address: to,
+            storage_keys: vec![H256::from_low_u64_be(1)],
+        }]);
+        let eip2930: TypedTransaction = Eip2930TransactionRequest::new(
+            TransactionRequest::new()
+                .nonce(3)
+                .gas_price(30_000_000_000u64)
+                .gas(50_000)
+                .to(to)
+                .value(1_000_000_000u64)
+                .data(vec![0xde, 0xad, 0xbe, 0xef])
+                .chain_id(1),
+            access_list,
+        )
+        .into();
+        assert_eq!(
+            compute_unsigned_hash(&eip2930),
+            hash("0x1c8ea3a8d154125536d13e11cbdfa2b4a90eb711ad643e48ac0f57757e773216")
+        );
+        assert_eq!(
+            compute_tx_hash(&eip2930, &signature(1)),
+            hash("0x31ec82a1853922396282c062c03612ce03f10baef27d8c4f0119883a961db280")
+        );
+
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .nonce(4)
+            .max_priority_fee_per_gas(2_000_000_000u64)
+            .max_fee_per_gas(40_000_000_000u64)
+            .gas(21_000)
+            .to(to)
+            .value(U256::exp10(18))
+            .chain_id(1)
+            .into();
+        assert_eq!(
+            compute_unsigned_hash(&eip1559),
+            hash("0xd4ad6d7122d2a7534cf35db22bb0012c573d8a5ff2015b7c03c54a06ca280a31")
+        );
+        let eip1559_hash =
+            hash("0xa6ab7e5ff510566e62e239533548c3de7540d03f97e64f439bfbb4760df921dd");
+        assert_eq!(compute_tx_hash(&eip1559, &signature(0)), eip1559_hash);
+        assert_eq!(compute_tx_hash(&eip1559, &signature(27)), eip1559_hash);
+        assert_eq!(compute_tx_hash(&eip1559, &signature(37)), eip1559_hash);
+    }
+
+    #[test]
+    fn test_decode_known_log() {
+        use super::decode_known_log;
+        use ethers::types::{H256, Log};
+        use ethers::utils::keccak256;
+
+        let transfer = H256::from(keccak256("Transfer(address,address,uint256)"));
+        let from = H256::from(Address::repeat_byte(0x01));
+        let to = H256::from(Address::repeat_byte(0x02));
+        let erc20 = Log {
+            topics: vec![transfer, from, to],
+            data: encode(&[Token::Uint(U256::from(1_000u64))]).into(),
+            ..Default::default()
+        };
+        let event = decode_known_log(&erc20).unwrap();
+        assert_eq!(event.signature, "Transfer(address,address,uint256)");
+        assert_eq!(event.params[2].name, "value");
+        assert_eq!(event.params[2].value, Token::Uint(U256::from(1_000u64)));
+
+        let erc721 = Log {
+            topics: vec![transfer, from, to, H256::from_low_u64_be(7)],
+            ..Default::default()
+        };
+        let event = decode_known_log(&erc721).unwrap();
+        assert_eq!(event.params[2].name, "tokenId");
+        assert!(event.params[2].indexed);
+
+        let unknown = Log {
+            topics: vec![H256::repeat_byte(0xee)],
+            ..Default::default()
+        };
+        assert!(decode_known_log(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_units_round_trip() {
+        use super::num::{format_units, parse_units};
+
+        // 2^53 + 1 wei and 0.1 ether have no exact f64 representation
+        let wei = parse_units("9007199254740993", 0).unwrap();
+        assert_eq!(wei, U256::from(9_007_199_254_740_993u64));
+        let tenth = parse_units("0.1", 18).unwrap();
+        assert_eq!(tenth, U256::exp10(17));
+        let precise = parse_units("123456789.123456789123456789", 18).unwrap();
+        assert_eq!(precise.to_string(), "123456789123456789123456789");
+        assert_eq!(format_units(precise, 18), "123456789.123456789123456789");
+
+        assert_eq!(
+            parse_units("1_000.5", 6).unwrap(),
+            U256::from(1_000_500_000u64)
+        );
+        assert_eq!(
+            parse_units("1.23456789", 6).unwrap(),
+            U256::from(1_234_567u64)
+        );
+        assert_eq!(parse_units(".5", 1).unwrap(), U256::from(5));
+        assert_eq!(format_units(U256::from(5), 18), "0.000000000000000005");
+        assert_eq!(format_units(U256::from(42), 0), "42.0");
+        assert_eq!(
+            format_units(U256::MAX, 255),
+            format!("0.{}{}", "0".repeat(255 - 78), U256::MAX)
+        );
+
+        assert!(parse_units("-1", 18).is_err());
+        assert!(parse_units("1e18", 0).is_err());
+        assert!(parse_units(".", 18).is_err());
+        assert!(parse_units("1", 78).is_err());
+    }
+
+    #[test]
+    fn test_find_known_function() {
+        use super::calldata::{KNOWN_FUNCTIONS, find_known_function};
+        let function = find_known_function([0xa9, 0x05, 0x9c, 0xbb]).unwrap();
+        assert_eq!(function.signature(), "transfer(address,uint256)");
+        let function = find_known_function(id("multicall(uint256,bytes[])")).unwrap();
+        assert_eq!(function.name, "multicall");
+        assert!(find_known_function([0xde, 0xad, 0xbe, 0xef]).is_none());
+        // Every built-in signature parses and resolves to itself
+        for signature in KNOWN_FUNCTIONS {
+            let function = find_known_function(id(signature)).unwrap();
+            assert_eq!(function.short_signature(), id(signature));
+        }
+    }
+
+    #[test]
+    fn test_decode_safe_exec() {
+        let token = Address::repeat_byte(0x70);
+        let recipient = Address::repeat_byte(0x11);
+        let mut inner = id("transfer(address,uint256)").to_vec();
+        inner.extend(encode(&[
+            Token::Address(recipient),
+            Token::Uint(U256::from(5)),
+        ]));
+        let mut input = vec![0x6a, 0x76, 0x12, 0x02];
+        input.extend(encode(&[
+            Token::Address(token),
+            Token::Uint(U256::zero()),
+            Token::Bytes(inner.clone()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Address(Address::zero()),
+            Token::Address(Address::zero()),
+            Token::Bytes(vec![0x01; 65]),
+        ]));
+        let exec = decode_safe_exec(&input).unwrap();
+        assert_eq!(exec.to, token);
+        assert_eq!(exec.operation, SafeOperation::Call);
+        assert_eq!(exec.data.to_vec(), inner);
+        assert_eq!(exec.signatures.len(), 65);
+        assert_eq!(exec.inner.function_name.as_deref(), Some("transfer"));
+        assert!(decode_safe_exec(&inner).is_none());
+    }
+
+    #[test]
+    fn test_str_to_address() {
+        let checksummed = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        assert!(str_to_address(checksummed).is_ok());
+        assert!(str_to_address_strict(checksummed).is_ok());
+        assert!(str_to_address(&checksummed.to_lowercase()).is_ok());
+        assert!(str_to_address_strict(&checksummed.to_lowercase()).is_err());
+        // one letter flipped to lowercase breaks the checksum
+        let bad_checksum = "0xc02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let err = str_to_address(bad_checksum).unwrap_err().to_string();
+        assert!(err.contains("checksum"), "{}", err);
+        let err = str_to_address("0x1234").unwrap_err().to_string();
+        assert!(err.contains("40 hex characters"), "{}", err);
+    }
+}
@@ -1,16 +1,29 @@
-use crate::{Evm, EvmError, erc::erc20::ERC20Service, global::is_quote, types::Direction};
+use crate::{
+    Evm, EvmError,
+    block::{BlockService, effective_gas_price},
+    contract::{DecodedEvent, DecodedParam},
+    erc::erc20::ERC20Service,
+    global::{
+        governance_events, identify_address, is_quote_address, lending_events, ownership_events,
+        vault_events,
+    },
+    onchain::{lending::LiquidationEvent, uniswap::UniswapService},
+    tool::{decode_known_log, price::PriceOracle},
+    types::{ContractRole, Direction},
+};
 use ethers::{
-    providers::Middleware,
+    providers::{Middleware, Provider, StreamExt, Ws},
     types::{
-        Address, BlockNumber, Filter, H256, Log, Transaction, TransactionReceipt, U256,
+        Address, BlockNumber, Filter, H256, I256, Log, Transaction, TransactionReceipt, U256,
         ValueOrArray,
     },
 };
 use log::error;
 use serde::{Deserialize, Serialize};
 use std::{
+    io::Write,
     sync::{
-        Arc,
+        Arc, Weak,
         atomic::{AtomicU64, Ordering},
     },
     time::Duration,
@@ -24,6 +37,22 @@ pub struct TransactionQuery {
     pub to_block: Option<u64>,
     pub page: Option<u64>,
     pub page_size: Option<u64>,
+    #[serde(default)]
+    pub mode: TransactionScanMode,
+}
+
+/// How `get_transactions_by_address` finds the transactions of an address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionScanMode {
+    /// Transactions in which the address emitted a log, found with `eth_getLogs`. Cheap
+    /// over wide ranges but only meaningful for contracts: an EOA emits no logs, so its
+    /// plain ETH transfers are never returned.
+    #[default]
+    Logs,
+    /// Transactions sent from or to the address, found by reading every block of the
+    /// range. Captures native transfers, at the cost of one request per block; ETH moved
+    /// by internal calls is still not visible.
+    Blocks,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +63,8 @@ pub struct TransactionWithReceipt {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaginatedTransactions {
+    /// Mode the transactions were found with
+    pub mode: TransactionScanMode,
     pub transactions: Vec<TransactionWithReceipt>,
     pub total: u64,
     pub page: u64,
@@ -69,37 +100,47 @@ impl Trade {
     /// let tx_info = trade_service.get_transactions_by_tx("0x1234...").await?;
     /// ```
     pub async fn get_transactions_by_tx(&self, tx_hash: &str) -> Result<TransactionInfo, EvmError> {
-        let hash: H256 = tx_hash
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid transaction hash format: {}", e)))?;
+        let hash: H256 = tx_hash.parse().map_err(|e| {
+            EvmError::InvalidInput(format!("Invalid transaction hash format: {}", e))
+        })?;
         let transaction = self
             .evm
-            .client
-            .provider
-            .get_transaction(hash)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transaction: {}", e)))?
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_transaction(hash)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transaction", e))
+            })
+            .await?
             .ok_or_else(|| EvmError::RpcError("Transaction not found".to_string()))?;
         let receipt = self
             .evm
-            .client
-            .provider
-            .get_transaction_receipt(hash)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transaction receipt: {}", e)))?;
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_transaction_receipt(hash)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transaction receipt", e))
+            })
+            .await?;
         let mut timestamp: Option<U256> = None;
+        let mut base_fee_per_gas: Option<U256> = None;
         let mut logs = Vec::new();
         if let Some(block_number) = transaction.block_number {
             if let Ok(Some(block)) = self
                 .evm
-                .client
-                .provider
-                .get_block(ethers::types::BlockId::Number(
-                    ethers::types::BlockNumber::Number(block_number),
-                ))
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
+                        .get_block(block_number)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get block", e))
+                })
                 .await
             {
                 timestamp = Some(block.timestamp);
+                base_fee_per_gas = block.base_fee_per_gas;
             }
             if let Some(ref receipt_data) = receipt {
                 logs = receipt_data.logs.clone();
@@ -116,16 +157,14 @@ impl Trade {
         let status = receipt.as_ref().and_then(|r| r.status).map(|s| s.as_u64());
         let is_success = status.map(|s| s == 1).unwrap_or(false);
         let gas_used = receipt.as_ref().and_then(|r| r.gas_used);
-        let total_gas_cost =
-            if let (Some(gas_used_val), Some(gas_price_val)) = (gas_used, transaction.gas_price) {
-                gas_used_val.checked_mul(gas_price_val)
-            } else {
-                None
-            };
         let max_priority_fee_per_gas = transaction.max_priority_fee_per_gas;
         let max_fee_per_gas = transaction.max_fee_per_gas;
         let transaction_type = transaction.transaction_type.map(|t| t.as_u64());
         let chain_id = transaction.chain_id;
+        let l1_fee = receipt
+            .as_ref()
+            .and_then(|r| r.other.get_deserialized::<U256>("l1Fee"))
+            .and_then(|fee| fee.ok());
         let mut token_decimals_cache = std::collections::HashMap::new();
         for log in &logs {
             let token_address = log.address;
@@ -138,7 +177,7 @@ impl Trade {
                 }
             }
         }
-        Ok(TransactionInfo {
+        let mut tx_info = TransactionInfo {
             hash,
             from: transaction.from,
             to: transaction.to,
@@ -162,13 +201,32 @@ impl Trade {
             chain_id,
             logs,
             is_success,
-            total_gas_cost,
+            total_gas_cost: None,
             token_decimals_cache,
-        })
+            l1_fee,
+        };
+        // Prefer the price the receipt reports; `gasPrice` of a type-2 tx is only its
+        // effective price on some nodes, so recompute it from the block base fee.
+        let paid_gas_price = tx_info
+            .receipt
+            .as_ref()
+            .and_then(|r| r.effective_gas_price)
+            .or_else(|| base_fee_per_gas.map(|base_fee| tx_info.effective_gas_price(base_fee)))
+            .or(tx_info.gas_price);
+        tx_info.total_gas_cost = gas_used
+            .zip(paid_gas_price)
+            .and_then(|(gas_used, price)| gas_used.checked_mul(price));
+        Ok(tx_info)
     }
 
     /// Get transactions for a specific address with filtering and pagination
     ///
+    /// `query.mode` selects how transactions are found, see `TransactionScanMode`; the
+    /// default `Logs` mode suits contracts, `Blocks` is needed for the native transfers
+    /// of an EOA and requires `from_block` (`to_block` defaults to the latest block).
+    /// Each transaction appears once, in chain order, and the mode used is reported in
+    /// the result.
+    ///
     /// # Example
     /// ```
     /// let query = TransactionQuery {
@@ -177,6 +235,7 @@ impl Trade {
     ///     to_block: Some(1001000),
     ///     page: Some(1),
     ///     page_size: Some(50),
+    ///     mode: TransactionScanMode::Blocks,
     /// };
     /// let result = trade_service.get_transactions_by_address(query).await?;
     /// ```
@@ -184,55 +243,105 @@ impl Trade {
         &self,
         query: TransactionQuery,
     ) -> Result<PaginatedTransactions, EvmError> {
-        let address: Address = query
-            .address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
+        let address = self.evm.resolve_or_parse(&query.address).await?;
 
         let page = query.page.unwrap_or(1);
         let page_size = query.page_size.unwrap_or(50);
-        let mut filter = Filter::new().address(ValueOrArray::Value(address));
-        if let Some(from_block) = query.from_block {
-            filter = filter.from_block(BlockNumber::Number(from_block.into()));
-        }
-        if let Some(to_block) = query.to_block {
-            filter = filter.to_block(BlockNumber::Number(to_block.into()));
-        }
-        let logs = self
-            .evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))?;
+        // Transactions found by a block scan are kept; log hits are fetched per page
+        let candidates: Vec<(H256, Option<Transaction>)> = match query.mode {
+            TransactionScanMode::Logs => {
+                let mut filter = Filter::new().address(ValueOrArray::Value(address));
+                if let Some(from_block) = query.from_block {
+                    filter = filter.from_block(BlockNumber::Number(from_block.into()));
+                }
+                if let Some(to_block) = query.to_block {
+                    filter = filter.to_block(BlockNumber::Number(to_block.into()));
+                }
+                let logs = self
+                    .evm
+                    .read_with_retry(|| async {
+                        self.evm
+                            .provider()
+                            .get_logs(&filter)
+                            .await
+                            .map_err(|e| EvmError::rpc("Failed to get logs", e))
+                    })
+                    .await?;
+                let mut seen = std::collections::HashSet::new();
+                logs.into_iter()
+                    .filter_map(|log| log.transaction_hash)
+                    .filter(|tx_hash| seen.insert(*tx_hash))
+                    .map(|tx_hash| (tx_hash, None))
+                    .collect()
+            }
+            TransactionScanMode::Blocks => {
+                let from_block = query.from_block.ok_or_else(|| {
+                    EvmError::InvalidInput("Block scan mode requires from_block".to_string())
+                })?;
+                let to_block = match query.to_block {
+                    Some(to_block) => to_block,
+                    None => self.evm.get_block_number().await?,
+                };
+                if from_block > to_block {
+                    return Err(EvmError::InvalidInput(format!(
+                        "from_block {} is after to_block {}",
+                        from_block, to_block
+                    )));
+                }
+                let mut candidates = Vec::new();
+                for block_number in from_block..=to_block {
+                    let Some(block) = self
+                        .evm
+                        .get_block_with_txs(BlockNumber::Number(block_number.into()))
+                        .await?
+                    else {
+                        continue;
+                    };
+                    candidates.extend(
+                        block
+                            .transactions
+                            .into_iter()
+                            .filter(|tx| tx.from == address || tx.to == Some(address))
+                            .map(|tx| (tx.hash, Some(tx))),
+                    );
+                }
+                candidates
+            }
+        };
 
-        let total = logs.len() as u64;
+        let total = candidates.len() as u64;
         let total_pages = (total as f64 / page_size as f64).ceil() as u64;
         let start_index = ((page - 1) * page_size) as usize;
-        let end_index = std::cmp::min(start_index + page_size as usize, logs.len());
         let mut transactions = Vec::new();
-        for log in logs
+        for (tx_hash, tx) in candidates
             .into_iter()
             .skip(start_index)
-            .take(end_index - start_index)
+            .take(page_size as usize)
         {
-            if let Some(tx_hash) = log.transaction_hash {
-                if let Ok(Some(tx)) = self.evm.client.provider.get_transaction(tx_hash).await {
-                    let receipt = self
-                        .evm
-                        .client
-                        .provider
+            let tx = match tx {
+                Some(tx) => tx,
+                None => match self.evm.get_transaction(tx_hash).await? {
+                    Some(tx) => tx,
+                    None => continue,
+                },
+            };
+            let receipt = self
+                .evm
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
                         .get_transaction_receipt(tx_hash)
                         .await
-                        .map_err(|e| EvmError::RpcError(format!("Failed to get receipt: {}", e)))?;
-                    transactions.push(TransactionWithReceipt {
-                        transaction: tx,
-                        receipt,
-                    });
-                }
-            }
+                        .map_err(|e| EvmError::rpc("Failed to get receipt", e))
+                })
+                .await?;
+            transactions.push(TransactionWithReceipt {
+                transaction: tx,
+                receipt,
+            });
         }
         Ok(PaginatedTransactions {
+            mode: query.mode,
             transactions,
             total,
             page,
@@ -241,7 +350,12 @@ impl Trade {
         })
     }
 
-    /// Get transactions involving two specific addresses
+    /// Get transactions between two specific addresses
+    ///
+    /// A transaction is returned when one address is its sender and the other its
+    /// recipient, in either direction; use `get_transactions_from_b_to_a` for a single
+    /// direction. Candidates come from logs emitted by either address, each transaction
+    /// appears once, and results are sorted by block number then transaction index.
     ///
     /// # Example
     /// ```
@@ -261,12 +375,8 @@ impl Trade {
         from_block: Option<u64>,
         to_block: Option<u64>,
     ) -> Result<Vec<TransactionWithReceipt>, EvmError> {
-        let address_a_parsed: Address = address_a
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address_a format: {}", e)))?;
-        let address_b_parsed: Address = address_b
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address_b format: {}", e)))?;
+        let address_a_parsed = self.evm.resolve_or_parse(&address_a).await?;
+        let address_b_parsed = self.evm.resolve_or_parse(&address_b).await?;
 
         let mut filter = Filter::new().address(ValueOrArray::Array(vec![
             address_a_parsed,
@@ -280,11 +390,14 @@ impl Trade {
         }
         let logs = self
             .evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))?;
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get logs", e))
+            })
+            .await?;
         let mut transactions = Vec::new();
         let mut processed_hashes = std::collections::HashSet::new();
         for log in logs {
@@ -293,23 +406,9 @@ impl Trade {
                     continue;
                 }
                 processed_hashes.insert(tx_hash);
-                if let Ok(Some(tx)) = self.evm.client.provider.get_transaction(tx_hash).await {
-                    let involves_both = tx.from == address_a_parsed
-                        || tx.from == address_b_parsed
-                        || tx
-                            .to
-                            .map(|to| to == address_a_parsed || to == address_b_parsed)
-                            .unwrap_or(false);
-                    if involves_both {
-                        let receipt = self
-                            .evm
-                            .client
-                            .provider
-                            .get_transaction_receipt(tx_hash)
-                            .await
-                            .map_err(|e| {
-                                EvmError::RpcError(format!("Failed to get receipt: {}", e))
-                            })?;
+                if let Some(tx) = self.evm.get_transaction(tx_hash).await? {
+                    if Self::involves_both(&tx, address_a_parsed, address_b_parsed) {
+                        let receipt = self.evm.get_transaction_receipt(tx_hash).await?;
                         transactions.push(TransactionWithReceipt {
                             transaction: tx,
                             receipt,
@@ -318,9 +417,31 @@ impl Trade {
                 }
             }
         }
+        Self::sort_by_position(&mut transactions);
         Ok(transactions)
     }
 
+    /// One address sent the transaction and the other received it
+    fn involves_both(tx: &Transaction, address_a: Address, address_b: Address) -> bool {
+        match tx.to {
+            Some(to) => {
+                (tx.from == address_a && to == address_b)
+                    || (tx.from == address_b && to == address_a)
+            }
+            None => false,
+        }
+    }
+
+    /// Order transactions by block number, then by index within the block
+    fn sort_by_position(transactions: &mut [TransactionWithReceipt]) {
+        transactions.sort_by_key(|t| {
+            (
+                t.transaction.block_number.map(|n| n.as_u64()),
+                t.transaction.transaction_index.map(|i| i.as_u64()),
+            )
+        });
+    }
+
     /// Get transactions where sender address sent to receiver address
     ///
     /// # Example
@@ -341,12 +462,8 @@ impl Trade {
         from_block: Option<u64>,
         to_block: Option<u64>,
     ) -> Result<Vec<TransactionWithReceipt>, EvmError> {
-        let receiver_parsed: Address = receiver
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid receiver address format: {}", e)))?;
-        let sender_parsed: Address = sender
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid sender address format: {}", e)))?;
+        let receiver_parsed = self.evm.resolve_or_parse(&receiver).await?;
+        let sender_parsed = self.evm.resolve_or_parse(&sender).await?;
 
         let mut filter = Filter::new().address(ValueOrArray::Value(receiver_parsed));
         if let Some(from_block) = from_block {
@@ -357,27 +474,22 @@ impl Trade {
         }
         let logs = self
             .evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))?;
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get logs", e))
+            })
+            .await?;
         let mut transactions = Vec::new();
         for log in logs {
             if let Some(tx_hash) = log.transaction_hash {
-                if let Ok(Some(tx)) = self.evm.client.provider.get_transaction(tx_hash).await {
+                if let Some(tx) = self.evm.get_transaction(tx_hash).await? {
                     if tx.from == sender_parsed
                         && tx.to.map(|to| to == receiver_parsed).unwrap_or(false)
                     {
-                        let receipt = self
-                            .evm
-                            .client
-                            .provider
-                            .get_transaction_receipt(tx_hash)
-                            .await
-                            .map_err(|e| {
-                                EvmError::RpcError(format!("Failed to get receipt: {}", e))
-                            })?;
+                        let receipt = self.evm.get_transaction_receipt(tx_hash).await?;
                         transactions.push(TransactionWithReceipt {
                             transaction: tx,
                             receipt,
@@ -405,9 +517,7 @@ impl Trade {
         from_block: Option<u64>,
         to_block: Option<u64>,
     ) -> Result<TransactionStats, EvmError> {
-        let address_parsed: Address = address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
 
         let query = TransactionQuery {
             address: address.clone(),
@@ -415,6 +525,7 @@ impl Trade {
             to_block,
             page: None,
             page_size: None,
+            mode: TransactionScanMode::default(),
         };
         let transactions = self.get_transactions_by_address(query).await?;
         let mut total_received = ethers::types::U256::zero();
@@ -456,19 +567,25 @@ impl Trade {
     ) -> Result<Option<TransactionWithReceipt>, EvmError> {
         let tx = self
             .evm
-            .client
-            .provider
-            .get_transaction(tx_hash)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transaction: {}", e)))?;
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_transaction(tx_hash)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transaction", e))
+            })
+            .await?;
         if let Some(transaction) = tx {
             let receipt = self
                 .evm
-                .client
-                .provider
-                .get_transaction_receipt(tx_hash)
-                .await
-                .map_err(|e| EvmError::RpcError(format!("Failed to get receipt: {}", e)))?;
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
+                        .get_transaction_receipt(tx_hash)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get receipt", e))
+                })
+                .await?;
             Ok(Some(TransactionWithReceipt {
                 transaction,
                 receipt,
@@ -480,6 +597,10 @@ impl Trade {
 
     /// Get balance history for an address
     ///
+    /// Samples every `interval` blocks from `from_block`. Block timestamps are fetched
+    /// concurrently with `BlockService::get_timestamps`; a block the node cannot return
+    /// keeps a zero timestamp.
+    ///
     /// # Example
     /// ```
     /// let history = trade_service.get_balance_history(
@@ -496,306 +617,1709 @@ impl Trade {
         to_block: u64,
         interval: u64,
     ) -> Result<Vec<BalanceSnapshot>, EvmError> {
-        let address_parsed: Address = address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
+        let blocks: Vec<u64> = (from_block..=to_block).step_by(interval as usize).collect();
+        let timestamps = BlockService::new(self.evm.clone())
+            .get_timestamps(blocks.clone())
+            .await?;
         let mut snapshots = Vec::new();
-        for block_number in (from_block..=to_block).step_by(interval as usize) {
+        for block_number in blocks {
             let balance = self
                 .evm
-                .client
-                .provider
-                .get_balance(address_parsed, Some(block_number.into()))
-                .await
-                .map_err(|e| EvmError::RpcError(format!("Failed to get balance: {}", e)))?;
+                .read_with_retry(|| async {
+                    self.evm
+                        .provider()
+                        .get_balance(address_parsed, Some(block_number.into()))
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get balance", e))
+                })
+                .await?;
             snapshots.push(BalanceSnapshot {
                 block_number,
                 balance,
-                timestamp: 0,
+                timestamp: timestamps.get(&block_number).copied().unwrap_or_default(),
             });
         }
         Ok(snapshots)
     }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransferEvent {
-    pub from: Address,
-    pub to: Address,
-    pub value: ethers::types::U256,
-    pub transaction_hash: H256,
-    pub block_number: u64,
-    pub log_index: u64,
-}
 
-impl TransferEvent {
-    pub fn from_log(log: &Log) -> Result<Self, String> {
-        if log.topics.len() != 3 {
-            return Err(format!(
-                "Invalid Transfer event log: expected 3 topics, got {}",
-                log.topics.len()
-            ));
-        }
-        let from_bytes = log.topics[1].as_bytes();
-        if from_bytes.len() != 32 {
-            return Err(format!("Invalid from topic length: {}", from_bytes.len()));
-        }
-        let from = Address::from_slice(&from_bytes[12..]);
-        let to_bytes = log.topics[2].as_bytes();
-        if to_bytes.len() != 32 {
-            return Err(format!("Invalid to topic length: {}", to_bytes.len()));
+    /// Find the block in which `address` sent its first transaction
+    ///
+    /// Binary-searches the nonce history, so it takes about `log2(head)` nonce reads and
+    /// needs an archive node for anything older than the node's pruning window. Returns
+    /// `None` when the address has never sent a transaction; incoming transfers alone
+    /// do not count.
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(block) = trade_service.find_first_activity("0x...".to_string()).await? {
+    ///     println!("First transaction in block {}", block);
+    /// }
+    /// ```
+    pub async fn find_first_activity(&self, address: String) -> Result<Option<u64>, EvmError> {
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
+        let head = self.evm.get_block_number().await?;
+        let nonce_at = |block| self.evm.get_transaction_count_at(address_parsed, block);
+        if nonce_at(head).await? == 0 {
+            return Ok(None);
         }
-        let to = Address::from_slice(&to_bytes[12..]);
-        let value = if log.data.is_empty() {
-            ethers::types::U256::zero()
-        } else {
-            let mut data_bytes = [0u8; 32];
-            let data_len = log.data.len();
-            if data_len >= 32 {
-                data_bytes.copy_from_slice(&log.data[..32]);
+        // Invariant: the nonce is zero before `low` and non-zero at `high`.
+        let (mut low, mut high) = (0, head);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if nonce_at(middle).await? > 0 {
+                high = middle;
             } else {
-                let start = 32 - data_len;
-                data_bytes[start..].copy_from_slice(&log.data);
+                low = middle + 1;
             }
-            ethers::types::U256::from_big_endian(&data_bytes)
-        };
-        let transaction_hash = log
-            .transaction_hash
-            .ok_or("Missing transaction hash in log".to_string())?;
-        let block_number = log
-            .block_number
-            .ok_or("Missing block number in log".to_string())?
-            .as_u64();
-        let log_index = log
-            .log_index
-            .ok_or("Missing log index in log".to_string())?
-            .as_u64();
-        Ok(TransferEvent {
-            from,
-            to,
-            value,
-            transaction_hash,
-            block_number,
-            log_index,
-        })
+        }
+        Ok(Some(high))
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransactionStats {
-    pub address: Address,
-    pub total_transactions: u64,
-    pub incoming_count: u64,
-    pub outgoing_count: u64,
-    pub total_received: ethers::types::U256,
-    pub total_sent: ethers::types::U256,
-    pub first_seen_block: u64,
-    pub last_seen_block: u64,
-}
+    /// Count how many transactions sent by `address` in a block range succeeded
+    ///
+    /// Transactions are collected by `sent_transactions`. A mined transaction whose
+    /// receipt the node did not return, or whose receipt has no status (blocks before
+    /// Byzantium), is counted as pending and left out of the success rate.
+    ///
+    /// # Example
+    /// ```
+    /// let report = trade_service
+    ///     .get_success_rate("0x...".to_string(), 19_000_000, 19_001_000)
+    ///     .await?;
+    /// println!("{} ok, {} reverted", report.successful, report.reverted);
+    /// ```
+    pub async fn get_success_rate(
+        &self,
+        address: String,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<SuccessRateReport, EvmError> {
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
+        let mut report = SuccessRateReport {
+            address: address_parsed,
+            from_block,
+            to_block,
+            ..Default::default()
+        };
+        for sent in self
+            .sent_transactions(address_parsed, from_block, to_block)
+            .await?
+        {
+            match sent.receipt.and_then(|receipt| receipt.status) {
+                Some(status) if status.as_u64() == 1 => report.successful += 1,
+                Some(_) => report.reverted += 1,
+                None => report.pending += 1,
+            }
+        }
+        Ok(report)
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BalanceSnapshot {
-    pub block_number: u64,
-    pub balance: ethers::types::U256,
-    pub timestamp: u64,
-}
+    /// Total fees `address` paid for the transactions it sent in a block range, in wei
+    ///
+    /// Sums `gas_used * effective_gas_price` over the receipts, recomputing the price
+    /// from the block base fee when a node omits `effectiveGasPrice`, so EIP-1559
+    /// transactions are charged what they actually paid. Reverted transactions count
+    /// too; L1 data fees of rollups are not included.
+    ///
+    /// # Example
+    /// ```
+    /// let spent = trade_service
+    ///     .total_gas_spent("0x...".to_string(), 19_000_000, 19_001_000)
+    ///     .await?;
+    /// println!("Spent {} ETH on gas", format_units(spent, "ether")?);
+    /// ```
+    pub async fn total_gas_spent(
+        &self,
+        address: String,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<U256, EvmError> {
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
+        let mut total = U256::zero();
+        for sent in self
+            .sent_transactions(address_parsed, from_block, to_block)
+            .await?
+        {
+            let Some(receipt) = sent.receipt else {
+                continue;
+            };
+            let gas_price = receipt
+                .effective_gas_price
+                .unwrap_or_else(|| effective_gas_price(&sent.transaction, sent.base_fee_per_gas));
+            let fee = receipt
+                .gas_used
+                .unwrap_or_default()
+                .saturating_mul(gas_price);
+            total = total.saturating_add(fee);
+        }
+        Ok(total)
+    }
 
-/// Event listener for transaction monitoring
-pub struct TradeEventListener {
-    evm: Arc<Evm>,
-}
+    /// `total_gas_spent` together with its USD value
+    ///
+    /// `native_token` is the wrapped native token of the chain (WETH, WBNB, ...) the
+    /// oracle is asked to price. The USD value is `None` when the oracle cannot price it.
+    ///
+    /// # Example
+    /// ```
+    /// let weth: Address = ETH_ETHEREUM_MAINNET.parse()?;
+    /// let (wei, usd) = trade_service
+    ///     .total_gas_spent_usd(bot.to_string(), 19_000_000, 19_001_000, &oracle, weth)
+    ///     .await?;
+    /// ```
+    pub async fn total_gas_spent_usd(
+        &self,
+        address: String,
+        from_block: u64,
+        to_block: u64,
+        oracle: &impl PriceOracle,
+        native_token: Address,
+    ) -> Result<(U256, Option<f64>), EvmError> {
+        let spent = self.total_gas_spent(address, from_block, to_block).await?;
+        let usd = match oracle.get_price(native_token).await {
+            Ok(price) => ethers::utils::format_ether(spent)
+                .parse::<f64>()
+                .ok()
+                .map(|amount| amount * price),
+            Err(_) => None,
+        };
+        Ok((spent, usd))
+    }
 
-impl TradeEventListener {
-    pub fn new(evm: Arc<Evm>) -> Self {
-        Self { evm: evm }
+    /// Transactions `address` sent in `from_block..=to_block` with their receipts
+    ///
+    /// Blocks are fetched `SENT_TX_CONCURRENCY` at a time and results keep block
+    /// order. Receipts are only requested for blocks the address sent something in,
+    /// through `Evm::receipts_of`.
+    async fn sent_transactions(
+        &self,
+        address: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<SentTransaction>, EvmError> {
+        use futures::stream::{self, TryStreamExt};
+        const SENT_TX_CONCURRENCY: usize = 16;
+        if from_block > to_block {
+            return Err(EvmError::InvalidInput(format!(
+                "from_block {} is after to_block {}",
+                from_block, to_block
+            )));
+        }
+        let blocks: Vec<Vec<SentTransaction>> = stream::iter(from_block..=to_block)
+            .map(|block_number| async move {
+                let Some(block) = self
+                    .evm
+                    .get_block_with_txs(BlockNumber::Number(block_number.into()))
+                    .await?
+                else {
+                    return Ok(Vec::new());
+                };
+                let sent: Vec<&Transaction> = block
+                    .transactions
+                    .iter()
+                    .filter(|tx| tx.from == address)
+                    .collect();
+                if sent.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let tx_hashes: Vec<H256> = sent.iter().map(|tx| tx.hash).collect();
+                let mut receipts = self.evm.receipts_of(block_number, &tx_hashes).await?;
+                Ok::<_, EvmError>(
+                    sent.into_iter()
+                        .map(|tx| SentTransaction {
+                            receipt: receipts.remove(&tx.hash),
+                            transaction: tx.clone(),
+                            base_fee_per_gas: block.base_fee_per_gas,
+                        })
+                        .collect(),
+                )
+            })
+            .buffered(SENT_TX_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(blocks.into_iter().flatten().collect())
     }
 
-    /// Watch for large transactions based on value threshold
+    /// Rank the DEXes `token` traded on over the last `sample_blocks` blocks by volume share
+    ///
+    /// Pools are found through the token's `Transfer` logs: the receipts of the
+    /// transactions moving the token are decoded with `TransactionInfo::decode_swaps`,
+    /// and every swap with the token on either side counts its token amount towards
+    /// the DEX identified from the swap event. Shares are fractions of the total and
+    /// sum to 1; an empty vec means no swap was found in the window.
     ///
     /// # Example
     /// ```
-    /// let mut receiver = event_listener.watch_large_transactions(
-    ///     U256::from(10u64.pow(18)), // 1 ETH
-    ///     3
-    /// ).await?;
-    ///
-    /// while let Some(tx) = receiver.recv().await {
-    ///     println!("Large transaction: {:?}", tx.transaction.hash);
+    /// for (dex, share) in trade_service.dominant_dex(token, 300).await? {
+    ///     println!("{}: {:.1}%", dex, share * 100.0);
     /// }
     /// ```
-    pub async fn watch_large_transactions(
+    pub async fn dominant_dex(
         &self,
-        min_value: ethers::types::U256,
-        poll_interval_secs: u64,
-    ) -> Result<tokio::sync::mpsc::Receiver<TransactionWithReceipt>, EvmError> {
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let evm = self.evm.clone();
-        let last_block = Arc::new(AtomicU64::new(0));
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        last_block.store(current_block.as_u64(), Ordering::SeqCst);
-        tokio::spawn(async move {
-            let mut poll_interval = interval(Duration::from_secs(poll_interval_secs));
-            loop {
-                poll_interval.tick().await;
-                if let Err(e) =
-                    Self::poll_large_transactions(&evm, &last_block, min_value, &tx).await
-                {
-                    tokio::time::sleep(Duration::from_secs(poll_interval_secs * 2)).await;
+        token: Address,
+        sample_blocks: u64,
+    ) -> Result<Vec<(String, f64)>, EvmError> {
+        if sample_blocks == 0 {
+            return Ok(Vec::new());
+        }
+        let head = self.evm.get_block_number().await?;
+        let filter = Filter::new()
+            .address(token)
+            .topic0(H256::from(crate::global::dex_events::erc20_transfer()))
+            .from_block(head.saturating_sub(sample_blocks - 1))
+            .to_block(head);
+        let mut tx_hashes_by_block: std::collections::BTreeMap<u64, Vec<H256>> =
+            std::collections::BTreeMap::new();
+        for log in self.evm.get_logs(filter).await? {
+            if let (Some(block_number), Some(tx_hash)) = (log.block_number, log.transaction_hash) {
+                let tx_hashes = tx_hashes_by_block.entry(block_number.as_u64()).or_default();
+                if !tx_hashes.contains(&tx_hash) {
+                    tx_hashes.push(tx_hash);
                 }
             }
-        });
-        Ok(rx)
-    }
-
-    /// The core logic of polling large transactions
-    async fn poll_large_transactions(
-        evm: &Evm,
-        last_block: &AtomicU64,
-        min_value: ethers::types::U256,
-        tx: &tokio::sync::mpsc::Sender<TransactionWithReceipt>,
-    ) -> Result<(), EvmError> {
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        let current_block_num = current_block.as_u64();
-        let from_block = last_block.load(Ordering::SeqCst) + 1;
-        if from_block > current_block_num {
-            return Ok(());
         }
-        let to_block = current_block_num;
-        for block_number in from_block..=to_block {
-            if let Ok(Some(block)) = evm.client.provider.get_block_with_txs(block_number).await {
-                for transaction in block.transactions {
-                    if transaction.value >= min_value {
-                        let receipt = evm
-                            .client
-                            .provider
-                            .get_transaction_receipt(transaction.hash)
-                            .await
-                            .map_err(|e| {
-                                EvmError::RpcError(format!("Failed to get receipt: {}", e))
-                            })?;
-                        let tx_with_receipt = TransactionWithReceipt {
-                            transaction,
-                            receipt,
-                        };
-                        if tx.send(tx_with_receipt).await.is_err() {
-                            return Ok(());
-                        }
-                    }
+        let mut volume: std::collections::HashMap<String, U256> = std::collections::HashMap::new();
+        for (block_number, tx_hashes) in tx_hashes_by_block {
+            let mut receipts = self.evm.receipts_of(block_number, &tx_hashes).await?;
+            for receipt in tx_hashes
+                .iter()
+                .filter_map(|tx_hash| receipts.remove(tx_hash))
+            {
+                if receipt.status.is_some_and(|status| status.is_zero()) {
+                    continue;
+                }
+                for swap in decode_swap_logs(&receipt.logs) {
+                    let amount = if swap.token_in == token {
+                        swap.amount_in
+                    } else if swap.token_out == token {
+                        swap.amount_out
+                    } else {
+                        continue;
+                    };
+                    let total = volume.entry(swap.dex).or_default();
+                    *total = total.saturating_add(amount);
                 }
             }
         }
-        last_block.store(to_block, Ordering::SeqCst);
-        Ok(())
+        Ok(rank_by_share(volume))
     }
 
-    /// Watch for large ERC20 transfer events
+    /// Compute the `amountOutMin` for a V2 router swap, accounting for slippage and transfer tax
+    ///
+    /// The router quote (`getAmountsOut`) assumes the pool receives and pays out full
+    /// amounts, but a fee-on-transfer token skims `token_tax_bps` on the way in or out,
+    /// so the recipient gets less than quoted and the router's `amountOutMin` check
+    /// reverts. The tax is therefore deducted from the quote first, and the slippage
+    /// tolerance is applied on top of the taxed amount. The two compound, so a 5% tax
+    /// with 1% slippage yields `quote * 0.95 * 0.99`, not `quote * 0.94`.
+    ///
+    /// `token_tax_bps` is the total tax charged along the path, e.g. the sell tax of
+    /// the input token plus the buy tax of the output token.
     ///
     /// # Example
     /// ```
-    /// let token_address = "0x...".to_string();
-    /// let mut receiver = event_listener.watch_large_transfers(
-    ///     Some(token_address),
-    ///     U256::from(1000 * 10u64.pow(18)), // 1000 tokens
-    ///     3
-    /// ).await?;
+    /// let min_out = trade_service
+    ///     .compute_amount_out_min(router, amount_in, vec![token, weth], 100, 500)
+    ///     .await?;
     /// ```
-    pub async fn watch_large_transfers(
+    pub async fn compute_amount_out_min(
         &self,
-        token_address: Option<String>,
-        min_value: ethers::types::U256,
-        poll_interval_secs: u64,
-    ) -> Result<tokio::sync::mpsc::Receiver<LargeTransferEvent>, EvmError> {
-        let token_address_parsed = match &token_address {
-            Some(addr_str) => {
-                let addr: Address = addr_str.parse().map_err(|e| {
-                    EvmError::RpcError(format!("Invalid token address format: {}", e))
-                })?;
-                Some(addr)
-            }
-            None => None,
-        };
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let evm = self.evm.clone();
-        let last_block = Arc::new(AtomicU64::new(0));
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        last_block.store(current_block.as_u64(), Ordering::SeqCst);
-        tokio::spawn(async move {
-            let mut poll_interval = interval(Duration::from_secs(poll_interval_secs));
-            loop {
-                poll_interval.tick().await;
-                if let Err(e) = Self::poll_large_transfers(
-                    &evm,
-                    &last_block,
-                    token_address_parsed,
-                    min_value,
-                    &tx,
+        router: Address,
+        amount_in: U256,
+        path: Vec<Address>,
+        slippage_bps: u32,
+        token_tax_bps: u32,
+    ) -> Result<U256, EvmError> {
+        let amounts = UniswapService::new(self.evm.clone())
+            .v2_get_amounts_out(router, amount_in, path)
+            .await?;
+        let quote = amounts
+            .last()
+            .copied()
+            .ok_or_else(|| EvmError::ContractError("Router returned no amounts".to_string()))?;
+        apply_slippage_and_tax(quote, slippage_bps, token_tax_bps)
+    }
+
+    /// Estimate the profit an attacker extracted with a sandwich, in the quote token
+    ///
+    /// The front-run must buy a token on a pool with the quote token and the back-run
+    /// sell it back on the same pool, later in the same block. Profit is the quote
+    /// received in the back-run minus the quote spent in the front-run; tokens bought
+    /// but not sold back are valued at the back-run price. Gas of both transactions is
+    /// deducted when the quote token is the wrapped native token, and left out for other
+    /// quote tokens, as there is no on-chain price to convert it. A losing sandwich
+    /// reports zero.
+    ///
+    /// # Example
+    /// ```
+    /// let profit = trade_service.estimate_sandwich_profit(front_run, back_run).await?;
+    /// ```
+    pub async fn estimate_sandwich_profit(
+        &self,
+        front_run: H256,
+        back_run: H256,
+    ) -> Result<U256, EvmError> {
+        let front = self
+            .get_transactions_by_tx(&format!("{:?}", front_run))
+            .await?;
+        let back = self
+            .get_transactions_by_tx(&format!("{:?}", back_run))
+            .await?;
+        let in_order = match (
+            (front.block_number, front.transaction_index),
+            (back.block_number, back.transaction_index),
+        ) {
+            ((Some(front_block), Some(front_index)), (Some(back_block), Some(back_index))) => {
+                front_block == back_block && front_index < back_index
+            }
+            _ => false,
+        };
+        if !in_order {
+            return Err(EvmError::CalculationError(
+                "Front-run and back-run must be mined in the same block, in that order".to_string(),
+            ));
+        }
+        let back_swaps = back.decode_swaps();
+        let (front_swap, back_swap) = front
+            .decode_swaps()
+            .into_iter()
+            .find_map(|front_swap| {
+                back_swaps
+                    .iter()
+                    .find(|back_swap| {
+                        back_swap.pool == front_swap.pool
+                            && back_swap.token_in == front_swap.token_out
+                            && back_swap.token_out == front_swap.token_in
+                    })
+                    .map(|back_swap| (front_swap.clone(), back_swap.clone()))
+            })
+            .ok_or_else(|| {
+                EvmError::CalculationError(
+                    "Transactions do not trade opposite directions of a shared pool".to_string(),
+                )
+            })?;
+        let gas_cost = if crate::global::is_wrapped_native(&front_swap.token_in) {
+            front.total_gas_cost.unwrap_or_default() + back.total_gas_cost.unwrap_or_default()
+        } else {
+            U256::zero()
+        };
+        let profit = sandwich_profit(&front_swap, &back_swap, gas_cost);
+        Ok(if profit.is_negative() {
+            U256::zero()
+        } else {
+            profit.into_raw()
+        })
+    }
+
+    /// Position of a swap transaction among the transactions of its block that swap on
+    /// the same pool
+    ///
+    /// The pool and direction are those of the first swap `TransactionInfo::decode_swaps`
+    /// finds in the transaction. Every transaction of the block is decoded the same way,
+    /// so the result tells which swaps on the pool came before and after it, in the same
+    /// or the opposite direction, which is the context needed to spot a sandwich.
+    ///
+    /// # Example
+    /// ```
+    /// let ordering = trade_service.get_tx_pool_ordering(tx_hash).await?;
+    /// println!("Swap {} of {} on {:?}", ordering.position + 1, ordering.pool_transactions.len(), ordering.pool);
+    /// if ordering.is_sandwiched() {
+    ///     println!("Sandwiched");
+    /// }
+    /// ```
+    pub async fn get_tx_pool_ordering(&self, tx_hash: H256) -> Result<PoolOrdering, EvmError> {
+        let receipt = self
+            .evm
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| {
+                EvmError::InvalidInput(format!("Transaction {:?} is not mined", tx_hash))
+            })?;
+        let block_number = receipt
+            .block_number
+            .ok_or_else(|| {
+                EvmError::InvalidInput(format!("Transaction {:?} is not mined", tx_hash))
+            })?
+            .as_u64();
+        let block = self
+            .evm
+            .get_block_with_txs(BlockNumber::Number(block_number.into()))
+            .await?
+            .ok_or_else(|| EvmError::RpcError(format!("Block {} not found", block_number)))?;
+        let tx_hashes: Vec<H256> = block.transactions.iter().map(|tx| tx.hash).collect();
+        let receipts: Vec<TransactionReceipt> = self
+            .evm
+            .receipts_of(block_number, &tx_hashes)
+            .await?
+            .into_values()
+            .collect();
+        pool_ordering(tx_hash, block_number, receipts).ok_or_else(|| {
+            EvmError::CalculationError(format!("Transaction {:?} has no decodable swap", tx_hash))
+        })
+    }
+
+    /// Make sure `spender` may move at least `needed` of the wallet's `token`
+    ///
+    /// Sends an approval only when the current allowance is below `needed`, approving
+    /// `U256::MAX` when `approve_max` is set so later swaps through the same router need
+    /// no further approvals. Returns the approval transaction hash, or `None` when the
+    /// allowance already suffices. The swap must not be sent before the approval is
+    /// mined, or its gas estimation reverts.
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(approval) = trade_service.ensure_allowance(usdc, router, amount_in, true).await? {
+    ///     println!("Approval sent: {:?}", approval);
+    /// }
+    /// ```
+    pub async fn ensure_allowance(
+        &self,
+        token: Address,
+        spender: Address,
+        needed: U256,
+        approve_max: bool,
+    ) -> Result<Option<H256>, EvmError> {
+        if !approve_max {
+            return self
+                .erc20_service
+                .ensure_allowance(token, spender, needed)
+                .await;
+        }
+        if !self
+            .erc20_service
+            .prepare_approval(token, spender, needed)
+            .await?
+        {
+            return Ok(None);
+        }
+        self.erc20_service
+            .approve_max(token, spender)
+            .await
+            .map(Some)
+    }
+
+    /// Export every `Transfer` of a token in a block range to a writer, as CSV or JSON lines
+    ///
+    /// Logs are fetched in chunks of `TRANSFER_LOG_CHUNK_SIZE` blocks and each transfer is
+    /// written as soon as it is decoded, so memory use does not grow with the range.
+    /// The writer is flushed after every chunk. Returns the number of transfers
+    /// written. A failure after the export started is an `EvmError::ExportInterrupted`
+    /// carrying how many transfers were written before it; its message names the block
+    /// the export stopped at, so it can be resumed from the last flushed chunk.
+    ///
+    /// # Example
+    /// ```
+    /// let file = std::io::BufWriter::new(std::fs::File::create("usdc.csv")?);
+    /// let count = trade_service
+    ///     .export_transfers_to_writer(usdc, 18_000_000, 18_500_000, ExportFormat::Csv, file)
+    ///     .await?;
+    /// ```
+    pub async fn export_transfers_to_writer<W: Write>(
+        &self,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+        format: ExportFormat,
+        mut writer: W,
+    ) -> Result<u64, EvmError> {
+        if from_block > to_block {
+            return Err(EvmError::InvalidInput(format!(
+                "from_block {} is after to_block {}",
+                from_block, to_block
+            )));
+        }
+        let mut written = 0u64;
+        let interrupted =
+            |written: u64, e: EvmError| EvmError::ExportInterrupted(written, Box::new(e));
+        if format == ExportFormat::Csv {
+            writeln!(writer, "{}", TRANSFER_CSV_HEADER).map_err(|e| {
+                interrupted(
+                    0,
+                    EvmError::IOError(format!("Failed to write CSV header: {}", e)),
                 )
+            })?;
+        }
+        let mut chunk_start = from_block;
+        while chunk_start <= to_block {
+            let chunk_end = to_block.min(chunk_start.saturating_add(TRANSFER_LOG_CHUNK_SIZE - 1));
+            let logs = self
+                .transfer_logs(token, chunk_start, chunk_end)
                 .await
-                {
-                    tokio::time::sleep(Duration::from_secs(poll_interval_secs * 2)).await;
+                .map_err(|e| {
+                    interrupted(
+                        written,
+                        EvmError::RpcError(format!(
+                            "Export stopped at block {}: {}",
+                            chunk_start, e
+                        )),
+                    )
+                })?;
+            for log in &logs {
+                let transfer = match TransferEvent::from_log(log) {
+                    Ok(transfer) => transfer,
+                    Err(e) => {
+                        error!(target: "[Trade Module]", "Skipping transfer log: {}", e);
+                        continue;
+                    }
+                };
+                write_transfer(&mut writer, format, &transfer).map_err(|e| {
+                    interrupted(
+                        written,
+                        EvmError::IOError(format!(
+                            "Export stopped at block {}: {}",
+                            transfer.block_number, e
+                        )),
+                    )
+                })?;
+                written += 1;
+            }
+            writer.flush().map_err(|e| {
+                interrupted(
+                    written,
+                    EvmError::IOError(format!(
+                        "Failed to flush export at block {}: {}",
+                        chunk_end, e
+                    )),
+                )
+            })?;
+            chunk_start = match chunk_end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(written)
+    }
+
+    /// Transfer volume of a token aggregated into buckets of `bucket_blocks` blocks
+    ///
+    /// Buckets start at `from_block` and cover the range in ascending order, the last
+    /// one ending at `to_block`; buckets without transfers are included with zero
+    /// volume. Each bucket is stamped with the timestamp of its first block, fetched
+    /// concurrently with `BlockService::get_timestamps`, so a range split into more
+    /// than `MAX_VOLUME_BUCKETS` buckets is rejected.
+    ///
+    /// # Example
+    /// ```
+    /// // Hourly buckets on Ethereum
+    /// let buckets = trade_service
+    ///     .get_volume_timeseries(usdc, 19_000_000, 19_007_200, 300)
+    ///     .await?;
+    /// for bucket in buckets {
+    ///     println!("{}: {} in {} transfers", bucket.timestamp, bucket.volume, bucket.transfer_count);
+    /// }
+    /// ```
+    pub async fn get_volume_timeseries(
+        &self,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+        bucket_blocks: u64,
+    ) -> Result<Vec<VolumeBucket>, EvmError> {
+        if from_block > to_block {
+            return Err(EvmError::InvalidInput(format!(
+                "from_block {} is after to_block {}",
+                from_block, to_block
+            )));
+        }
+        if bucket_blocks == 0 {
+            return Err(EvmError::InvalidInput(
+                "bucket_blocks must be greater than zero".to_string(),
+            ));
+        }
+        if (to_block - from_block) / bucket_blocks >= MAX_VOLUME_BUCKETS {
+            return Err(EvmError::InvalidInput(format!(
+                "Blocks {}..={} in buckets of {} blocks exceed {} buckets",
+                from_block, to_block, bucket_blocks, MAX_VOLUME_BUCKETS
+            )));
+        }
+        let mut buckets = empty_buckets(from_block, to_block, bucket_blocks);
+        let mut chunk_start = from_block;
+        while chunk_start <= to_block {
+            let chunk_end = to_block.min(chunk_start.saturating_add(TRANSFER_LOG_CHUNK_SIZE - 1));
+            for log in self.transfer_logs(token, chunk_start, chunk_end).await? {
+                let transfer = match TransferEvent::from_log(&log) {
+                    Ok(transfer) => transfer,
+                    Err(e) => {
+                        error!(target: "[Trade Module]", "Skipping transfer log: {}", e);
+                        continue;
+                    }
+                };
+                // A node may return logs outside the requested range
+                let Some(offset) = transfer.block_number.checked_sub(from_block) else {
+                    continue;
+                };
+                if let Some(bucket) = buckets.get_mut((offset / bucket_blocks) as usize) {
+                    bucket.volume = bucket.volume.saturating_add(transfer.value);
+                    bucket.transfer_count += 1;
+                }
+            }
+            chunk_start = match chunk_end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        let timestamps = BlockService::new(self.evm.clone())
+            .get_timestamps(buckets.iter().map(|bucket| bucket.start_block).collect())
+            .await?;
+        for bucket in &mut buckets {
+            bucket.timestamp = timestamps
+                .get(&bucket.start_block)
+                .copied()
+                .unwrap_or_default();
+        }
+        Ok(buckets)
+    }
+
+    /// `Transfer` logs of a token in a block range
+    async fn transfer_logs(
+        &self,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, EvmError> {
+        let filter = Filter::new()
+            .address(token)
+            .topic0(H256::from(crate::global::dex_events::erc20_transfer()))
+            .from_block(from_block)
+            .to_block(to_block);
+        self.evm.get_logs(filter).await
+    }
+}
+
+/// Whether a receipt reports a reverted transaction; unknown status counts as success
+fn is_reverted(receipt: Option<&TransactionReceipt>) -> bool {
+    receipt
+        .and_then(|receipt| receipt.status)
+        .is_some_and(|status| status.is_zero())
+}
+
+/// Zero-volume buckets of `bucket_blocks` blocks covering `from_block..=to_block`
+fn empty_buckets(from_block: u64, to_block: u64, bucket_blocks: u64) -> Vec<VolumeBucket> {
+    let mut buckets = Vec::new();
+    let mut start_block = from_block;
+    loop {
+        let end_block = to_block.min(start_block.saturating_add(bucket_blocks - 1));
+        buckets.push(VolumeBucket {
+            start_block,
+            end_block,
+            ..Default::default()
+        });
+        match end_block.checked_add(1) {
+            Some(next) if next <= to_block => start_block = next,
+            _ => return buckets,
+        }
+    }
+}
+
+/// Transfer volume of a token over a range of blocks, see `Trade::get_volume_timeseries`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VolumeBucket {
+    pub start_block: u64,
+    /// Last block of the bucket, inclusive
+    pub end_block: u64,
+    /// Unix timestamp of `start_block`, zero if the node could not return the block
+    pub timestamp: u64,
+    /// Sum of the transferred amounts, in the token's smallest unit
+    pub volume: U256,
+    pub transfer_count: u64,
+}
+
+/// Block range queried per `eth_getLogs` call when scanning a token's transfers
+pub(crate) const TRANSFER_LOG_CHUNK_SIZE: u64 = 2_000;
+
+/// Most buckets `Trade::get_volume_timeseries` returns, each costing a block lookup
+const MAX_VOLUME_BUCKETS: u64 = 100_000;
+
+/// Largest window of full blocks the large transaction watchers download at once
+const LARGE_TX_BLOCKS_PER_SCAN: u64 = 100;
+
+const TRANSFER_CSV_HEADER: &str = "block_number,transaction_hash,log_index,from,to,value";
+
+/// Output format of `Trade::export_transfers_to_writer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated rows, preceded by a header line
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// Write a single transfer as a CSV row or a JSON line
+fn write_transfer<W: Write>(
+    writer: &mut W,
+    format: ExportFormat,
+    transfer: &TransferEvent,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Csv => writeln!(
+            writer,
+            "{},{:?},{},{:?},{:?},{}",
+            transfer.block_number,
+            transfer.transaction_hash,
+            transfer.log_index,
+            transfer.from,
+            transfer.to,
+            transfer.value
+        ),
+        ExportFormat::JsonLines => {
+            serde_json::to_writer(&mut *writer, transfer)?;
+            writeln!(writer)
+        }
+    }
+}
+
+/// Role of the contract emitting `log`, inferred from the event
+fn role_from_log(log: &Log, decoded: Option<&DecodedEvent>) -> Option<ContractRole> {
+    let topic0 = log.topics.first()?.as_bytes();
+    // Compound's `Mint` shares the Uniswap V2 topic but has no indexed sender
+    let compound_mint = topic0 == lending_events::compound_mint() && log.topics.len() == 1;
+    let dex_event = crate::global::dex_events::is_dex_event(topic0);
+    if lending_events::is_lending_event(topic0) && (compound_mint || !dex_event) {
+        Some(ContractRole::LendingMarket)
+    } else if dex_event {
+        Some(ContractRole::Pool)
+    } else if vault_events::is_vault_event(topic0) {
+        Some(ContractRole::Vault)
+    } else {
+        match decoded?.name.as_str() {
+            "Transfer" | "Approval" | "ApprovalForAll" | "TransferSingle" | "TransferBatch"
+            | "Deposit" | "Withdrawal" => Some(ContractRole::Token),
+            "PairCreated" | "PoolCreated" => Some(ContractRole::Factory),
+            _ => None,
+        }
+    }
+}
+
+/// Pair each swap event in `logs` with the ERC20 transfers into and out of its pool,
+/// see `TransactionInfo::decode_swaps`
+fn decode_swap_logs(logs: &[Log]) -> Vec<DecodedSwap> {
+    use crate::global::dex_events;
+    let swap_events = [
+        dex_events::uniswap_v2_swap(),
+        dex_events::uniswap_v3_swap(),
+        dex_events::curve_token_exchange(),
+        dex_events::pancake_swap(),
+        dex_events::balancer_swap(),
+    ];
+    let mut transfers: Vec<(Address, TransferEvent)> = Vec::new();
+    let mut swaps = Vec::new();
+    for log in logs {
+        let Some(topic0) = log.topics.first() else {
+            continue;
+        };
+        if let Ok(transfer) = TransferEvent::from_log(log) {
+            transfers.push((log.address, transfer));
+            continue;
+        }
+        if !swap_events.iter().any(|event| topic0.as_bytes() == event) {
+            continue;
+        }
+        let pool = log.address;
+        let in_index = transfers.iter().rposition(|(_, t)| t.to == pool);
+        let out_index = transfers.iter().rposition(|(_, t)| t.from == pool);
+        let (Some(in_index), Some(out_index)) = (in_index, out_index) else {
+            continue;
+        };
+        if in_index == out_index {
+            continue;
+        }
+        let (token_in, transfer_in) = transfers[in_index].clone();
+        let (token_out, transfer_out) = transfers[out_index].clone();
+        transfers.remove(in_index.max(out_index));
+        transfers.remove(in_index.min(out_index));
+        swaps.push(DecodedSwap {
+            pool,
+            dex: dex_events::identify_dex_by_event(topic0.as_bytes())
+                .unwrap_or("Unknown")
+                .to_string(),
+            token_in,
+            amount_in: transfer_in.value,
+            token_out,
+            amount_out: transfer_out.value,
+            log_index: log.log_index.map(|i| i.as_u64()),
+        });
+    }
+    swaps
+}
+
+/// Rank DEXes by their share of the total volume, largest first
+fn rank_by_share(volume: std::collections::HashMap<String, U256>) -> Vec<(String, f64)> {
+    let volume: Vec<(String, f64)> = volume
+        .into_iter()
+        // Lossy but total: raw amounts of 18-decimal tokens quickly exceed u64
+        .map(|(dex, amount)| (dex, amount.to_string().parse().unwrap_or(f64::MAX)))
+        .collect();
+    let total: f64 = volume.iter().map(|(_, amount)| amount).sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+    let mut ranked: Vec<(String, f64)> = volume
+        .into_iter()
+        .map(|(dex, amount)| (dex, amount / total))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+/// Order the swaps of a block's receipts on the pool of the first swap of `tx_hash`
+fn pool_ordering(
+    tx_hash: H256,
+    block_number: u64,
+    mut receipts: Vec<TransactionReceipt>,
+) -> Option<PoolOrdering> {
+    receipts.sort_by_key(|receipt| receipt.transaction_index);
+    let target = receipts
+        .iter()
+        .find(|receipt| receipt.transaction_hash == tx_hash)
+        .and_then(|receipt| decode_swap_logs(&receipt.logs).into_iter().next())?;
+    let pool_transactions: Vec<PoolTransaction> = receipts
+        .iter()
+        .filter_map(|receipt| {
+            let swap = decode_swap_logs(&receipt.logs)
+                .into_iter()
+                .find(|swap| swap.pool == target.pool)?;
+            Some(PoolTransaction {
+                tx_hash: receipt.transaction_hash,
+                transaction_index: receipt.transaction_index.as_u64(),
+                from: receipt.from,
+                same_direction: swap.token_in == target.token_in,
+            })
+        })
+        .collect();
+    let position = pool_transactions
+        .iter()
+        .position(|tx| tx.tx_hash == tx_hash)?;
+    Some(PoolOrdering {
+        tx_hash,
+        block_number,
+        pool: target.pool,
+        dex: target.dex,
+        position,
+        preceded_same_direction: pool_transactions[..position]
+            .iter()
+            .any(|tx| tx.same_direction),
+        followed_same_direction: pool_transactions[position + 1..]
+            .iter()
+            .any(|tx| tx.same_direction),
+        pool_transactions,
+    })
+}
+
+/// Attacker profit of a sandwich in the quote token: what the back-run sold for, plus
+/// any bought tokens kept (valued at the back-run price), minus what the front-run paid
+/// and the gas cost
+///
+/// Amounts come from logs any contract can emit, so the arithmetic saturates instead of
+/// overflowing and amounts beyond `I256::MAX` are clamped.
+fn sandwich_profit(front: &DecodedSwap, back: &DecodedSwap, gas_cost: U256) -> I256 {
+    let signed = |value: U256| I256::from_raw(value.min(I256::MAX.into_raw()));
+    let mut proceeds = signed(back.amount_out);
+    if front.amount_out > back.amount_in && !back.amount_in.is_zero() {
+        let kept = front.amount_out - back.amount_in;
+        let kept_value = U256::try_from(
+            kept.full_mul(back.amount_out) / ethers::types::U512::from(back.amount_in),
+        )
+        .unwrap_or(U256::MAX);
+        proceeds = proceeds.saturating_add(signed(kept_value));
+    }
+    proceeds
+        .saturating_sub(signed(front.amount_in))
+        .saturating_sub(signed(gas_cost))
+}
+
+/// Decode a single `permit` call, either EIP-2612 or DAI-style
+fn decode_permit(input: &[u8], in_multicall: bool) -> Option<PermitCall> {
+    use crate::tool::calldata::{PERMIT_DAI, PERMIT_EIP2612};
+    let info = crate::tool::decode_calldata(input, None);
+    let signature = info.signature?;
+    let mut args = info.args?.into_iter();
+    let owner = args.next()?.into_address()?;
+    let spender = args.next()?.into_address()?;
+    if signature == PERMIT_EIP2612 {
+        Some(PermitCall {
+            kind: PermitKind::Eip2612,
+            owner,
+            spender,
+            value: args.next()?.into_uint()?,
+            deadline: args.next()?.into_uint()?,
+            nonce: None,
+            in_multicall,
+        })
+    } else if signature == PERMIT_DAI {
+        let nonce = args.next()?.into_uint()?;
+        let deadline = args.next()?.into_uint()?;
+        let allowed = args.next()?.into_bool()?;
+        Some(PermitCall {
+            kind: PermitKind::Dai,
+            owner,
+            spender,
+            value: if allowed { U256::MAX } else { U256::zero() },
+            deadline,
+            nonce: Some(nonce),
+            in_multicall,
+        })
+    } else {
+        None
+    }
+}
+
+/// Deduct a transfer tax and then a slippage tolerance, both in basis points, from a quote
+fn apply_slippage_and_tax(
+    quote: U256,
+    slippage_bps: u32,
+    token_tax_bps: u32,
+) -> Result<U256, EvmError> {
+    const BPS: u32 = 10_000;
+    if slippage_bps > BPS || token_tax_bps > BPS {
+        return Err(EvmError::InvalidInput(format!(
+            "Slippage ({} bps) and tax ({} bps) must not exceed {} bps",
+            slippage_bps, token_tax_bps, BPS
+        )));
+    }
+    let after_tax = quote * U256::from(BPS - token_tax_bps) / U256::from(BPS);
+    Ok(after_tax * U256::from(BPS - slippage_bps) / U256::from(BPS))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferEvent {
+    pub from: Address,
+    pub to: Address,
+    pub value: ethers::types::U256,
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+impl TransferEvent {
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        if log.topics.len() != 3 {
+            return Err(format!(
+                "Invalid Transfer event log: expected 3 topics, got {}",
+                log.topics.len()
+            ));
+        }
+        let from_bytes = log.topics[1].as_bytes();
+        if from_bytes.len() != 32 {
+            return Err(format!("Invalid from topic length: {}", from_bytes.len()));
+        }
+        let from = Address::from_slice(&from_bytes[12..]);
+        let to_bytes = log.topics[2].as_bytes();
+        if to_bytes.len() != 32 {
+            return Err(format!("Invalid to topic length: {}", to_bytes.len()));
+        }
+        let to = Address::from_slice(&to_bytes[12..]);
+        let value = if log.data.is_empty() {
+            ethers::types::U256::zero()
+        } else {
+            let mut data_bytes = [0u8; 32];
+            let data_len = log.data.len();
+            if data_len >= 32 {
+                data_bytes.copy_from_slice(&log.data[..32]);
+            } else {
+                let start = 32 - data_len;
+                data_bytes[start..].copy_from_slice(&log.data);
+            }
+            ethers::types::U256::from_big_endian(&data_bytes)
+        };
+        let transaction_hash = log
+            .transaction_hash
+            .ok_or("Missing transaction hash in log".to_string())?;
+        let block_number = log
+            .block_number
+            .ok_or("Missing block number in log".to_string())?
+            .as_u64();
+        let log_index = log
+            .log_index
+            .ok_or("Missing log index in log".to_string())?
+            .as_u64();
+        Ok(TransferEvent {
+            from,
+            to,
+            value,
+            transaction_hash,
+            block_number,
+            log_index,
+        })
+    }
+}
+
+/// `Transfer(from, to, value)` log of `token` mined in block 1, shared by the tests
+#[cfg(test)]
+pub(crate) fn transfer_log(token: Address, from: Address, to: Address, value: U256) -> Log {
+    Log {
+        address: token,
+        topics: vec![
+            H256::from(crate::global::dex_events::erc20_transfer()),
+            H256::from(from),
+            H256::from(to),
+        ],
+        data: ethers::abi::encode(&[ethers::abi::Token::Uint(value)]).into(),
+        transaction_hash: Some(H256::zero()),
+        block_number: Some(1u64.into()),
+        log_index: Some(U256::zero()),
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStats {
+    pub address: Address,
+    pub total_transactions: u64,
+    pub incoming_count: u64,
+    pub outgoing_count: u64,
+    pub total_received: ethers::types::U256,
+    pub total_sent: ethers::types::U256,
+    pub first_seen_block: u64,
+    pub last_seen_block: u64,
+}
+
+/// Outcome of the transactions an address sent, see `Trade::get_success_rate`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuccessRateReport {
+    pub address: Address,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub successful: u64,
+    pub reverted: u64,
+    /// Mined transactions the node returned no receipt for, or a receipt without a
+    /// status (pre-Byzantium); excluded from `success_rate`
+    pub pending: u64,
+}
+
+impl SuccessRateReport {
+    /// Percentage of settled transactions that succeeded, `None` when none settled
+    pub fn success_rate(&self) -> Option<f64> {
+        let settled = self.successful + self.reverted;
+        (settled > 0).then(|| self.successful as f64 * 100.0 / settled as f64)
+    }
+}
+
+/// A transaction found by `Trade::sent_transactions`
+struct SentTransaction {
+    transaction: Transaction,
+    /// `None` when the node returned no receipt for it
+    receipt: Option<TransactionReceipt>,
+    /// Base fee of the block the transaction was mined in
+    base_fee_per_gas: Option<U256>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub block_number: u64,
+    pub balance: ethers::types::U256,
+    pub timestamp: u64,
+}
+
+/// Polling behaviour shared by every `watch_*` method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Delay between two polls of the chain head
+    pub poll_interval: Duration,
+    /// Largest block range requested from the node in one call
+    pub max_blocks_per_poll: u64,
+    /// First block to process, `None` starts at the current head.
+    ///
+    /// A past block replays history: the watcher catches up to the head in
+    /// `max_blocks_per_poll` windows, then keeps polling in real time.
+    pub start_block: Option<u64>,
+}
+
+impl WatchConfig {
+    /// Start the watcher from a historical block instead of the current head
+    ///
+    /// To resume after a restart without missing events, persist
+    /// `ListenerHealth::last_block()` and pass `last_block + 1` here.
+    ///
+    /// # Example
+    /// ```
+    /// let config = WatchConfig::default().start_from(Some(checkpoint + 1));
+    /// let mut receiver = event_listener.watch_transfer_events(address, config).await?;
+    /// ```
+    pub fn start_from(mut self, block: Option<u64>) -> Self {
+        self.start_block = block;
+        self
+    }
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(3),
+            max_blocks_per_poll: 1000,
+            start_block: None,
+        }
+    }
+}
+
+/// Next block window of at most `window` blocks to scan after `last_block`, capped at `head`
+fn next_scan_range(last_block: u64, head: u64, window: u64) -> (u64, u64) {
+    let from_block = last_block.saturating_add(1);
+    let to_block = head.min(from_block.saturating_add(window.max(1) - 1));
+    (from_block, to_block)
+}
+
+/// Drop the watchers whose supervisor has stopped and return the running ones
+fn live_watchers(watchers: &mut Vec<Weak<ListenerHealth>>) -> Vec<Arc<ListenerHealth>> {
+    watchers.retain(|watcher| watcher.strong_count() > 0);
+    watchers.iter().filter_map(Weak::upgrade).collect()
+}
+
+/// Event listener for transaction monitoring
+pub struct TradeEventListener {
+    evm: Arc<Evm>,
+    health: Arc<std::sync::Mutex<Vec<Weak<ListenerHealth>>>>,
+}
+
+/// Progress of a running watcher, updated by its supervisor loop
+#[derive(Debug)]
+pub struct ListenerHealth {
+    name: String,
+    last_block: AtomicU64,
+    head_block: AtomicU64,
+    consecutive_failures: AtomicU64,
+}
+
+impl ListenerHealth {
+    fn new(name: &str, start_block: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            last_block: AtomicU64::new(start_block),
+            head_block: AtomicU64::new(start_block),
+            consecutive_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Name of the watcher, e.g. `large_transfers`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Last block fully processed by the watcher
+    pub fn last_block(&self) -> u64 {
+        self.last_block.load(Ordering::SeqCst)
+    }
+
+    /// Chain head seen on the last successful poll
+    pub fn head_block(&self) -> u64 {
+        self.head_block.load(Ordering::SeqCst)
+    }
+
+    /// Number of blocks the watcher still has to process to reach the head
+    pub fn blocks_behind(&self) -> u64 {
+        self.head_block().saturating_sub(self.last_block())
+    }
+
+    /// The watcher has processed every block up to the last seen head
+    pub fn is_caught_up(&self) -> bool {
+        self.blocks_behind() == 0
+    }
+
+    /// Number of failed polls since the last successful one
+    pub fn consecutive_failures(&self) -> u64 {
+        self.consecutive_failures.load(Ordering::SeqCst)
+    }
+}
+
+impl TradeEventListener {
+    pub fn new(evm: Arc<Evm>) -> Self {
+        Self {
+            evm: evm,
+            health: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Health of every running watcher started from this listener
+    ///
+    /// Watchers that stopped, because their receiver was dropped or their
+    /// subscription ended, are no longer reported.
+    ///
+    /// # Example
+    /// ```
+    /// for health in event_listener.listener_health() {
+    ///     println!("{}: {} blocks behind", health.name(), health.blocks_behind());
+    /// }
+    /// ```
+    pub fn listener_health(&self) -> Vec<Arc<ListenerHealth>> {
+        self.health
+            .lock()
+            .map(|mut health| live_watchers(&mut health))
+            .unwrap_or_default()
+    }
+
+    /// Register a new watcher starting at `config.start_block`, or at the current
+    /// chain head when no start block is configured
+    async fn register_watcher(
+        &self,
+        name: &str,
+        config: &WatchConfig,
+    ) -> Result<Arc<ListenerHealth>, EvmError> {
+        let current_block = self
+            .evm
+            .read_with_retry(|| async {
+                self.evm
+                    .provider()
+                    .get_block_number()
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get current block", e))
+            })
+            .await?
+            .as_u64();
+        let last_block = match config.start_block {
+            Some(start_block) if start_block > current_block + 1 => {
+                return Err(EvmError::InvalidInput(format!(
+                    "Start block {} is ahead of the chain head {}",
+                    start_block, current_block
+                )));
+            }
+            Some(start_block) => start_block.saturating_sub(1),
+            None => current_block,
+        };
+        let health = Arc::new(ListenerHealth::new(name, last_block));
+        health.head_block.store(current_block, Ordering::SeqCst);
+        if let Ok(mut watchers) = self.health.lock() {
+            watchers.retain(|watcher| watcher.strong_count() > 0);
+            watchers.push(Arc::downgrade(&health));
+        }
+        Ok(health)
+    }
+
+    /// Drive a watcher: poll the chain head and hand `scan` bounded block windows.
+    ///
+    /// After an outage the backlog is processed window by window without waiting
+    /// for the next tick. A failing window is halved before it is retried so a
+    /// range too large for the node does not block the watcher forever, and
+    /// failures back off proportionally to how many happened in a row. The watcher
+    /// stops once `is_closed` reports the receiver gone, checked before every tick
+    /// and window, or when `scan` returns `Ok(false)` after a failed send.
+    async fn supervise<C, F, Fut>(
+        evm: Arc<Evm>,
+        health: Arc<ListenerHealth>,
+        config: WatchConfig,
+        is_closed: C,
+        mut scan: F,
+    ) where
+        C: Fn() -> bool,
+        F: FnMut(Arc<Evm>, u64, u64) -> Fut,
+        Fut: std::future::Future<Output = Result<bool, EvmError>>,
+    {
+        let max_window = config.max_blocks_per_poll.max(1);
+        let mut window = max_window;
+        let mut poll_interval = interval(config.poll_interval.max(Duration::from_millis(100)));
+        loop {
+            if is_closed() {
+                return;
+            }
+            poll_interval.tick().await;
+            let head = match evm.get_block_number().await {
+                Ok(head) => head,
+                Err(e) => {
+                    Self::record_failure(&health, config.poll_interval, &e).await;
+                    continue;
+                }
+            };
+            health.head_block.store(head, Ordering::SeqCst);
+            while health.last_block() < head {
+                if is_closed() {
+                    return;
+                }
+                let (from_block, to_block) = next_scan_range(health.last_block(), head, window);
+                match scan(evm.clone(), from_block, to_block).await {
+                    Ok(true) => {
+                        health.last_block.store(to_block, Ordering::SeqCst);
+                        health.consecutive_failures.store(0, Ordering::SeqCst);
+                        window = window.saturating_mul(2).min(max_window);
+                    }
+                    Ok(false) => return,
+                    Err(e) => {
+                        window = (window / 2).max(1);
+                        Self::record_failure(&health, config.poll_interval, &e).await;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn record_failure(health: &ListenerHealth, poll_interval: Duration, e: &EvmError) {
+        const MAX_BACKOFF: Duration = Duration::from_secs(60);
+        let failures = health.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        error!(
+            target: "[Trade Module]",
+            "Watcher {} failed ({} in a row, {} blocks behind): {:?}",
+            health.name(),
+            failures,
+            health.blocks_behind(),
+            e
+        );
+        let backoff = poll_interval
+            .max(Duration::from_secs(1))
+            .saturating_mul(failures as u32)
+            .min(MAX_BACKOFF);
+        tokio::time::sleep(backoff).await;
+    }
+
+    /// Watch for large transactions based on value threshold
+    ///
+    /// Every block is downloaded with its transactions, so at most
+    /// `LARGE_TX_BLOCKS_PER_SCAN` blocks are scanned per window even when
+    /// `config.max_blocks_per_poll` allows more.
+    ///
+    /// # Example
+    /// ```
+    /// let mut receiver = event_listener.watch_large_transactions(
+    ///     U256::from(10u64.pow(18)), // 1 ETH
+    ///     WatchConfig::default(),
+    /// ).await?;
+    ///
+    /// while let Some(tx) = receiver.recv().await {
+    ///     println!("Large transaction: {:?}", tx.transaction.hash);
+    /// }
+    /// ```
+    pub async fn watch_large_transactions(
+        &self,
+        min_value: ethers::types::U256,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<TransactionWithReceipt>, EvmError> {
+        let config = WatchConfig {
+            max_blocks_per_poll: config.max_blocks_per_poll.min(LARGE_TX_BLOCKS_PER_SCAN),
+            ..config
+        };
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("large_transactions", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move {
+                    Self::scan_large_transactions(&evm, from_block, to_block, min_value, true, &tx)
+                        .await
+                }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// Watch for large transactions with every option of a `LargeTransactionConfig`
+    ///
+    /// Without `watch_tokens`, transactions sending at least `min_value` wei of the native
+    /// token are reported, as with `watch_large_transactions`. With `watch_tokens`, only
+    /// transactions moving at least the threshold of one of those tokens are, found through
+    /// their `Transfer` logs. Each threshold is given in the token's smallest unit.
+    ///
+    /// Reverted transactions are dropped unless `include_failed` is set. A reverted
+    /// transaction emits no logs, so with `watch_tokens` it is never found and
+    /// `include_failed` only affects native value transfers. `poll_interval_secs` replaces
+    /// the poll interval of `config`, and without `watch_tokens` the window is capped at
+    /// `LARGE_TX_BLOCKS_PER_SCAN` blocks like `watch_large_transactions`.
+    ///
+    /// # Example
+    /// ```
+    /// let large = LargeTransactionConfig {
+    ///     watch_tokens: HashMap::from([
+    ///         (usdc, U256::from(1_000_000) * U256::exp10(6)), // 1M USDC
+    ///         (weth, U256::exp10(18) * 500),                  // 500 WETH
+    ///     ]),
+    ///     ..Default::default()
+    /// };
+    /// let mut receiver = event_listener
+    ///     .watch_large_transactions_with(large, WatchConfig::default())
+    ///     .await?;
+    /// ```
+    pub async fn watch_large_transactions_with(
+        &self,
+        large: LargeTransactionConfig,
+        mut config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<TransactionWithReceipt>, EvmError> {
+        config.poll_interval = Duration::from_secs(large.poll_interval_secs.max(1));
+        if large.watch_tokens.is_empty() {
+            config.max_blocks_per_poll = config.max_blocks_per_poll.min(LARGE_TX_BLOCKS_PER_SCAN);
+        }
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("large_transactions", &config).await?;
+        let large = Arc::new(large);
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                let large = large.clone();
+                async move {
+                    if large.watch_tokens.is_empty() {
+                        Self::scan_large_transactions(
+                            &evm,
+                            from_block,
+                            to_block,
+                            large.min_value,
+                            large.include_failed,
+                            &tx,
+                        )
+                        .await
+                    } else {
+                        Self::scan_large_token_transactions(&evm, from_block, to_block, &large, &tx)
+                            .await
+                    }
+                }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// The core logic of scanning blocks for large transactions
+    async fn scan_large_transactions(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        min_value: ethers::types::U256,
+        include_failed: bool,
+        tx: &tokio::sync::mpsc::Sender<TransactionWithReceipt>,
+    ) -> Result<bool, EvmError> {
+        for block_number in from_block..=to_block {
+            let block = evm
+                .read_with_retry(|| async {
+                    evm.provider()
+                        .get_block_with_txs(block_number)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get block", e))
+                })
+                .await?;
+            let Some(block) = block else {
+                continue;
+            };
+            for transaction in block.transactions {
+                if transaction.value >= min_value {
+                    let receipt = evm
+                        .read_with_retry(|| async {
+                            evm.provider()
+                                .get_transaction_receipt(transaction.hash)
+                                .await
+                                .map_err(|e| EvmError::rpc("Failed to get receipt", e))
+                        })
+                        .await?;
+                    if !include_failed && is_reverted(receipt.as_ref()) {
+                        continue;
+                    }
+                    let tx_with_receipt = TransactionWithReceipt {
+                        transaction,
+                        receipt,
+                    };
+                    if tx.send(tx_with_receipt).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Scan a block range for transactions moving at least the threshold of a watched token
+    async fn scan_large_token_transactions(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        large: &LargeTransactionConfig,
+        tx: &tokio::sync::mpsc::Sender<TransactionWithReceipt>,
+    ) -> Result<bool, EvmError> {
+        let filter = Filter::new()
+            .address(large.watch_tokens.keys().copied().collect::<Vec<_>>())
+            .topic0(H256::from(crate::global::dex_events::erc20_transfer()))
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = evm.get_logs(filter).await?;
+        let mut seen = std::collections::HashSet::new();
+        let tx_hashes: Vec<H256> = logs
+            .iter()
+            .filter_map(|log| {
+                let transfer = TransferEvent::from_log(log).ok()?;
+                let min_value = large.watch_tokens.get(&log.address)?;
+                (transfer.value >= *min_value).then_some(transfer.transaction_hash)
+            })
+            .filter(|tx_hash| seen.insert(*tx_hash))
+            .collect();
+        for tx_hash in tx_hashes {
+            let Some(transaction) = evm
+                .read_with_retry(|| async {
+                    evm.provider()
+                        .get_transaction(tx_hash)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get transaction", e))
+                })
+                .await?
+            else {
+                continue;
+            };
+            let receipt = evm.get_transaction_receipt(tx_hash).await?;
+            if !large.include_failed && is_reverted(receipt.as_ref()) {
+                continue;
+            }
+            let tx_with_receipt = TransactionWithReceipt {
+                transaction,
+                receipt,
+            };
+            if tx.send(tx_with_receipt).await.is_err() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Watch for large ERC20 transfer events
+    ///
+    /// # Example
+    /// ```
+    /// let token_address = "0x...".to_string();
+    /// let mut receiver = event_listener.watch_large_transfers(
+    ///     Some(token_address),
+    ///     U256::from(1000 * 10u64.pow(18)), // 1000 tokens
+    ///     WatchConfig::default(),
+    /// ).await?;
+    /// ```
+    pub async fn watch_large_transfers(
+        &self,
+        token_address: Option<String>,
+        min_value: ethers::types::U256,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<LargeTransferEvent>, EvmError> {
+        let token_address_parsed = match &token_address {
+            Some(addr_str) => Some(self.evm.resolve_or_parse(addr_str).await?),
+            None => None,
+        };
+        let tokens: Vec<Address> = token_address_parsed.into_iter().collect();
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("large_transfers", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                let tokens = tokens.clone();
+                async move {
+                    Self::scan_large_transfers(
+                        &evm,
+                        from_block,
+                        to_block,
+                        &tokens,
+                        |_| Some(min_value),
+                        &tx,
+                    )
+                    .await
+                }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// Watch large ERC20 transfers of several tokens with one log filter
+    ///
+    /// Every token needs its own threshold in `min_value_per_token`, given in the
+    /// token's smallest unit so tokens with different decimals or prices can be mixed.
+    ///
+    /// # Example
+    /// ```
+    /// let thresholds = HashMap::from([
+    ///     (usdc, U256::from(1_000_000u64) * 100_000), // 100k USDC, 6 decimals
+    ///     (weth, U256::exp10(18) * 50),               // 50 WETH, 18 decimals
+    /// ]);
+    /// let mut receiver = event_listener.watch_large_transfers_multi(
+    ///     vec![usdc, weth],
+    ///     thresholds,
+    ///     WatchConfig::default(),
+    /// ).await?;
+    /// ```
+    pub async fn watch_large_transfers_multi(
+        &self,
+        tokens: Vec<Address>,
+        min_value_per_token: std::collections::HashMap<Address, U256>,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<LargeTransferEvent>, EvmError> {
+        if tokens.is_empty() {
+            return Err(EvmError::InvalidInput("No tokens to watch".to_string()));
+        }
+        if let Some(token) = tokens
+            .iter()
+            .find(|token| !min_value_per_token.contains_key(token))
+        {
+            return Err(EvmError::InvalidInput(format!(
+                "No minimum value for token {:?}",
+                token
+            )));
+        }
+        let min_value_per_token = Arc::new(min_value_per_token);
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self
+            .register_watcher("large_transfers_multi", &config)
+            .await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                let tokens = tokens.clone();
+                let min_value_per_token = min_value_per_token.clone();
+                async move {
+                    Self::scan_large_transfers(
+                        &evm,
+                        from_block,
+                        to_block,
+                        &tokens,
+                        |token| min_value_per_token.get(&token).copied(),
+                        &tx,
+                    )
+                    .await
                 }
-            }
-        });
+            },
+        ));
         Ok(rx)
     }
 
-    /// The core logic of polling large transfer events
-    async fn poll_large_transfers(
+    /// The core logic of scanning large transfer events
+    ///
+    /// An empty `tokens` list scans transfers of every token. `min_value` gives the
+    /// threshold of a token, or `None` to ignore its transfers.
+    async fn scan_large_transfers(
         evm: &Evm,
-        last_block: &AtomicU64,
-        token_address: Option<Address>,
-        min_value: ethers::types::U256,
+        from_block: u64,
+        to_block: u64,
+        tokens: &[Address],
+        min_value: impl Fn(Address) -> Option<U256>,
         tx: &tokio::sync::mpsc::Sender<LargeTransferEvent>,
-    ) -> Result<(), EvmError> {
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        let current_block_num = current_block.as_u64();
-        let from_block = last_block.load(Ordering::SeqCst) + 1;
-        if from_block > current_block_num {
-            return Ok(());
-        }
-        let to_block = if current_block_num - from_block > 1000 {
-            from_block + 1000
-        } else {
-            current_block_num
-        };
+    ) -> Result<bool, EvmError> {
         // Build Transfer event filters
         let mut filter = Filter::new()
             .event("Transfer(address,address,uint256)")
             .from_block(BlockNumber::Number(from_block.into()))
             .to_block(BlockNumber::Number(to_block.into()));
-        // If a token address is specified, transfer events for that token will be filtered.
-        if let Some(token_addr) = token_address {
-            filter = filter.address(token_addr);
+        // If token addresses are specified, only transfer events of those tokens are filtered.
+        if !tokens.is_empty() {
+            filter = filter.address(tokens.to_vec());
         }
         let logs = evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transfer logs: {}", e)))?;
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transfer logs", e))
+            })
+            .await?;
         for log in logs {
             match TransferEvent::from_log(&log) {
                 Ok(transfer_event) => {
+                    let Some(min_value) = min_value(log.address) else {
+                        continue;
+                    };
                     if transfer_event.value >= min_value {
                         let large_transfer = LargeTransferEvent {
                             token_address: log.address,
@@ -807,7 +2331,7 @@ impl TradeEventListener {
                             log_index: transfer_event.log_index,
                         };
                         if tx.send(large_transfer).await.is_err() {
-                            return Ok(());
+                            return Ok(false);
                         }
                     }
                 }
@@ -816,8 +2340,7 @@ impl TradeEventListener {
                 }
             }
         }
-        last_block.store(to_block, Ordering::SeqCst);
-        Ok(())
+        Ok(true)
     }
 
     /// Watch large transfers for a specific token
@@ -826,7 +2349,7 @@ impl TradeEventListener {
         token_address: String,
         min_value: ethers::types::U256,
     ) -> Result<tokio::sync::mpsc::Receiver<LargeTransferEvent>, EvmError> {
-        self.watch_large_transfers(Some(token_address), min_value, 3)
+        self.watch_large_transfers(Some(token_address), min_value, WatchConfig::default())
             .await
     }
 
@@ -835,7 +2358,8 @@ impl TradeEventListener {
         &self,
         min_value: ethers::types::U256,
     ) -> Result<tokio::sync::mpsc::Receiver<LargeTransferEvent>, EvmError> {
-        self.watch_large_transfers(None, min_value, 3).await
+        self.watch_large_transfers(None, min_value, WatchConfig::default())
+            .await
     }
 
     /// Watch all events for a specific address
@@ -843,153 +2367,201 @@ impl TradeEventListener {
     /// # Example
     /// ```
     /// let mut receiver = event_listener.watch_address_events(
-    ///     "0x...".to_string()
+    ///     "0x...".to_string(),
+    ///     WatchConfig::default(),
     /// ).await?;
     /// ```
     pub async fn watch_address_events(
         &self,
         address: String,
+        config: WatchConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<Log>, EvmError> {
-        let address_parsed: Address = address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
         let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let evm = self.evm.clone();
-        let last_block = Arc::new(AtomicU64::new(0));
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        last_block.store(current_block.as_u64(), Ordering::SeqCst);
-        tokio::spawn(async move {
-            let mut poll_interval = interval(Duration::from_secs(3));
-            loop {
-                poll_interval.tick().await;
-                if let Err(e) = Self::poll_events(&evm, &last_block, address_parsed, &tx).await {
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                }
-            }
-        });
+        let health = self.register_watcher("address_events", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move { Self::scan_events(&evm, from_block, to_block, address_parsed, &tx).await }
+            },
+        ));
         Ok(rx)
     }
 
-    /// The core logic of polling events
-    async fn poll_events(
+    /// The core logic of scanning events
+    async fn scan_events(
         evm: &Evm,
-        last_block: &AtomicU64,
+        from_block: u64,
+        to_block: u64,
         address: Address,
         tx: &tokio::sync::mpsc::Sender<Log>,
-    ) -> Result<(), EvmError> {
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        let current_block_num = current_block.as_u64();
-        let from_block = last_block.load(Ordering::SeqCst) + 1;
-        if from_block > current_block_num {
-            return Ok(());
-        }
-        let to_block = if current_block_num - from_block > 1000 {
-            from_block + 1000
-        } else {
-            current_block_num
-        };
+    ) -> Result<bool, EvmError> {
         let filter = Filter::new()
             .address(address)
             .from_block(BlockNumber::Number(from_block.into()))
             .to_block(BlockNumber::Number(to_block.into()));
         let logs = evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))?;
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get logs", e))
+            })
+            .await?;
         for log in logs {
             if tx.send(log).await.is_err() {
-                return Ok(());
+                return Ok(false);
             }
         }
-        last_block.store(to_block, Ordering::SeqCst);
-        Ok(())
+        Ok(true)
     }
 
     /// Watch transfer events for a specific address
+    ///
+    /// With a WebSocket attached (`Evm::with_ws`) and no `start_block` to catch up from,
+    /// logs are pushed by an `eth_subscribe` log subscription instead of being polled;
+    /// logs the node retracts after a reorg are dropped. If the subscription fails or
+    /// ends, the failure is recorded in the watcher's `ListenerHealth` and the watcher
+    /// falls back to polling from the block of the last delivered log.
     pub async fn watch_transfer_events(
         &self,
         address: String,
+        config: WatchConfig,
     ) -> Result<tokio::sync::mpsc::Receiver<TransferEvent>, EvmError> {
-        let address_parsed: Address = address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
-
+        let address_parsed = self.evm.resolve_or_parse(&address).await?;
         let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("transfer_events", &config).await?;
         let evm = self.evm.clone();
-        let last_block = Arc::new(AtomicU64::new(0));
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        last_block.store(current_block.as_u64(), Ordering::SeqCst);
         tokio::spawn(async move {
-            let mut poll_interval = interval(Duration::from_secs(3));
-            loop {
-                poll_interval.tick().await;
-                if let Err(e) =
-                    Self::poll_transfer_events(&evm, &last_block, address_parsed, &tx).await
+            let mut delivered = None;
+            if let (Some(ws), None) = (evm.ws_provider().cloned(), config.start_block) {
+                match Self::follow_transfer_events(
+                    &ws,
+                    address_parsed,
+                    &health,
+                    &tx,
+                    &mut delivered,
+                )
+                .await
                 {
-                    error!(target: "[Trade Module]", "Error polling transfer events: {:?}", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(()) => return,
+                    Err(e) => {
+                        error!(target: "[Trade Module]", "Transfer log subscription failed, polling instead: {:?}", e);
+                        Self::record_failure(&health, config.poll_interval, &e).await;
+                    }
                 }
             }
+            let sender = tx.clone();
+            Self::supervise(
+                evm,
+                health,
+                config,
+                move || sender.is_closed(),
+                move |evm, from_block, to_block| {
+                    let tx = tx.clone();
+                    async move {
+                        Self::scan_transfer_events(
+                            &evm,
+                            from_block,
+                            to_block,
+                            address_parsed,
+                            delivered,
+                            &tx,
+                        )
+                        .await
+                    }
+                },
+            )
+            .await;
         });
         Ok(rx)
     }
 
-    /// The core logic of polling transfer events
-    async fn poll_transfer_events(
-        evm: &Evm,
-        last_block: &AtomicU64,
+    /// Forward the logs of a transfer log subscription until the receiver is dropped
+    ///
+    /// Every block before the one of the latest log is marked as processed and the
+    /// position of the latest delivered log is kept in `delivered`, so polling can
+    /// resume without gaps or duplicates when the subscription fails or ends.
+    async fn follow_transfer_events(
+        ws: &Provider<Ws>,
         address: Address,
+        health: &ListenerHealth,
         tx: &tokio::sync::mpsc::Sender<TransferEvent>,
+        delivered: &mut Option<(u64, U256)>,
     ) -> Result<(), EvmError> {
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        let current_block_num = current_block.as_u64();
-        let from_block = last_block.load(Ordering::SeqCst) + 1;
-        if from_block > current_block_num {
-            return Ok(());
-        }
-        let to_block = if current_block_num - from_block > 1000 {
-            from_block + 1000
-        } else {
-            current_block_num
-        };
+        let filter = Filter::new()
+            .address(address)
+            .event("Transfer(address,address,uint256)");
+        let mut stream = ws.subscribe_logs(&filter).await.map_err(|e| {
+            EvmError::RpcError(format!("Failed to subscribe to transfer logs: {}", e))
+        })?;
+        while let Some(log) = stream.next().await {
+            if log.removed == Some(true) {
+                continue;
+            }
+            if let Some(block_number) = log.block_number {
+                let block_number = block_number.as_u64();
+                health.head_block.fetch_max(block_number, Ordering::SeqCst);
+                health
+                    .last_block
+                    .fetch_max(block_number.saturating_sub(1), Ordering::SeqCst);
+                *delivered = Some((block_number, log.log_index.unwrap_or_default()));
+            }
+            match TransferEvent::from_log(&log) {
+                Ok(transfer_event) => {
+                    if tx.send(transfer_event).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    error!(target: "[Trade Module]", "Failed to parse transfer event: {:?}", e);
+                }
+            }
+        }
+        Err(EvmError::RpcError(
+            "Transfer log subscription ended".to_string(),
+        ))
+    }
+
+    /// The core logic of scanning transfer events
+    async fn scan_transfer_events(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        address: Address,
+        delivered: Option<(u64, U256)>,
+        tx: &tokio::sync::mpsc::Sender<TransferEvent>,
+    ) -> Result<bool, EvmError> {
         let filter = Filter::new()
             .address(address)
             .event("Transfer(address,address,uint256)")
             .from_block(BlockNumber::Number(from_block.into()))
             .to_block(BlockNumber::Number(to_block.into()));
         let logs = evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get transfer logs: {}", e)))?;
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get transfer logs", e))
+            })
+            .await?;
         for log in logs {
+            let position = (
+                log.block_number.unwrap_or_default().as_u64(),
+                log.log_index.unwrap_or_default(),
+            );
+            if delivered.is_some_and(|delivered| position <= delivered) {
+                continue;
+            }
             match TransferEvent::from_log(&log) {
                 Ok(transfer_event) => {
                     if tx.send(transfer_event).await.is_err() {
-                        return Ok(());
+                        return Ok(false);
                     }
                 }
                 Err(e) => {
@@ -997,8 +2569,7 @@ impl TradeEventListener {
                 }
             }
         }
-        last_block.store(to_block, Ordering::SeqCst);
-        Ok(())
+        Ok(true)
     }
 
     /// Watch address events with custom configuration
@@ -1008,81 +2579,359 @@ impl TradeEventListener {
         poll_interval_secs: u64,
         max_blocks_per_poll: u64,
     ) -> Result<tokio::sync::mpsc::Receiver<Log>, EvmError> {
-        let address_parsed: Address = address
-            .parse()
-            .map_err(|e| EvmError::RpcError(format!("Invalid address format: {}", e)))?;
+        let config = WatchConfig {
+            poll_interval: Duration::from_secs(poll_interval_secs),
+            max_blocks_per_poll,
+            ..WatchConfig::default()
+        };
+        self.watch_address_events(address, config).await
+    }
+
+    /// Watch proposal lifecycle and vote events of a Governor contract
+    ///
+    /// Works with both Compound Governor Bravo and OpenZeppelin Governor, which
+    /// emit the same `ProposalCreated`, `VoteCast`, `ProposalQueued` and
+    /// `ProposalExecuted` events.
+    ///
+    /// # Example
+    /// ```
+    /// let governor: Address = "0x408ED6354d4973f66138C91495F2f2FCbd8724C3".parse()?;
+    /// let mut receiver = event_listener.watch_governance(governor, WatchConfig::default()).await?;
+    ///
+    /// while let Some(event) = receiver.recv().await {
+    ///     println!("{:?}", event.action);
+    /// }
+    /// ```
+    pub async fn watch_governance(
+        &self,
+        governor: Address,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<GovernanceEvent>, EvmError> {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let evm = self.evm.clone();
-        let last_block = Arc::new(AtomicU64::new(0));
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        last_block.store(current_block.as_u64(), Ordering::SeqCst);
-        tokio::spawn(async move {
-            let mut poll_interval = interval(Duration::from_secs(poll_interval_secs));
-            loop {
-                poll_interval.tick().await;
-                if let Err(e) = Self::poll_events_with_config(
-                    &evm,
-                    &last_block,
-                    address_parsed,
-                    &tx,
-                    max_blocks_per_poll,
-                )
-                .await
-                {
-                    error!(target: "[Trade Module]", "Error polling events: {:?}", e);
-                    tokio::time::sleep(Duration::from_secs(poll_interval_secs * 2)).await;
+        let health = self.register_watcher("governance", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move {
+                    Self::scan_governance_events(&evm, from_block, to_block, governor, &tx).await
+                }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// The core logic of scanning governance events
+    async fn scan_governance_events(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        governor: Address,
+        tx: &tokio::sync::mpsc::Sender<GovernanceEvent>,
+    ) -> Result<bool, EvmError> {
+        let filter = Filter::new()
+            .address(governor)
+            .events([
+                governance_events::PROPOSAL_CREATED,
+                governance_events::VOTE_CAST,
+                governance_events::PROPOSAL_QUEUED,
+                governance_events::PROPOSAL_EXECUTED,
+            ])
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()));
+        let logs = evm
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get governance logs", e))
+            })
+            .await?;
+        for log in logs {
+            match GovernanceEvent::from_log(&log) {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+                Err(e) => {
+                    error!(target: "[Trade Module]", "Failed to parse governance event: {:?}", e);
                 }
             }
-        });
+        }
+        Ok(true)
+    }
+
+    /// Watch `OwnershipTransferred` events of an `Ownable` contract
+    ///
+    /// Renouncements (transfers to the zero address) are flagged with `renounced`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut receiver = event_listener
+    ///     .watch_ownership_transfers(token, WatchConfig::default())
+    ///     .await?;
+    ///
+    /// while let Some(event) = receiver.recv().await {
+    ///     if event.renounced {
+    ///         println!("{:?} renounced ownership", event.contract);
+    ///     }
+    /// }
+    /// ```
+    pub async fn watch_ownership_transfers(
+        &self,
+        contract: Address,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<OwnershipTransferEvent>, EvmError> {
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self
+            .register_watcher("ownership_transfers", &config)
+            .await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move {
+                    Self::scan_ownership_transfers(&evm, from_block, to_block, contract, &tx).await
+                }
+            },
+        ));
         Ok(rx)
     }
 
-    /// Polling logic with configuration
-    async fn poll_events_with_config(
+    /// The core logic of scanning ownership transfer events
+    async fn scan_ownership_transfers(
         evm: &Evm,
-        last_block: &AtomicU64,
-        address: Address,
-        tx: &tokio::sync::mpsc::Sender<Log>,
-        max_blocks_per_poll: u64,
-    ) -> Result<(), EvmError> {
-        let current_block = evm
-            .client
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get current block: {}", e)))?;
-        let current_block_num = current_block.as_u64();
-        let from_block = last_block.load(Ordering::SeqCst) + 1;
-        if from_block > current_block_num {
-            return Ok(());
-        }
-        let to_block = if current_block_num - from_block > max_blocks_per_poll {
-            from_block + max_blocks_per_poll
-        } else {
-            current_block_num
-        };
+        from_block: u64,
+        to_block: u64,
+        contract: Address,
+        tx: &tokio::sync::mpsc::Sender<OwnershipTransferEvent>,
+    ) -> Result<bool, EvmError> {
         let filter = Filter::new()
-            .address(address)
+            .address(contract)
+            .event(ownership_events::OWNERSHIP_TRANSFERRED)
             .from_block(BlockNumber::Number(from_block.into()))
             .to_block(BlockNumber::Number(to_block.into()));
         let logs = evm
-            .client
-            .provider
-            .get_logs(&filter)
-            .await
-            .map_err(|e| EvmError::RpcError(format!("Failed to get logs: {}", e)))?;
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get ownership logs", e))
+            })
+            .await?;
         for log in logs {
-            if tx.send(log).await.is_err() {
-                return Ok(());
+            match OwnershipTransferEvent::from_log(&log) {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+                Err(e) => {
+                    error!(target: "[Trade Module]", "Failed to parse ownership event: {:?}", e);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Watch Aave V3 `LiquidationCall` events of a lending pool
+    ///
+    /// # Example
+    /// ```
+    /// let mut receiver = event_listener
+    ///     .watch_liquidations(aave_pool, WatchConfig::default())
+    ///     .await?;
+    ///
+    /// while let Some(event) = receiver.recv().await {
+    ///     println!("{:?} liquidated by {:?}", event.user, event.liquidator);
+    /// }
+    /// ```
+    pub async fn watch_liquidations(
+        &self,
+        pool: Address,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<LiquidationEvent>, EvmError> {
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("liquidations", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move { Self::scan_liquidations(&evm, from_block, to_block, pool, &tx).await }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// The core logic of scanning liquidation events
+    async fn scan_liquidations(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        pool: Address,
+        tx: &tokio::sync::mpsc::Sender<LiquidationEvent>,
+    ) -> Result<bool, EvmError> {
+        let filter = Filter::new()
+            .address(pool)
+            .event(lending_events::AAVE_LIQUIDATION_CALL)
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()));
+        let logs = evm
+            .read_with_retry(|| async {
+                evm.provider()
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| EvmError::rpc("Failed to get liquidation logs", e))
+            })
+            .await?;
+        for log in logs {
+            match LiquidationEvent::from_log(&log) {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+                Err(e) => {
+                    error!(target: "[Trade Module]", "Failed to parse liquidation event: {:?}", e);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Watch all native and token activity of a wallet as one chronological feed
+    ///
+    /// Native transfers are taken from the transactions of each block sent from or to
+    /// `address`; ERC20, ERC721 and ERC1155 transfers from the token logs naming `address`
+    /// as sender or recipient. Within a polled window activities are ordered by block,
+    /// transaction index and log index. Native value moved by internal calls (e.g. a
+    /// contract paying out ETH) is not visible without tracing and is not reported.
+    ///
+    /// # Example
+    /// ```
+    /// let mut receiver = event_listener.watch_wallet(wallet, WatchConfig::default()).await?;
+    ///
+    /// while let Some(activity) = receiver.recv().await {
+    ///     println!("{:?} {:?} {}", activity.direction, activity.kind, activity.amount);
+    /// }
+    /// ```
+    pub async fn watch_wallet(
+        &self,
+        address: Address,
+        config: WatchConfig,
+    ) -> Result<tokio::sync::mpsc::Receiver<WalletActivity>, EvmError> {
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let health = self.register_watcher("wallet", &config).await?;
+        let sender = tx.clone();
+        tokio::spawn(Self::supervise(
+            self.evm.clone(),
+            health,
+            config,
+            move || sender.is_closed(),
+            move |evm, from_block, to_block| {
+                let tx = tx.clone();
+                async move { Self::scan_wallet_activity(&evm, from_block, to_block, address, &tx).await }
+            },
+        ));
+        Ok(rx)
+    }
+
+    /// The core logic of scanning wallet activity
+    async fn scan_wallet_activity(
+        evm: &Evm,
+        from_block: u64,
+        to_block: u64,
+        address: Address,
+        tx: &tokio::sync::mpsc::Sender<WalletActivity>,
+    ) -> Result<bool, EvmError> {
+        let mut activities = Vec::new();
+        for block_number in from_block..=to_block {
+            let block = evm
+                .read_with_retry(|| async {
+                    evm.provider()
+                        .get_block_with_txs(block_number)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get block", e))
+                })
+                .await?;
+            let Some(block) = block else {
+                continue;
+            };
+            for transaction in block.transactions {
+                if transaction.value.is_zero()
+                    || (transaction.from != address && transaction.to != Some(address))
+                {
+                    continue;
+                }
+                if let Some(activity) = WalletActivity::from_transaction(&transaction, address) {
+                    activities.push(activity);
+                }
+            }
+        }
+
+        let transfer = "Transfer(address,address,uint256)";
+        let erc1155 = vec![
+            "TransferSingle(address,address,address,uint256,uint256)",
+            "TransferBatch(address,address,address,uint256[],uint256[])",
+        ];
+        let base = Filter::new()
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()));
+        let filters = [
+            base.clone().event(transfer).topic1(address),
+            base.clone().event(transfer).topic2(address),
+            base.clone().events(erc1155.clone()).topic2(address),
+            base.events(erc1155).topic3(address),
+        ];
+        let mut seen = std::collections::HashSet::new();
+        for filter in filters.iter() {
+            let logs = evm
+                .read_with_retry(|| async {
+                    evm.provider()
+                        .get_logs(filter)
+                        .await
+                        .map_err(|e| EvmError::rpc("Failed to get wallet logs", e))
+                })
+                .await?;
+            for log in logs {
+                if !seen.insert((log.transaction_hash, log.log_index)) {
+                    continue;
+                }
+                match WalletActivity::from_log(&log, address) {
+                    Ok(mut decoded) => activities.append(&mut decoded),
+                    Err(e) => {
+                        error!(target: "[Trade Module]", "Failed to parse wallet log: {:?}", e);
+                    }
+                }
+            }
+        }
+
+        activities.sort_by_key(|activity| {
+            (
+                activity.block_number,
+                activity.transaction_index,
+                activity.log_index.map_or(0, |index| index + 1),
+            )
+        });
+        for activity in activities {
+            if tx.send(activity).await.is_err() {
+                return Ok(false);
             }
         }
-        last_block.store(to_block, Ordering::SeqCst);
-        Ok(())
+        Ok(true)
     }
 
     pub fn stop_event_listener(receiver: tokio::sync::mpsc::Receiver<Log>) {
@@ -1090,6 +2939,176 @@ impl TradeEventListener {
     }
 }
 
+/// Asset moved by a `WalletActivity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalletAssetKind {
+    Native,
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+/// Direction of a `WalletActivity` relative to the watched wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivityDirection {
+    Incoming,
+    Outgoing,
+    /// The wallet is both sender and recipient
+    SelfTransfer,
+}
+
+impl ActivityDirection {
+    fn of(wallet: Address, from: Address, to: Address) -> Self {
+        match (from == wallet, to == wallet) {
+            (true, true) => ActivityDirection::SelfTransfer,
+            (true, false) => ActivityDirection::Outgoing,
+            _ => ActivityDirection::Incoming,
+        }
+    }
+}
+
+/// One native or token transfer involving a watched wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletActivity {
+    pub kind: WalletAssetKind,
+    pub direction: ActivityDirection,
+    /// Token contract, `None` for native transfers
+    pub token_address: Option<Address>,
+    pub from: Address,
+    pub to: Address,
+    /// Token id for ERC721 and ERC1155 transfers
+    pub token_id: Option<U256>,
+    /// Amount transferred in raw units; always 1 for ERC721
+    pub amount: U256,
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    pub transaction_index: u64,
+    /// Log index of token transfers, `None` for native transfers
+    pub log_index: Option<u64>,
+}
+
+impl WalletActivity {
+    fn from_transaction(transaction: &Transaction, wallet: Address) -> Option<Self> {
+        let to = transaction.to?;
+        Some(Self {
+            kind: WalletAssetKind::Native,
+            direction: ActivityDirection::of(wallet, transaction.from, to),
+            token_address: None,
+            from: transaction.from,
+            to,
+            token_id: None,
+            amount: transaction.value,
+            transaction_hash: transaction.hash,
+            block_number: transaction.block_number?.as_u64(),
+            transaction_index: transaction.transaction_index?.as_u64(),
+            log_index: None,
+        })
+    }
+
+    /// Decode an ERC20/ERC721 `Transfer` or ERC1155 `TransferSingle`/`TransferBatch` log.
+    /// A batch transfer yields one activity per token id.
+    fn from_log(log: &Log, wallet: Address) -> Result<Vec<Self>, String> {
+        use ethers::abi::{ParamType, decode};
+
+        let topic_address = |index: usize| -> Result<Address, String> {
+            log.topics
+                .get(index)
+                .map(|topic| Address::from_slice(&topic.as_bytes()[12..]))
+                .ok_or_else(|| format!("Missing topic {}", index))
+        };
+        let transaction_hash = log
+            .transaction_hash
+            .ok_or("Missing transaction hash in log".to_string())?;
+        let block_number = log
+            .block_number
+            .ok_or("Missing block number in log".to_string())?
+            .as_u64();
+        let transaction_index = log
+            .transaction_index
+            .ok_or("Missing transaction index in log".to_string())?
+            .as_u64();
+        let log_index = log
+            .log_index
+            .ok_or("Missing log index in log".to_string())?
+            .as_u64();
+        let activity = |kind, from, to, token_id, amount| Self {
+            kind,
+            direction: ActivityDirection::of(wallet, from, to),
+            token_address: Some(log.address),
+            from,
+            to,
+            token_id,
+            amount,
+            transaction_hash,
+            block_number,
+            transaction_index,
+            log_index: Some(log_index),
+        };
+
+        let signature = log.topics.first().ok_or("Log has no topics".to_string())?;
+        let event_topic = |event: &str| H256::from(ethers::utils::keccak256(event));
+        if *signature == event_topic("Transfer(address,address,uint256)") {
+            let (from, to) = (topic_address(1)?, topic_address(2)?);
+            return match log.topics.len() {
+                3 => {
+                    let transfer = TransferEvent::from_log(log)?;
+                    Ok(vec![activity(
+                        WalletAssetKind::Erc20,
+                        from,
+                        to,
+                        None,
+                        transfer.value,
+                    )])
+                }
+                4 => {
+                    let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+                    Ok(vec![activity(
+                        WalletAssetKind::Erc721,
+                        from,
+                        to,
+                        Some(token_id),
+                        U256::one(),
+                    )])
+                }
+                n => Err(format!("Unexpected Transfer topic count: {}", n)),
+            };
+        }
+
+        let (from, to) = (topic_address(2)?, topic_address(3)?);
+        let uint_array = ParamType::Array(Box::new(ParamType::Uint(256)));
+        let (ids, amounts) = if *signature
+            == event_topic("TransferSingle(address,address,address,uint256,uint256)")
+        {
+            let tokens = decode(&[ParamType::Uint(256), ParamType::Uint(256)], &log.data)
+                .map_err(|e| format!("Failed to decode TransferSingle: {}", e))?;
+            (vec![tokens[0].clone()], vec![tokens[1].clone()])
+        } else if *signature
+            == event_topic("TransferBatch(address,address,address,uint256[],uint256[])")
+        {
+            let tokens = decode(&[uint_array.clone(), uint_array], &log.data)
+                .map_err(|e| format!("Failed to decode TransferBatch: {}", e))?;
+            let mut tokens = tokens.into_iter();
+            let ids = tokens
+                .next()
+                .and_then(|t| t.into_array())
+                .unwrap_or_default();
+            let amounts = tokens
+                .next()
+                .and_then(|t| t.into_array())
+                .unwrap_or_default();
+            (ids, amounts)
+        } else {
+            return Err(format!("Unsupported transfer event: {:?}", signature));
+        };
+        Ok(ids
+            .into_iter()
+            .zip(amounts)
+            .filter_map(|(id, amount)| Some((id.into_uint()?, amount.into_uint()?)))
+            .map(|(id, amount)| activity(WalletAssetKind::Erc1155, from, to, Some(id), amount))
+            .collect())
+    }
+}
+
 /// Large transfer event structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LargeTransferEvent {
@@ -1135,10 +3154,12 @@ impl LargeTransferEvent {
 /// Large transaction monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LargeTransactionConfig {
+    /// Smallest native value, in wei, reported when no tokens are watched
     pub min_value: ethers::types::U256,
     pub poll_interval_secs: u64,
     pub include_failed: bool,
-    pub watch_tokens: Vec<Address>,
+    /// Tokens to watch, each with the smallest transfer reported in the token's smallest unit
+    pub watch_tokens: std::collections::HashMap<Address, U256>,
 }
 
 impl Default for LargeTransactionConfig {
@@ -1147,12 +3168,338 @@ impl Default for LargeTransactionConfig {
             min_value: ethers::types::U256::from(10u64.pow(18)), // 1 ETH
             poll_interval_secs: 3,
             include_failed: false,
-            watch_tokens: Vec::new(),
+            watch_tokens: std::collections::HashMap::new(),
+        }
+    }
+}
+
+/// Decoded `OwnershipTransferred(address indexed previousOwner, address indexed newOwner)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipTransferEvent {
+    pub contract: Address,
+    pub previous_owner: Address,
+    pub new_owner: Address,
+    /// Ownership was renounced: `new_owner` is the zero address
+    pub renounced: bool,
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+}
+
+impl OwnershipTransferEvent {
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        if log.topics.len() != 3
+            || log.topics[0] != H256::from(ownership_events::ownership_transferred())
+        {
+            return Err("Not an OwnershipTransferred log".to_string());
+        }
+        let new_owner = Address::from(log.topics[2]);
+        Ok(Self {
+            contract: log.address,
+            previous_owner: Address::from(log.topics[1]),
+            new_owner,
+            renounced: new_owner.is_zero(),
+            block_number: log
+                .block_number
+                .ok_or("Missing block number in log".to_string())?
+                .as_u64(),
+            transaction_hash: log
+                .transaction_hash
+                .ok_or("Missing transaction hash in log".to_string())?,
+            log_index: log
+                .log_index
+                .ok_or("Missing log index in log".to_string())?
+                .as_u64(),
+        })
+    }
+}
+
+/// Decoded Governor event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceEvent {
+    pub governor: Address,
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub action: GovernanceAction,
+}
+
+/// Governor proposal lifecycle and voting actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GovernanceAction {
+    ProposalCreated {
+        proposal_id: U256,
+        proposer: Address,
+        targets: Vec<Address>,
+        values: Vec<U256>,
+        signatures: Vec<String>,
+        calldatas: Vec<ethers::types::Bytes>,
+        /// Voting start (block number or timestamp, depending on the governor clock)
+        vote_start: U256,
+        /// Voting end (block number or timestamp, depending on the governor clock)
+        vote_end: U256,
+        description: String,
+    },
+    VoteCast {
+        voter: Address,
+        proposal_id: U256,
+        /// 0 = against, 1 = for, 2 = abstain
+        support: u8,
+        weight: U256,
+        reason: String,
+    },
+    ProposalQueued {
+        proposal_id: U256,
+        /// Earliest execution time (unix seconds)
+        eta: U256,
+    },
+    ProposalExecuted {
+        proposal_id: U256,
+    },
+}
+
+impl GovernanceEvent {
+    pub fn from_log(log: &Log) -> Result<Self, String> {
+        use ethers::abi::{ParamType, Token, decode};
+        let topic0 = log
+            .topics
+            .first()
+            .ok_or("Missing event signature topic".to_string())?;
+        let decode_data = |types: &[ParamType]| {
+            decode(types, &log.data).map_err(|e| format!("Failed to decode event data: {}", e))
+        };
+        let uint = |token: &Token| token.clone().into_uint().ok_or("Expected uint".to_string());
+        let string = |token: &Token| {
+            token
+                .clone()
+                .into_string()
+                .ok_or("Expected string".to_string())
+        };
+        let array = |token: &Token| {
+            token
+                .clone()
+                .into_array()
+                .ok_or("Expected array".to_string())
+        };
+        let action = if topic0.as_bytes() == &governance_events::proposal_created()[..] {
+            let tokens = decode_data(&[
+                ParamType::Uint(256),
+                ParamType::Address,
+                ParamType::Array(Box::new(ParamType::Address)),
+                ParamType::Array(Box::new(ParamType::Uint(256))),
+                ParamType::Array(Box::new(ParamType::String)),
+                ParamType::Array(Box::new(ParamType::Bytes)),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::String,
+            ])?;
+            GovernanceAction::ProposalCreated {
+                proposal_id: uint(&tokens[0])?,
+                proposer: tokens[1]
+                    .clone()
+                    .into_address()
+                    .ok_or("Expected proposer address".to_string())?,
+                targets: array(&tokens[2])?
+                    .into_iter()
+                    .filter_map(|t| t.into_address())
+                    .collect(),
+                values: array(&tokens[3])?
+                    .into_iter()
+                    .filter_map(|t| t.into_uint())
+                    .collect(),
+                signatures: array(&tokens[4])?
+                    .into_iter()
+                    .filter_map(|t| t.into_string())
+                    .collect(),
+                calldatas: array(&tokens[5])?
+                    .into_iter()
+                    .filter_map(|t| t.into_bytes())
+                    .map(ethers::types::Bytes::from)
+                    .collect(),
+                vote_start: uint(&tokens[6])?,
+                vote_end: uint(&tokens[7])?,
+                description: string(&tokens[8])?,
+            }
+        } else if topic0.as_bytes() == &governance_events::vote_cast()[..] {
+            let voter = log
+                .topics
+                .get(1)
+                .map(|t| Address::from_slice(&t.as_bytes()[12..]))
+                .ok_or("Missing voter topic".to_string())?;
+            let tokens = decode_data(&[
+                ParamType::Uint(256),
+                ParamType::Uint(8),
+                ParamType::Uint(256),
+                ParamType::String,
+            ])?;
+            GovernanceAction::VoteCast {
+                voter,
+                proposal_id: uint(&tokens[0])?,
+                support: uint(&tokens[1])?.low_u32() as u8,
+                weight: uint(&tokens[2])?,
+                reason: string(&tokens[3])?,
+            }
+        } else if topic0.as_bytes() == &governance_events::proposal_queued()[..] {
+            let tokens = decode_data(&[ParamType::Uint(256), ParamType::Uint(256)])?;
+            GovernanceAction::ProposalQueued {
+                proposal_id: uint(&tokens[0])?,
+                eta: uint(&tokens[1])?,
+            }
+        } else if topic0.as_bytes() == &governance_events::proposal_executed()[..] {
+            let tokens = decode_data(&[ParamType::Uint(256)])?;
+            GovernanceAction::ProposalExecuted {
+                proposal_id: uint(&tokens[0])?,
+            }
+        } else {
+            return Err(format!("Unknown governance event topic: {:?}", topic0));
+        };
+        Ok(Self {
+            governor: log.address,
+            transaction_hash: log
+                .transaction_hash
+                .ok_or("Missing transaction hash in log".to_string())?,
+            block_number: log
+                .block_number
+                .ok_or("Missing block number in log".to_string())?
+                .as_u64(),
+            log_index: log
+                .log_index
+                .ok_or("Missing log index in log".to_string())?
+                .as_u64(),
+            action,
+        })
+    }
+}
+
+/// A swap decoded from a pool's swap event and the token transfers around it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedSwap {
+    pub pool: Address,
+    /// Protocol identified from the swap event, e.g. `Uniswap V3`
+    pub dex: String,
+    pub token_in: Address,
+    pub amount_in: U256,
+    pub token_out: Address,
+    pub amount_out: U256,
+    pub log_index: Option<u64>,
+}
+
+/// Where a swap transaction sits among the block's transactions on the same pool,
+/// returned by `Trade::get_tx_pool_ordering`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolOrdering {
+    pub tx_hash: H256,
+    pub block_number: u64,
+    pub pool: Address,
+    pub dex: String,
+    /// Index of the transaction in `pool_transactions`
+    pub position: usize,
+    /// A swap in the same direction came earlier in the block (front-run indicator)
+    pub preceded_same_direction: bool,
+    /// A swap in the same direction came later in the block (back-run indicator)
+    pub followed_same_direction: bool,
+    /// Transactions of the block swapping on the pool, in block order, including this one
+    pub pool_transactions: Vec<PoolTransaction>,
+}
+
+impl PoolOrdering {
+    /// Whether one sender swapped on the pool in the same direction before this
+    /// transaction and in the opposite direction after it
+    pub fn is_sandwiched(&self) -> bool {
+        let (before, after) = self.pool_transactions.split_at(self.position);
+        let own_sender = after[0].from;
+        before
+            .iter()
+            .filter(|front| front.same_direction && front.from != own_sender)
+            .any(|front| {
+                after[1..]
+                    .iter()
+                    .any(|back| !back.same_direction && back.from == front.from)
+            })
+    }
+}
+
+/// A transaction swapping on the pool of a `PoolOrdering`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolTransaction {
+    pub tx_hash: H256,
+    pub transaction_index: u64,
+    pub from: Address,
+    /// Sells the same token into the pool as the transaction being ordered
+    pub same_direction: bool,
+}
+
+/// Flavour of an ERC20 `permit` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PermitKind {
+    /// EIP-2612, approving an exact `value`
+    Eip2612,
+    /// DAI-style, approving everything or nothing through an `allowed` flag
+    Dai,
+}
+
+/// A gasless approval found by `TransactionInfo::decode_permits`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermitCall {
+    pub kind: PermitKind,
+    pub owner: Address,
+    pub spender: Address,
+    /// Approved amount; `U256::MAX` or zero for DAI-style permits depending on `allowed`
+    pub value: U256,
+    /// Unix timestamp after which the signature is rejected; zero means no expiry for DAI
+    pub deadline: U256,
+    /// Nonce the signature was made for, only present in DAI-style calldata
+    pub nonce: Option<U256>,
+    /// Found inside a `multicall` rather than as the top-level call
+    pub in_multicall: bool,
+}
+
+/// A transaction log labeled by `TransactionInfo::labeled_logs`
+#[derive(Debug, Clone, Serialize)]
+pub struct LabeledLog {
+    pub address: Address,
+    pub log_index: Option<u64>,
+    /// Role of the emitting contract, `None` when it cannot be told
+    pub role: Option<ContractRole>,
+    /// DEX the event belongs to, for swap and liquidity events
+    pub dex: Option<String>,
+    /// Event name, `None` for events outside the known-event registry
+    pub name: Option<String>,
+    pub params: Vec<DecodedParam>,
+    pub log: Log,
+}
+
+impl LabeledLog {
+    /// One-line rendering, e.g. `Transfer(from=0x.., to=0x.., value=1000)`, or the
+    /// topic of an unknown event
+    pub fn display(&self) -> String {
+        match &self.name {
+            Some(name) => {
+                let params: Vec<String> = self
+                    .params
+                    .iter()
+                    .map(|param| format!("{}={}", param.name, param.display))
+                    .collect();
+                format!("{}({})", name, params.join(", "))
+            }
+            None => format!("Unknown event {:?}", self.log.topics.first()),
         }
     }
 }
 
+/// A profitable swap cycle found by `TransactionInfo::detect_arbitrage`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageInfo {
+    /// Token the cycle starts and ends in
+    pub profit_token: Address,
+    /// Net amount of `profit_token` gained by the initiator
+    pub profit: U256,
+    /// Protocol of each hop, in execution order
+    pub dex_path: Vec<String>,
+    pub swaps: Vec<DecodedSwap>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TransactionInfo {
     pub hash: H256,
     pub from: Address,
@@ -1179,9 +3526,47 @@ pub struct TransactionInfo {
     pub is_success: bool,
     pub total_gas_cost: Option<U256>,
     pub token_decimals_cache: std::collections::HashMap<Address, u8>,
+    /// L1 data fee charged by OP-stack rollups (receipt `l1Fee`), `None` elsewhere
+    #[serde(default)]
+    pub l1_fee: Option<U256>,
 }
 
 impl TransactionInfo {
+    /// Encode as MessagePack for compact storage
+    ///
+    /// Fields are written by name, so records stay readable after fields are added to
+    /// the struct. Decode with `from_bytes`.
+    ///
+    /// # Example
+    /// ```
+    /// let bytes = tx_info.to_bytes()?;
+    /// let restored = TransactionInfo::from_bytes(&bytes)?;
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EvmError> {
+        rmp_serde::to_vec_named(self)
+            .map_err(|e| EvmError::Error(format!("Failed to encode transaction info: {}", e)))
+    }
+
+    /// Decode a `TransactionInfo` written by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EvmError> {
+        rmp_serde::from_slice(bytes).map_err(|e| {
+            EvmError::InvalidInput(format!("Failed to decode transaction info: {}", e))
+        })
+    }
+
+    /// Price per gas the transaction pays in a block with the given base fee
+    ///
+    /// `min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)` for EIP-1559
+    /// transactions, `gas_price` for legacy and access list transactions.
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(max_priority_fee)) => {
+                max_fee.min(base_fee.saturating_add(max_priority_fee))
+            }
+            _ => self.gas_price.unwrap_or_default(),
+        }
+    }
+
     pub fn get_received_token(&self) -> Option<(Address, ethers::types::U256)> {
         if !self.is_success {
             return None;
@@ -1255,12 +3640,26 @@ impl TransactionInfo {
         *self.token_decimals_cache.get(token_address).unwrap_or(&18)
     }
 
-    pub fn getDirection(&self) -> Direction {
-        if (is_quote(&format!("{:?}", &self.get_spent_token_eth().unwrap().0))) {
+    /// Whether the transaction bought or sold a token against a quote token
+    ///
+    /// Spending a quote token (WETH, USDC, ...) is a buy, spending anything else a sell.
+    /// The spent token comes from the first decoded swap, or from the transfers when the
+    /// swap legs cannot be paired. Returns `None` for reverted transactions and for
+    /// transactions without any pool activity, such as plain transfers and approvals.
+    pub fn get_direction(&self) -> Option<Direction> {
+        if !self.is_success {
+            return None;
+        }
+        let spent = match self.decode_swaps().first() {
+            Some(swap) => swap.token_in,
+            None if self.get_liquidity_pool_addresses().is_empty() => return None,
+            None => self.get_spent_token()?.0,
+        };
+        Some(if is_quote_address(&spent) {
             Direction::Buy
         } else {
             Direction::Sell
-        }
+        })
     }
 
     /// get liquidity pool addresses
@@ -1303,16 +3702,14 @@ impl TransactionInfo {
     }
 
     pub fn get_dex_names(&self) -> Vec<String> {
-        use crate::global::{dex_events, get_dex_name_by_address};
+        use crate::global::{dex_events, get_dex_name};
         let mut exchange_names = Vec::new();
         if let Some(to_address) = self.to {
-            let to_str = format!("{:?}", to_address);
-            if let Some(dex_name) = get_dex_name_by_address(&to_str) {
+            if let Some(dex_name) = get_dex_name(&to_address) {
                 exchange_names.push(dex_name.to_string());
             }
         }
-        let from_str = format!("{:?}", self.from);
-        if let Some(dex_name) = get_dex_name_by_address(&from_str) {
+        if let Some(dex_name) = get_dex_name(&self.from) {
             let name = dex_name.to_string();
             if !exchange_names.contains(&name) {
                 exchange_names.push(name);
@@ -1329,8 +3726,7 @@ impl TransactionInfo {
                     dex_identified_by_events.push(dex_name.to_string());
                 }
             }
-            let log_addr_str = format!("{:?}", log.address);
-            if let Some(dex_name) = get_dex_name_by_address(&log_addr_str) {
+            if let Some(dex_name) = get_dex_name(&log.address) {
                 let name = dex_name.to_string();
                 if !exchange_names.contains(&name) {
                     exchange_names.push(name);
@@ -1370,15 +3766,805 @@ impl TransactionInfo {
         exchange_names.dedup();
         exchange_names
     }
+
+    /// Swaps executed by the transaction, in log order
+    ///
+    /// Each swap event (Uniswap V2/V3, Curve, PancakeSwap, Balancer) is paired with the
+    /// ERC20 transfers into and out of the emitting pool that precede it in the same
+    /// transaction, which gives the traded tokens without querying the pool. Swaps
+    /// whose token movements cannot be found (e.g. native ETH legs) are skipped.
+    pub fn decode_swaps(&self) -> Vec<DecodedSwap> {
+        decode_swap_logs(&self.logs)
+    }
+
+    /// Every log of the transaction, decoded where its event is known and tagged with
+    /// the role of the emitting contract, the way block explorers list them
+    ///
+    /// The role comes from the well-known addresses of `global::identify_address`,
+    /// otherwise from the event itself (a `Swap` is emitted by a pool, a `Transfer` by
+    /// a token, ...). Logs of unknown events keep their raw topics and data.
+    ///
+    /// # Example
+    /// ```
+    /// for labeled in tx_info.labeled_logs() {
+    ///     println!("{:?} {:?}: {}", labeled.address, labeled.role, labeled.display());
+    /// }
+    /// ```
+    pub fn labeled_logs(&self) -> Vec<LabeledLog> {
+        self.logs
+            .iter()
+            .map(|log| {
+                let decoded = decode_known_log(log);
+                let role =
+                    identify_address(&log.address).or_else(|| role_from_log(log, decoded.as_ref()));
+                // Compound's `Mint` shares its topic with Uniswap V2, so only pools get a DEX
+                let dex = match role {
+                    Some(ContractRole::Pool) => log
+                        .topics
+                        .first()
+                        .and_then(|topic| {
+                            crate::global::dex_events::identify_dex_by_event(topic.as_bytes())
+                        })
+                        .map(String::from),
+                    _ => None,
+                };
+                LabeledLog {
+                    address: log.address,
+                    log_index: log.log_index.map(|index| index.as_u64()),
+                    role,
+                    dex,
+                    name: decoded.as_ref().map(|event| event.name.clone()),
+                    params: decoded.map(|event| event.params).unwrap_or_default(),
+                    log: log.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Detect a cyclic arbitrage: swaps chained across pools that end in the starting
+    /// token and leave the initiator with more of it than before
+    ///
+    /// The initiator is the sender together with the called contract, since arbitrage
+    /// bots usually keep funds in their own contract. Profit is the net of all
+    /// transfers of the starting token to and from them, so flash loans that are
+    /// borrowed and repaid within the transaction cancel out.
+    ///
+    /// # Example
+    /// ```
+    /// if let Some(arb) = tx_info.detect_arbitrage() {
+    ///     println!("{} profit of {:?} via {:?}", arb.profit, arb.profit_token, arb.dex_path);
+    /// }
+    /// ```
+    pub fn detect_arbitrage(&self) -> Option<ArbitrageInfo> {
+        if !self.is_success {
+            return None;
+        }
+        let swaps = self.decode_swaps();
+        let (first, last) = (swaps.first()?, swaps.last()?);
+        let pools: std::collections::HashSet<Address> = swaps.iter().map(|s| s.pool).collect();
+        if swaps.len() < 2 || pools.len() < 2 || last.token_out != first.token_in {
+            return None;
+        }
+        if swaps
+            .windows(2)
+            .any(|pair| pair[0].token_out != pair[1].token_in)
+        {
+            return None;
+        }
+        let profit_token = first.token_in;
+        let deltas = crate::block::net_token_deltas(&self.logs);
+        let mut initiators = vec![self.from];
+        initiators.extend(self.to.filter(|to| *to != self.from));
+        let profit = initiators
+            .iter()
+            .filter_map(|holder| deltas.get(&(*holder, profit_token)))
+            .fold(I256::zero(), |total, delta| total + *delta);
+        if profit <= I256::zero() {
+            return None;
+        }
+        Some(ArbitrageInfo {
+            profit_token,
+            profit: profit.into_raw(),
+            dex_path: swaps.iter().map(|s| s.dex.clone()).collect(),
+            swaps,
+        })
+    }
+
+    /// Inner calls of a `multicall(bytes[])` or `multicall(uint256 deadline, bytes[])`
+    ///
+    /// Routers, the V3 position manager and aggregators batch their actions this way,
+    /// so the top-level selector hides what the transaction actually does. Returns
+    /// `None` when the input is not one of these multicalls or does not decode.
+    ///
+    /// # Example
+    /// ```
+    /// for call in tx_info.unwrap_multicall().unwrap_or_default() {
+    ///     println!("{:?}", crate::tool::decode_calldata(&call, None).function_name);
+    /// }
+    /// ```
+    pub fn unwrap_multicall(&self) -> Option<Vec<ethers::types::Bytes>> {
+        use ethers::abi::{ParamType, decode};
+        use ethers::utils::id;
+        if self.input.len() < 4 {
+            return None;
+        }
+        let (selector, data) = self.input.split_at(4);
+        let calls = ParamType::Array(Box::new(ParamType::Bytes));
+        let tokens = if selector == id("multicall(bytes[])") {
+            decode(&[calls], data).ok()?
+        } else if selector == id("multicall(uint256,bytes[])") {
+            decode(&[ParamType::Uint(256), calls], data).ok()?
+        } else {
+            return None;
+        };
+        tokens
+            .into_iter()
+            .last()?
+            .into_array()?
+            .into_iter()
+            .map(|call| call.into_bytes().map(Into::into))
+            .collect()
+    }
+
+    /// Decode EIP-2612 and DAI-style `permit` calls in the calldata, including calls
+    /// wrapped in a `multicall`
+    ///
+    /// A permit called directly targets the token in `to`; inside a router multicall
+    /// the token is not part of the permit arguments. Returns an empty vec when the
+    /// transaction carries no permit.
+    ///
+    /// # Example
+    /// ```
+    /// for permit in tx_info.decode_permits() {
+    ///     println!("{:?} allowed {:?} to spend {}", permit.owner, permit.spender, permit.value);
+    /// }
+    /// ```
+    pub fn decode_permits(&self) -> Vec<PermitCall> {
+        let top_level = std::iter::once((self.input.clone(), false));
+        let inner = self
+            .unwrap_multicall()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| (call.to_vec(), true));
+        top_level
+            .chain(inner)
+            .filter_map(|(input, in_multicall)| decode_permit(&input, in_multicall))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use evm_client::EvmType;
 
-    use crate::{Evm, trade::Trade};
+    use crate::{
+        Evm,
+        trade::{Trade, TransactionWithReceipt},
+    };
+    use ethers::types::{Address, Transaction, U64};
     use std::{sync::Arc, time::Duration};
 
+    #[test]
+    fn test_rank_by_share() {
+        use super::rank_by_share;
+        use ethers::types::U256;
+        let volume = [
+            ("Uniswap V2".to_string(), U256::from(100)),
+            ("Uniswap V3".to_string(), U256::from(300)),
+            ("Curve".to_string(), U256::from(100)),
+        ];
+        assert_eq!(
+            rank_by_share(volume.into_iter().collect()),
+            vec![
+                ("Uniswap V3".to_string(), 0.6),
+                ("Curve".to_string(), 0.2),
+                ("Uniswap V2".to_string(), 0.2),
+            ]
+        );
+        assert!(rank_by_share(Default::default()).is_empty());
+        // Raw amounts of 18-decimal tokens are far beyond u64::MAX (about 1.3e30 here)
+        let large = [
+            ("Uniswap V2".to_string(), U256::one() << 100),
+            ("Uniswap V3".to_string(), U256::from(3) << 100),
+        ];
+        assert_eq!(
+            rank_by_share(large.into_iter().collect()),
+            vec![
+                ("Uniswap V3".to_string(), 0.75),
+                ("Uniswap V2".to_string(), 0.25)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_labeled_logs() {
+        use super::{TransactionInfo, transfer_log};
+        use crate::{
+            global::{ETH_ETHEREUM_MAINNET, dex_events, lending_events},
+            types::ContractRole,
+        };
+        use ethers::{
+            abi::{Token, encode},
+            types::{H256, Log},
+        };
+        let weth: Address = ETH_ETHEREUM_MAINNET.parse().unwrap();
+        let pair = Address::repeat_byte(0x11);
+        let market = Address::repeat_byte(0x22);
+        let sender = H256::from(Address::repeat_byte(0x33));
+        let amounts = encode(&[Token::Uint(1.into()), Token::Uint(2.into())]);
+        let tx_info = TransactionInfo {
+            logs: vec![
+                transfer_log(
+                    weth,
+                    Address::repeat_byte(0x33),
+                    Address::repeat_byte(0x33),
+                    5.into(),
+                ),
+                Log {
+                    address: pair,
+                    topics: vec![H256::from(dex_events::uniswap_v2_mint()), sender],
+                    data: amounts.clone().into(),
+                    ..Default::default()
+                },
+                Log {
+                    address: market,
+                    topics: vec![H256::from(lending_events::compound_mint())],
+                    data: encode(&[
+                        Token::Address(Address::repeat_byte(0x33)),
+                        Token::Uint(1.into()),
+                        Token::Uint(2.into()),
+                    ])
+                    .into(),
+                    ..Default::default()
+                },
+                Log {
+                    address: pair,
+                    topics: vec![H256::repeat_byte(0xff)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let labeled = tx_info.labeled_logs();
+        let roles: Vec<Option<ContractRole>> = labeled.iter().map(|log| log.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                Some(ContractRole::Token),
+                Some(ContractRole::Pool),
+                Some(ContractRole::LendingMarket),
+                None,
+            ]
+        );
+        assert_eq!(labeled[0].name.as_deref(), Some("Transfer"));
+        assert_eq!(labeled[1].dex.as_deref(), Some("Uniswap V2"));
+        assert_eq!(labeled[2].params.len(), 3);
+        assert!(labeled[2].dex.is_none());
+        assert!(labeled[3].name.is_none());
+    }
+
+    #[test]
+    fn test_success_rate() {
+        use super::SuccessRateReport;
+        let mut report = SuccessRateReport {
+            pending: 2,
+            ..Default::default()
+        };
+        assert_eq!(report.success_rate(), None);
+        report.successful = 3;
+        report.reverted = 1;
+        assert_eq!(report.success_rate(), Some(75.0));
+    }
+
+    #[test]
+    fn test_apply_slippage_and_tax() {
+        use super::apply_slippage_and_tax;
+        use ethers::types::U256;
+
+        let quote = U256::from(1_000_000u64);
+        assert_eq!(
+            apply_slippage_and_tax(quote, 100, 500).unwrap(),
+            U256::from(940_500u64)
+        );
+        assert_eq!(apply_slippage_and_tax(quote, 0, 0).unwrap(), quote);
+        assert!(apply_slippage_and_tax(quote, 10_001, 0).is_err());
+    }
+
+    #[test]
+    fn test_write_transfer() {
+        use super::{ExportFormat, TransferEvent, write_transfer};
+        use ethers::types::{H256, U256};
+
+        let transfer = TransferEvent {
+            from: Address::repeat_byte(0x11),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(1_500u64),
+            transaction_hash: H256::repeat_byte(0xab),
+            block_number: 18_000_000,
+            log_index: 7,
+        };
+        let mut csv = Vec::new();
+        write_transfer(&mut csv, ExportFormat::Csv, &transfer).unwrap();
+        let row = String::from_utf8(csv).unwrap();
+        assert_eq!(row.matches(',').count(), 5);
+        assert!(row.starts_with("18000000,0xabab"));
+        assert!(row.ends_with(",1500\n"));
+
+        let mut jsonl = Vec::new();
+        write_transfer(&mut jsonl, ExportFormat::JsonLines, &transfer).unwrap();
+        let line = String::from_utf8(jsonl).unwrap();
+        assert_eq!(line.lines().count(), 1);
+        let parsed: TransferEvent = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(parsed.value, transfer.value);
+        assert_eq!(parsed.log_index, 7);
+    }
+
+    #[test]
+    fn test_unwrap_multicall() {
+        use super::TransactionInfo;
+        use ethers::abi::{Token, encode};
+        use ethers::types::U256;
+        use ethers::utils::id;
+
+        let inner = vec![vec![0x12, 0x34, 0x56, 0x78], vec![0xab; 36]];
+        let calls = Token::Array(inner.iter().cloned().map(Token::Bytes).collect());
+        let mut input = id("multicall(uint256,bytes[])").to_vec();
+        input.extend(encode(&[Token::Uint(U256::from(1_700_000_000u64)), calls]));
+        let tx_info = TransactionInfo {
+            input,
+            ..Default::default()
+        };
+        let unwrapped: Vec<Vec<u8>> = tx_info
+            .unwrap_multicall()
+            .unwrap()
+            .into_iter()
+            .map(|call| call.to_vec())
+            .collect();
+        assert_eq!(unwrapped, inner);
+
+        let plain = TransactionInfo {
+            input: id("transfer(address,uint256)").to_vec(),
+            ..Default::default()
+        };
+        assert!(plain.unwrap_multicall().is_none());
+    }
+
+    #[test]
+    fn test_decode_permits() {
+        use super::{PermitKind, TransactionInfo};
+        use ethers::abi::{Token, encode};
+        use ethers::types::U256;
+        use ethers::utils::id;
+
+        let owner = Address::repeat_byte(0x01);
+        let spender = Address::repeat_byte(0x02);
+        let signature = [
+            Token::Uint(U256::from(27u8)),
+            Token::FixedBytes(vec![0; 32]),
+            Token::FixedBytes(vec![0; 32]),
+        ];
+        let mut eip2612 =
+            id("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)").to_vec();
+        let mut args = vec![
+            Token::Address(owner),
+            Token::Address(spender),
+            Token::Uint(U256::from(500u64)),
+            Token::Uint(U256::from(1_700_000_000u64)),
+        ];
+        args.extend(signature.clone());
+        eip2612.extend(encode(&args));
+        let mut dai =
+            id("permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)").to_vec();
+        let mut args = vec![
+            Token::Address(owner),
+            Token::Address(spender),
+            Token::Uint(U256::from(3u64)),
+            Token::Uint(U256::zero()),
+            Token::Bool(true),
+        ];
+        args.extend(signature);
+        dai.extend(encode(&args));
+
+        let direct = TransactionInfo {
+            input: eip2612.clone(),
+            ..Default::default()
+        };
+        let permits = direct.decode_permits();
+        assert_eq!(permits.len(), 1);
+        assert_eq!(permits[0].kind, PermitKind::Eip2612);
+        assert_eq!(permits[0].value, U256::from(500u64));
+        assert!(!permits[0].in_multicall);
+
+        let mut input = id("multicall(bytes[])").to_vec();
+        input.extend(encode(&[Token::Array(vec![
+            Token::Bytes(dai),
+            Token::Bytes(eip2612),
+        ])]));
+        let batched = TransactionInfo {
+            input,
+            ..Default::default()
+        };
+        let permits = batched.decode_permits();
+        assert_eq!(permits.len(), 2);
+        assert_eq!(permits[0].kind, PermitKind::Dai);
+        assert_eq!(permits[0].value, U256::MAX);
+        assert_eq!(permits[0].nonce, Some(U256::from(3u64)));
+        assert!(
+            permits
+                .iter()
+                .all(|permit| permit.in_multicall && permit.owner == owner)
+        );
+
+        let plain = TransactionInfo {
+            input: id("transfer(address,uint256)").to_vec(),
+            ..Default::default()
+        };
+        assert!(plain.decode_permits().is_empty());
+    }
+
+    #[test]
+    fn test_transaction_info_bytes_round_trip() {
+        use super::TransactionInfo;
+        use ethers::types::{Bytes, H256, Log, TransactionReceipt, U256};
+
+        let hash = H256::repeat_byte(0x42);
+        let log = Log {
+            address: Address::repeat_byte(0x70),
+            topics: vec![H256::repeat_byte(0xdd), H256::repeat_byte(0x01)],
+            data: Bytes::from(vec![0xff; 32]),
+            transaction_hash: Some(hash),
+            log_index: Some(U256::from(3)),
+            ..Default::default()
+        };
+        let info = TransactionInfo {
+            hash,
+            from: Address::repeat_byte(0x01),
+            to: Some(Address::repeat_byte(0x02)),
+            value: U256::MAX,
+            gas_price: Some(U256::exp10(10)),
+            input: vec![0xa9, 0x05, 0x9c, 0xbb],
+            block_number: Some(18_000_000),
+            raw_transaction: Transaction {
+                hash,
+                value: U256::MAX,
+                ..Default::default()
+            },
+            receipt: Some(TransactionReceipt {
+                transaction_hash: hash,
+                logs: vec![log.clone()],
+                status: Some(U64::from(1)),
+                ..Default::default()
+            }),
+            logs: vec![log],
+            token_decimals_cache: [(Address::repeat_byte(0x70), 6u8)].into_iter().collect(),
+            l1_fee: Some(U256::from(1234)),
+            ..Default::default()
+        };
+        let bytes = info.to_bytes().unwrap();
+        assert_eq!(TransactionInfo::from_bytes(&bytes).unwrap(), info);
+        assert!(TransactionInfo::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_effective_gas_price() {
+        use super::TransactionInfo;
+        use ethers::types::U256;
+
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+        let base_fee = gwei(30);
+        let legacy = TransactionInfo {
+            gas_price: Some(gwei(50)),
+            ..Default::default()
+        };
+        assert_eq!(legacy.effective_gas_price(base_fee), gwei(50));
+        // base fee + tip exceeds the max fee: capped at max fee
+        let capped = TransactionInfo {
+            gas_price: Some(gwei(31)),
+            max_fee_per_gas: Some(gwei(31)),
+            max_priority_fee_per_gas: Some(gwei(2)),
+            ..Default::default()
+        };
+        assert_eq!(capped.effective_gas_price(base_fee), gwei(31));
+        // max fee leaves room: base fee + full tip
+        let uncapped = TransactionInfo {
+            max_fee_per_gas: Some(gwei(100)),
+            max_priority_fee_per_gas: Some(gwei(2)),
+            ..Default::default()
+        };
+        assert_eq!(uncapped.effective_gas_price(base_fee), gwei(32));
+    }
+
+    #[test]
+    fn test_sandwich_profit() {
+        use super::{DecodedSwap, sandwich_profit};
+        use ethers::types::{I256, U256};
+
+        let (weth, token, pool) = (
+            Address::repeat_byte(0xee),
+            Address::repeat_byte(0x70),
+            Address::repeat_byte(0xa2),
+        );
+        let swap = |token_in, amount_in: u64, token_out, amount_out: u64| DecodedSwap {
+            pool,
+            dex: "Uniswap V2".to_string(),
+            token_in,
+            amount_in: U256::from(amount_in),
+            token_out,
+            amount_out: U256::from(amount_out),
+            log_index: None,
+        };
+        let front = swap(weth, 1_000, token, 50_000);
+        let back = swap(token, 50_000, weth, 1_060);
+        assert_eq!(
+            sandwich_profit(&front, &back, U256::from(20)),
+            I256::from(40)
+        );
+        // half of the tokens kept, valued at the back-run price
+        let back_half = swap(token, 25_000, weth, 530);
+        assert_eq!(
+            sandwich_profit(&front, &back_half, U256::zero()),
+            I256::from(60)
+        );
+        // Forged amounts saturate instead of overflowing or turning negative
+        let forged = DecodedSwap {
+            amount_out: U256::MAX,
+            ..back_half.clone()
+        };
+        assert_eq!(
+            sandwich_profit(&front, &forged, U256::zero()),
+            I256::MAX - I256::from(1_000)
+        );
+    }
+
+    #[test]
+    fn test_detect_arbitrage() {
+        use super::{TransactionInfo, transfer_log};
+        use crate::global::dex_events;
+        use ethers::types::{H256, Log, U256};
+
+        let transfer = |token: Address, from: Address, to: Address, value: u64| {
+            transfer_log(token, from, to, value.into())
+        };
+        let swap = |pool: Address, event: [u8; 32]| Log {
+            address: pool,
+            topics: vec![H256::from(event)],
+            ..Default::default()
+        };
+        let (weth, token) = (Address::repeat_byte(0xee), Address::repeat_byte(0x70));
+        let (pool_v2, pool_v3) = (Address::repeat_byte(0xa2), Address::repeat_byte(0xa3));
+        let (searcher, bot) = (Address::repeat_byte(0x01), Address::repeat_byte(0xb0));
+        let tx_logs = vec![
+            // V2 hop: WETH in, TOKEN out
+            transfer(weth, bot, pool_v2, 100),
+            transfer(token, pool_v2, bot, 500),
+            swap(pool_v2, dex_events::uniswap_v2_swap()),
+            // V3 hop: WETH paid out first, TOKEN pulled in the callback
+            transfer(weth, pool_v3, bot, 110),
+            transfer(token, bot, pool_v3, 500),
+            swap(pool_v3, dex_events::uniswap_v3_swap()),
+        ];
+
+        let tx_info = TransactionInfo {
+            from: searcher,
+            to: Some(bot),
+            is_success: true,
+            logs: tx_logs,
+            ..Default::default()
+        };
+        let arb = tx_info.detect_arbitrage().unwrap();
+        assert_eq!(arb.profit_token, weth);
+        assert_eq!(arb.profit, U256::from(10));
+        assert_eq!(arb.dex_path, vec!["Uniswap V2", "Uniswap V3"]);
+        assert_eq!(arb.swaps[1].token_in, token);
+
+        let one_hop = TransactionInfo {
+            logs: tx_info.logs[..3].to_vec(),
+            ..tx_info
+        };
+        assert!(one_hop.detect_arbitrage().is_none());
+    }
+
+    #[test]
+    fn test_pool_ordering() {
+        use super::{pool_ordering, transfer_log};
+        use crate::global::dex_events;
+        use ethers::types::{H256, Log, TransactionReceipt};
+
+        let (weth, token) = (Address::repeat_byte(0xee), Address::repeat_byte(0x70));
+        let pool = Address::repeat_byte(0xa2);
+        let (attacker, victim) = (Address::repeat_byte(0xb0), Address::repeat_byte(0x01));
+        let transfer =
+            |token: Address, from: Address, to: Address| transfer_log(token, from, to, 100.into());
+        // A swap of `sender` selling `sold` for the other token of the pool
+        let receipt = |index: u64, sender: Address, sold: Address| {
+            let bought = if sold == weth { token } else { weth };
+            TransactionReceipt {
+                transaction_hash: H256::from_low_u64_be(index + 1),
+                transaction_index: U64::from(index),
+                from: sender,
+                logs: vec![
+                    transfer(sold, sender, pool),
+                    transfer(bought, pool, sender),
+                    Log {
+                        address: pool,
+                        topics: vec![H256::from(dex_events::uniswap_v2_swap())],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }
+        };
+        let receipts = vec![
+            receipt(2, attacker, token),
+            receipt(0, attacker, weth),
+            receipt(1, victim, weth),
+            TransactionReceipt {
+                transaction_index: U64::from(3),
+                ..Default::default()
+            },
+        ];
+        let ordering = pool_ordering(H256::from_low_u64_be(2), 10, receipts).unwrap();
+        assert_eq!(ordering.pool, pool);
+        assert_eq!(ordering.position, 1);
+        assert_eq!(ordering.pool_transactions.len(), 3);
+        assert!(ordering.preceded_same_direction);
+        assert!(!ordering.followed_same_direction);
+        assert!(ordering.is_sandwiched());
+        assert!(pool_ordering(H256::from_low_u64_be(9), 10, Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_is_reverted() {
+        use super::is_reverted;
+        use ethers::types::TransactionReceipt;
+        let receipt = |status: Option<u64>| TransactionReceipt {
+            status: status.map(U64::from),
+            ..Default::default()
+        };
+        assert!(is_reverted(Some(&receipt(Some(0)))));
+        assert!(!is_reverted(Some(&receipt(Some(1)))));
+        assert!(!is_reverted(Some(&receipt(None))));
+        assert!(!is_reverted(None));
+    }
+
+    #[test]
+    fn test_empty_buckets() {
+        use super::empty_buckets;
+        let buckets = empty_buckets(100, 349, 100);
+        let ranges: Vec<(u64, u64)> = buckets
+            .iter()
+            .map(|bucket| (bucket.start_block, bucket.end_block))
+            .collect();
+        assert_eq!(ranges, vec![(100, 199), (200, 299), (300, 349)]);
+        assert!(buckets.iter().all(|bucket| bucket.volume.is_zero()));
+        assert_eq!(empty_buckets(5, 5, 10).len(), 1);
+        assert_eq!(empty_buckets(0, u64::MAX, u64::MAX).len(), 2);
+    }
+
+    #[test]
+    fn test_get_direction() {
+        use super::{TransactionInfo, transfer_log};
+        use crate::{global, types::Direction};
+        use ethers::types::{H256, Log};
+
+        let weth: Address = global::ETH_ETHEREUM_MAINNET.parse().unwrap();
+        let (token, pool) = (Address::repeat_byte(0x70), Address::repeat_byte(0xa2));
+        let trader = Address::repeat_byte(0x01);
+        let transfer = |token: Address, from: Address, to: Address| {
+            transfer_log(token, from, to, 1_000.into())
+        };
+        let swap_log = Log {
+            address: pool,
+            topics: vec![H256::from(global::dex_events::uniswap_v2_swap())],
+            ..Default::default()
+        };
+        let buy = TransactionInfo {
+            is_success: true,
+            logs: vec![
+                transfer(weth, trader, pool),
+                transfer(token, pool, trader),
+                swap_log.clone(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(buy.get_direction(), Some(Direction::Buy));
+        let sell = TransactionInfo {
+            logs: vec![
+                transfer(token, trader, pool),
+                transfer(weth, pool, trader),
+                swap_log,
+            ],
+            ..buy.clone()
+        };
+        assert_eq!(sell.get_direction(), Some(Direction::Sell));
+
+        let bare_transfer = TransactionInfo {
+            is_success: true,
+            logs: vec![transfer(token, trader, Address::repeat_byte(0x02))],
+            ..Default::default()
+        };
+        assert_eq!(bare_transfer.get_direction(), None);
+        let reverted = TransactionInfo {
+            is_success: false,
+            ..buy
+        };
+        assert_eq!(reverted.get_direction(), None);
+    }
+
+    fn transaction(from: Address, to: Option<Address>, block: u64, index: u64) -> Transaction {
+        Transaction {
+            from,
+            to,
+            block_number: Some(U64::from(block)),
+            transaction_index: Some(U64::from(index)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_involves_both_requires_sender_and_recipient() {
+        let a = Address::repeat_byte(0xaa);
+        let b = Address::repeat_byte(0xbb);
+        let other = Address::repeat_byte(0xcc);
+        let between = |from, to| Trade::involves_both(&transaction(from, to, 1, 0), a, b);
+        assert!(between(a, Some(b)));
+        assert!(between(b, Some(a)));
+        // Involving only one of the two addresses is not enough
+        assert!(!between(a, Some(other)));
+        assert!(!between(other, Some(b)));
+        assert!(!between(a, None));
+    }
+
+    #[test]
+    fn test_sort_by_position() {
+        let a = Address::repeat_byte(0xaa);
+        let mut transactions: Vec<TransactionWithReceipt> = [(12, 3), (10, 7), (12, 0), (10, 2)]
+            .into_iter()
+            .map(|(block, index)| TransactionWithReceipt {
+                transaction: transaction(a, Some(a), block, index),
+                receipt: None,
+            })
+            .collect();
+        Trade::sort_by_position(&mut transactions);
+        let order: Vec<(u64, u64)> = transactions
+            .iter()
+            .map(|t| {
+                (
+                    t.transaction.block_number.unwrap().as_u64(),
+                    t.transaction.transaction_index.unwrap().as_u64(),
+                )
+            })
+            .collect();
+        assert_eq!(order, vec![(10, 2), (10, 7), (12, 0), (12, 3)]);
+    }
+
+    #[test]
+    fn test_next_scan_range() {
+        use super::next_scan_range;
+        assert_eq!(next_scan_range(100, 5000, 1000), (101, 1100));
+        assert_eq!(next_scan_range(100, 150, 1000), (101, 150));
+        assert_eq!(next_scan_range(100, 5000, 0), (101, 101));
+        // Near u64::MAX the window is clamped instead of overflowing
+        assert_eq!(
+            next_scan_range(u64::MAX - 10, u64::MAX, u64::MAX),
+            (u64::MAX - 9, u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_live_watchers_prunes_stopped_watchers() {
+        use super::{ListenerHealth, live_watchers};
+        let running = Arc::new(ListenerHealth::new("running", 10));
+        let stopped = Arc::new(ListenerHealth::new("stopped", 10));
+        let mut watchers = vec![Arc::downgrade(&running), Arc::downgrade(&stopped)];
+        assert_eq!(live_watchers(&mut watchers).len(), 2);
+        drop(stopped);
+        let live = live_watchers(&mut watchers);
+        assert_eq!(watchers.len(), 1);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].name(), "running");
+    }
+
     #[tokio::test]
     async fn test_get_transaction_by_tx() {
         let evm = Evm::new(evm_client::EvmType::ETHEREUM_MAINNET)
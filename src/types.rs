@@ -1,4 +1,9 @@
+use ethers::contract::ContractError;
+use ethers::providers::{Http, Provider, ProviderError, RpcError as _};
+use ethers::types::{Address, Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum EvmError {
@@ -16,6 +21,20 @@ pub enum EvmError {
     CalculationError(String),
     MempoolError(String),
     Error(String),
+    /// A read still failed after the given number of retries, see `Evm::with_retry`
+    RetriesExhausted(u32, Box<EvmError>),
+    /// An export failed after writing the given number of records, see
+    /// `Trade::export_transfers_to_writer`
+    ExportInterrupted(u64, Box<EvmError>),
+    /// Indexing failed after every block up to the given one was processed, see
+    /// `ERC20Service::index_all_transfers`
+    IndexingInterrupted(u64, Box<EvmError>),
+    /// A failure the node or network reported as temporary, see `EvmError::from_provider`.
+    /// Only seen by reads passed to `Evm::read_with_retry`, which unwraps it.
+    Transient(Box<EvmError>),
+    /// A `Transient` failure where the endpoint could not be reached or timed out, which
+    /// also makes an `Evm` built by `Evm::with_rpc_urls` fail over to its next endpoint
+    Unreachable(Box<EvmError>),
 }
 
 impl fmt::Display for EvmError {
@@ -30,19 +49,450 @@ impl fmt::Display for EvmError {
             EvmError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             EvmError::IOError(msg) => write!(f, "IO Error: {}", msg),
             EvmError::AaveError(msg) => write!(f, "Aave Error: {}", msg),
-            EvmError::ListenerError(msg) => write!(f, "Aave Error: {}", msg),
-            EvmError::ProviderError(msg) => write!(f, "Aave Error: {}", msg),
-            EvmError::CalculationError(msg) => write!(f, "Aave Error: {}", msg),
-            EvmError::MempoolError(msg) => write!(f, "Aave Error: {}", msg),
-            EvmError::Error(msg) => write!(f, "Aave Error: {}", msg),
+            EvmError::ListenerError(msg) => write!(f, "Listener error: {}", msg),
+            EvmError::ProviderError(msg) => write!(f, "Provider error: {}", msg),
+            EvmError::CalculationError(msg) => write!(f, "Calculation error: {}", msg),
+            EvmError::MempoolError(msg) => write!(f, "Mempool error: {}", msg),
+            EvmError::Error(msg) => write!(f, "Error: {}", msg),
+            EvmError::RetriesExhausted(retries, e) => {
+                write!(f, "{} (gave up after {} retries)", e, retries)
+            }
+            EvmError::ExportInterrupted(written, e) => {
+                write!(f, "{} ({} records written)", e, written)
+            }
+            EvmError::IndexingInterrupted(last_block, e) => {
+                write!(f, "{} (indexed up to block {})", e, last_block)
+            }
+            EvmError::Transient(e) | EvmError::Unreachable(e) => write!(f, "{}", e),
         }
     }
 }
 
+impl EvmError {
+    /// Stable, machine-readable name of the error category, e.g. `"rpc"`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EvmError::ConfigError(_) => "config",
+            EvmError::ConnectionError(_) => "connection",
+            EvmError::RpcError(_) => "rpc",
+            EvmError::WalletError(_) => "wallet",
+            EvmError::TransactionError(_) => "transaction",
+            EvmError::ContractError(_) => "contract",
+            EvmError::InvalidInput(_) => "invalid_input",
+            EvmError::IOError(_) => "io",
+            EvmError::AaveError(_) => "aave",
+            EvmError::ListenerError(_) => "listener",
+            EvmError::ProviderError(_) => "provider",
+            EvmError::CalculationError(_) => "calculation",
+            EvmError::MempoolError(_) => "mempool",
+            EvmError::Error(_) => "other",
+            EvmError::RetriesExhausted(_, e)
+            | EvmError::ExportInterrupted(_, e)
+            | EvmError::IndexingInterrupted(_, e)
+            | EvmError::Transient(e)
+            | EvmError::Unreachable(e) => e.kind(),
+        }
+    }
+
+    /// Number of retries spent before this error was returned, 0 if none were made
+    pub fn retries(&self) -> u32 {
+        match self {
+            EvmError::RetriesExhausted(retries, _) => *retries,
+            _ => 0,
+        }
+    }
+
+    /// Whether the error is a temporary node or network condition worth retrying
+    pub fn is_transient(&self) -> bool {
+        matches!(self, EvmError::Transient(_) | EvmError::Unreachable(_))
+    }
+
+    /// Whether the endpoint could not be reached or timed out
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, EvmError::Unreachable(_))
+    }
+
+    /// The error itself, without the `Transient` or `Unreachable` marker
+    pub fn into_inner(self) -> EvmError {
+        match self {
+            EvmError::Transient(e) | EvmError::Unreachable(e) => *e,
+            e => e,
+        }
+    }
+
+    /// `RpcError` for a failed provider request, see `from_provider`
+    pub(crate) fn rpc(context: &str, e: ProviderError) -> Self {
+        let error = EvmError::RpcError(format!("{}: {}", context, e));
+        Self::from_provider(&e, error)
+    }
+
+    /// Error for a failed read-only contract call
+    ///
+    /// A request that failed before the node answered is an `RpcError`, anything the
+    /// node answered (a revert) or undecodable return data a `ContractError`; both are
+    /// marked like `from_provider` so `Evm::read_with_retry` can retry or fail over.
+    pub(crate) fn contract(context: &str, e: &ContractError<Provider<Http>>) -> Self {
+        let message = format!("{}: {}", context, e);
+        match e.as_middleware_error().or(e.as_provider_error()) {
+            Some(provider_error) if provider_error.as_error_response().is_none() => {
+                Self::from_provider(provider_error, EvmError::RpcError(message))
+            }
+            Some(provider_error) => {
+                Self::from_provider(provider_error, EvmError::ContractError(message))
+            }
+            None => EvmError::ContractError(message),
+        }
+    }
+
+    /// Mark `error`, built from the provider failure `e`, as `Unreachable` when the
+    /// request timed out or could not connect, and as `Transient` when the node or a
+    /// gateway in front of it reported a rate limit or a temporary outage
+    ///
+    /// The classification follows ethers' `HttpRateLimitRetryPolicy`: HTTP 429 and 5xx
+    /// statuses, rate-limit JSON-RPC errors (429, -32005, -32016 "rate limit", "header
+    /// not found"), and, since the `Http` transport does not check the status, non-JSON
+    /// bodies of gateway and rate-limit responses.
+    pub(crate) fn from_provider(e: &ProviderError, error: EvmError) -> Self {
+        let transient = match e {
+            ProviderError::HTTPError(e) if e.is_timeout() || e.is_connect() => {
+                return EvmError::Unreachable(Box::new(error));
+            }
+            ProviderError::HTTPError(e) => e
+                .status()
+                .is_some_and(|status| status.as_u16() == 429 || status.is_server_error()),
+            e if e.as_serde_error().is_some() => {
+                let text = e.to_string();
+                text.split_once("Response: ")
+                    .is_some_and(|(_, body)| is_retryable_body(body))
+            }
+            e => e
+                .as_error_response()
+                .is_some_and(|response| is_retryable_response(response.code, &response.message)),
+        };
+        if transient {
+            EvmError::Transient(Box::new(error))
+        } else {
+            error
+        }
+    }
+}
+
+/// JSON-RPC error a node returns when rate limited or briefly out of sync
+fn is_retryable_response(code: i64, message: &str) -> bool {
+    matches!(code, 429 | -32005)
+        || (code == -32016 && message.contains("rate limit"))
+        || matches!(
+            message,
+            "header not found" | "daily request count exceeded, request rate limited"
+        )
+}
+
+/// Body of a response that is not valid JSON-RPC, from a rate limit or a failing gateway
+fn is_retryable_body(body: &str) -> bool {
+    #[derive(Deserialize)]
+    struct Response {
+        error: ethers::providers::JsonRpcError,
+    }
+    if let Ok(response) = serde_json::from_str::<Response>(body) {
+        return is_retryable_response(response.error.code, &response.error.message);
+    }
+    let body = body.to_ascii_lowercase();
+    [
+        "429",
+        "502",
+        "503",
+        "504",
+        "too many requests",
+        "rate limit",
+        "bad gateway",
+        "service unavailable",
+        "gateway timeout",
+    ]
+    .iter()
+    .any(|marker| body.contains(marker))
+}
+
 impl std::error::Error for EvmError {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     Buy,
     Sell,
 }
+
+/// Part a contract plays in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractRole {
+    /// ERC20, ERC721 or ERC1155 token
+    Token,
+    /// DEX pool or pair (including the Balancer vault)
+    Pool,
+    /// DEX router or aggregator
+    Router,
+    /// DEX factory deploying pools
+    Factory,
+    /// ERC-4626 tokenized vault
+    Vault,
+    /// Aave pool or Compound market
+    LendingMarket,
+}
+
+/// A single call frame returned by `trace_filter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceCall {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub gas_used: Option<U256>,
+    pub input: Bytes,
+    pub output: Option<Bytes>,
+    /// `call`, `delegatecall`, `staticcall` or `callcode`
+    pub call_type: String,
+    /// Position of the frame in the call tree, empty for the top-level call
+    pub trace_address: Vec<usize>,
+    pub transaction_hash: Option<H256>,
+    pub block_number: u64,
+    pub error: Option<String>,
+}
+
+/// Exponential backoff for retried reads, see `Evm::with_retry`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each following one
+    pub base_delay: Duration,
+    /// Upper bound of a single delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before retry number `retry` (0-based), with up to 50% random jitter removed
+    /// so clients that failed together do not retry together
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos())
+            .unwrap_or(0);
+        let jitter = f64::from(seed % 1_000) / 2_000.0;
+        delay.mul_f64(1.0 - jitter)
+    }
+}
+
+/// Result of `Evm::health_check`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Not syncing and on the expected chain
+    pub healthy: bool,
+    /// Round trip of `eth_blockNumber`
+    pub latency: Duration,
+    pub block_number: u64,
+    pub chain_id: u64,
+    pub expected_chain_id: u64,
+    pub syncing: bool,
+}
+
+/// Fee suggestion for the next block, see `Evm::recommended_fees`
+///
+/// The EIP-1559 fields are `None` on chains without a base fee; `legacy_gas_price` is
+/// always set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxFees {
+    /// Base fee of the latest block
+    pub base_fee: Option<U256>,
+    /// Base fee the next block will have, derived from the latest block's gas usage
+    pub next_base_fee: Option<U256>,
+    /// Suggested `max_priority_fee_per_gas`
+    pub priority_fee: Option<U256>,
+    /// Suggested `max_fee_per_gas`, leaving room for the base fee to keep rising
+    pub max_fee_per_gas: Option<U256>,
+    /// Suggested `gas_price` for legacy transactions
+    pub legacy_gas_price: U256,
+}
+
+/// Hash a relay assigns to an accepted bundle
+pub type BundleHash = H256;
+
+/// Parameters of an `eth_sendBundle` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleParams {
+    /// Signed raw transactions, executed in order
+    pub txs: Vec<Bytes>,
+    /// Block the bundle targets, hex encoded on the wire
+    #[serde(
+        serialize_with = "serialize_block_number",
+        deserialize_with = "deserialize_block_number"
+    )]
+    pub block_number: u64,
+    /// Earliest block timestamp the bundle is valid for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_timestamp: Option<u64>,
+    /// Latest block timestamp the bundle is valid for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_timestamp: Option<u64>,
+    /// Transactions allowed to revert without invalidating the bundle
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub reverting_tx_hashes: Vec<H256>,
+}
+
+impl BundleParams {
+    pub fn new(txs: Vec<Bytes>, block_number: u64) -> Self {
+        Self {
+            txs,
+            block_number,
+            min_timestamp: None,
+            max_timestamp: None,
+            reverting_tx_hashes: Vec::new(),
+        }
+    }
+
+    /// Restrict the bundle to blocks with a timestamp in `min..=max`
+    pub fn timestamps(mut self, min: Option<u64>, max: Option<u64>) -> Self {
+        self.min_timestamp = min;
+        self.max_timestamp = max;
+        self
+    }
+
+    /// Allow the given transactions to revert
+    pub fn reverting(mut self, tx_hashes: Vec<H256>) -> Self {
+        self.reverting_tx_hashes = tx_hashes;
+        self
+    }
+}
+
+fn serialize_block_number<S: serde::Serializer>(block: &u64, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("0x{:x}", block))
+}
+
+fn deserialize_block_number<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    let value = String::deserialize(d)?;
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EvmError, RetryConfig};
+    use ethers::providers::{HttpClientError, JsonRpcError, ProviderError};
+    use std::time::Duration;
+
+    #[test]
+    fn test_error_display_and_kind() {
+        let error = EvmError::MempoolError("pool closed".to_string());
+        assert_eq!(error.to_string(), "Mempool error: pool closed");
+        assert_eq!(error.kind(), "mempool");
+        let error = EvmError::ListenerError("stream ended".to_string());
+        assert_eq!(error.to_string(), "Listener error: stream ended");
+        assert_eq!(error.kind(), "listener");
+        assert_eq!(EvmError::AaveError(String::new()).kind(), "aave");
+        let error =
+            EvmError::ExportInterrupted(42, Box::new(EvmError::IOError("disk full".to_string())));
+        assert_eq!(
+            error.to_string(),
+            "IO Error: disk full (42 records written)"
+        );
+        assert_eq!(error.kind(), "io");
+        let error = EvmError::IndexingInterrupted(
+            19_000_000,
+            Box::new(EvmError::RpcError(
+                "query returned more than 10000 results".to_string(),
+            )),
+        );
+        assert!(
+            error
+                .to_string()
+                .ends_with("(indexed up to block 19000000)")
+        );
+        assert_eq!(error.kind(), "rpc");
+    }
+
+    #[test]
+    fn test_retry_backoff_and_exhausted_error() {
+        let config = RetryConfig {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+        for (retry, full) in [(0, 100), (1, 200), (2, 300), (10, 300)] {
+            let delay = config.backoff(retry);
+            assert!(delay <= Duration::from_millis(full));
+            assert!(delay >= Duration::from_millis(full / 2));
+        }
+        let provider_error = |code: i64, message: &str| {
+            ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(
+                JsonRpcError {
+                    code,
+                    message: message.to_string(),
+                    data: None,
+                },
+            )))
+        };
+        let error = EvmError::rpc(
+            "Failed to get logs",
+            provider_error(429, "Too many requests"),
+        );
+        assert!(error.is_transient());
+        assert_eq!(
+            error.to_string(),
+            "RPC error: Failed to get logs: (code: 429, message: Too many requests, data: None)"
+        );
+        // A revert mentioning a rate limit is still a revert
+        let revert = provider_error(3, "execution reverted: 429 rate limit");
+        assert!(!EvmError::rpc("Call failed", revert).is_transient());
+        assert!(!EvmError::RpcError("502 Bad Gateway".to_string()).is_transient());
+        let error = EvmError::RetriesExhausted(3, Box::new(error.into_inner()));
+        assert_eq!(error.retries(), 3);
+        assert_eq!(error.kind(), "rpc");
+        assert!(error.to_string().ends_with("(gave up after 3 retries)"));
+    }
+
+    #[test]
+    fn test_classify_non_json_responses() {
+        // What the `Http` transport returns for a non-2xx response it cannot parse
+        let bad_body = |text: &str| {
+            let err = serde_json::from_str::<serde_json::Value>(text).unwrap_err();
+            let e = ProviderError::JsonRpcClientError(Box::new(HttpClientError::SerdeJson {
+                err,
+                text: text.to_string(),
+            }));
+            EvmError::rpc("Failed to get block", e)
+        };
+        assert!(bad_body("<html><h1>502 Bad Gateway</h1></html>").is_transient());
+        assert!(bad_body("Too Many Requests").is_transient());
+        assert!(bad_body("error code: 503").is_transient());
+        assert!(!bad_body("<html>404 page not found</html>").is_transient());
+        // Rate-limit errors missing the JSON-RPC `id` are still rate limits
+        let limited = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"limit exceeded"}}"#;
+        let e = ProviderError::JsonRpcClientError(Box::new(HttpClientError::SerdeJson {
+            err: serde_json::from_str::<u64>(limited).unwrap_err(),
+            text: limited.to_string(),
+        }));
+        assert!(EvmError::rpc("Failed to get logs", e).is_transient());
+        // A bad result from a healthy node carries no response body
+        let e = ProviderError::SerdeJson(serde_json::from_str::<u64>("\"0x429\"").unwrap_err());
+        assert!(!EvmError::rpc("Failed to get logs", e).is_transient());
+        let header = ProviderError::JsonRpcClientError(Box::new(HttpClientError::JsonRpcError(
+            JsonRpcError {
+                code: -32000,
+                message: "header not found".to_string(),
+                data: None,
+            },
+        )));
+        assert!(EvmError::rpc("Failed to get block", header).is_transient());
+    }
+}